@@ -0,0 +1,949 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_shell::{process::CommandEvent, ShellExt};
+use tauri_plugin_store::StoreExt;
+use tokio::sync::oneshot;
+
+// 引入事件缓冲器
+use crate::event_buffer::{BridgeEventData, EventBuffer};
+use crate::python_rpc::PythonRpcClient;
+use crate::{ApiHealthState, ApiProcessState};
+
+/// 单个监控窗口内允许的最大重启次数，超过后放弃自动恢复
+const MAX_RESTARTS_IN_WINDOW: u32 = 5;
+/// 重启次数计数的滑动窗口
+const RESTART_WINDOW: Duration = Duration::from_secs(10 * 60);
+/// 健康检查探测间隔
+const HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// 就绪探测的轮询间隔：进程刚拉起时 FastAPI 还没开始监听，需要比常规健康
+/// 检查更密集地重试
+const READINESS_POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// 就绪探测的总超时：超过这个时长还没探测到 `/health` 成功响应，就认为
+/// 启动失败，不再无限期挂起调用方
+const READINESS_TIMEOUT: Duration = Duration::from_secs(30);
+/// 选中端口的持久化 store 文件名（由 `tauri_plugin_store` 管理，落在应用
+/// 数据目录下），以及其中记录端口的键名
+const API_PORT_STORE_FILE: &str = "api_runtime.json";
+const API_PORT_STORE_KEY: &str = "api_port";
+
+/// 这个 sidecar 在 `service_controller::ServiceController` 里登记用的服务名；
+/// `pub(crate)` 是因为 `commands::api_status` 也要用它去查这一个服务的快照
+pub(crate) const PYTHON_API_SERVICE_ID: &str = "python-api";
+/// 初始重启退避时间，之后翻倍，直到达到上限
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// 从 `preferred` 开始探测一个可用的 TCP 端口：先尝试 `preferred` 本身，
+/// 绑不上（比如上一个实例的僵尸进程还占着、或者被别的服务占用）就依次往上
+/// 扫描，扫描区间内还是找不到就退化成绑定端口 0，交给操作系统分配一个
+/// 临时端口。绑定测试本身存在"探测后、真正监听前"被其他进程抢占的极小
+/// 窗口期，但这比硬编码端口导致启动静默失败要好得多。
+pub fn find_available_port(preferred: u16) -> u16 {
+    if std::net::TcpListener::bind(("127.0.0.1", preferred)).is_ok() {
+        return preferred;
+    }
+
+    println!("[API_STARTUP] 端口 {} 不可用，向上扫描空闲端口", preferred);
+    for candidate in preferred.saturating_add(1)..=preferred.saturating_add(1000) {
+        if std::net::TcpListener::bind(("127.0.0.1", candidate)).is_ok() {
+            println!("[API_STARTUP] 找到可用端口: {}", candidate);
+            return candidate;
+        }
+    }
+
+    match std::net::TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|listener| listener.local_addr())
+    {
+        Ok(addr) => {
+            println!("[API_STARTUP] 扫描范围内无可用端口，改用操作系统分配的端口: {}", addr.port());
+            addr.port()
+        }
+        Err(e) => {
+            eprintln!("[API_STARTUP] 连操作系统分配端口都失败了: {}，继续使用 {}", e, preferred);
+            preferred
+        }
+    }
+}
+
+/// 把这次实际选中的端口落盘到 `tauri_plugin_store`，供下次启动时参考（见
+/// `find_available_port` 的 `preferred` 入参）、或供诊断命令在 sidecar 还
+/// 没走到注册到 `service_controller` 那一步之前也能查到"用的是哪个端口"。
+/// 打不开 store 文件/写入失败都只是记一条日志，不影响本次启动流程。
+fn persist_selected_port(app_handle: &AppHandle, port: u16) {
+    match app_handle.store(API_PORT_STORE_FILE) {
+        Ok(store) => {
+            store.set(API_PORT_STORE_KEY, serde_json::json!(port));
+            if let Err(e) = store.save() {
+                eprintln!("[API_STARTUP] 持久化选中端口失败: {}", e);
+            }
+        }
+        Err(e) => {
+            eprintln!("[API_STARTUP] 打开端口持久化 store 失败: {}", e);
+        }
+    }
+}
+
+/// 解析Python stdout输出中的桥接事件
+///
+/// 支持的格式：
+/// EVENT_NOTIFY_JSON:{"event":"event-name","payload":{...}}
+///
+/// 返回解析后的事件数据，如果不是桥接事件则返回None
+fn parse_bridge_event(line: &str) -> Option<BridgeEventData> {
+    let line = line.trim();
+
+    // 检查新格式：EVENT_NOTIFY_JSON:
+    if let Some(json_part) = line.strip_prefix("EVENT_NOTIFY_JSON:") {
+        match serde_json::from_str::<BridgeEventData>(json_part) {
+            Ok(event_data) => {
+                return Some(event_data);
+            }
+            Err(e) => {
+                eprintln!("解析桥接事件JSON失败: {} - 原始内容: {}", e, json_part);
+                return None;
+            }
+        }
+    }
+
+    // 不是桥接事件
+    None
+}
+
+/// 结构化日志行：`LOG_JSON:{"level":"INFO|WARN|ERROR","msg":"...","ts":...}`。
+#[derive(Debug, serde::Deserialize)]
+struct StructuredLogLine {
+    level: String,
+    msg: String,
+    #[serde(default)]
+    #[allow(dead_code)] // 暂时只用于落盘时原样记录，不参与路由判断
+    ts: Option<i64>,
+}
+
+/// 解析Python stdout/stderr输出中的结构化日志行。命中就返回带级别的日志，
+/// 调用方据此决定发 `api-log` 还是 `api-error`，不再靠扫描
+/// `"error"`/`"Failed"`/`"Traceback"` 这类子串——文件路径、变量名里随便
+/// 就可能带上这些词，子串匹配天然就会误判。解析不出结构化格式的行原样
+/// 返回 `None`，退回旧的子串启发式（见 `is_error_like_line`）。
+fn parse_structured_log(line: &str) -> Option<StructuredLogLine> {
+    let json_part = line.trim().strip_prefix("LOG_JSON:")?;
+    match serde_json::from_str::<StructuredLogLine>(json_part) {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            eprintln!("解析结构化日志JSON失败: {} - 原始内容: {}", e, json_part);
+            None
+        }
+    }
+}
+
+/// 旧的子串启发式，仅作为没有 `LOG_JSON:` 前缀的非结构化行的兜底判断。
+fn is_error_like_line(line: &str) -> bool {
+    line.contains("error")
+        || line.contains("Error")
+        || line.contains("ERROR")
+        || line.contains("failed")
+        || line.contains("Failed")
+        || line.contains("FAILED")
+        || line.contains("exception")
+        || line.contains("Exception")
+        || line.contains("EXCEPTION")
+        || line.contains("traceback")
+        || line.contains("Traceback")
+}
+
+/// 对一行 sidecar 输出做分类（是否当作错误展示给用户）并落盘，供
+/// stdout/stderr 两处调用复用：优先按 `LOG_JSON:` 结构化级别判断，解析
+/// 不出结构化格式才退回 `is_error_like_line` 子串启发式。返回值表示这行
+/// 是否应该走 `api-error`（而不是 `api-log`）。
+fn classify_and_sink_line(
+    line: &str,
+    stream: &str,
+    log_sink: Option<&crate::log_sink::FileLogSink>,
+) -> bool {
+    let (level, is_error) = match parse_structured_log(line) {
+        Some(structured) => {
+            let is_error = structured.level.eq_ignore_ascii_case("ERROR");
+            if let Some(sink) = log_sink {
+                sink.write_line(&structured.level, &structured.msg);
+            }
+            return is_error;
+        }
+        None => {
+            let is_error = is_error_like_line(line);
+            (if is_error { "ERROR" } else { "INFO" }, is_error)
+        }
+    };
+
+    if let Some(sink) = log_sink {
+        sink.write_line(level, &format!("[{}] {}", stream, line));
+    }
+
+    is_error
+}
+
+// Helper function to start the Python API service
+// 返回一个oneshot channel的接收端，当API成功启动且可访问后会发送信号
+pub fn start_python_api(
+    app_handle: AppHandle,
+    api_state_mutex: Arc<Mutex<ApiProcessState>>,
+) -> oneshot::Receiver<bool> {
+    // 创建一对channel，用于通知API已准备好
+    let (tx, rx) = oneshot::channel();
+
+    // oneshot发送端不能克隆，但我们可以在开始健康检查前保存它
+    let tx = std::sync::Arc::new(std::sync::Mutex::new(Some(tx)));
+
+    // 创建事件缓冲器
+    let event_buffer = Arc::new(EventBuffer::new(app_handle.clone()));
+    // 注册到 AppState，供优雅关闭流程在进程退出前排空未发送的事件
+    app_handle
+        .state::<crate::AppState>()
+        .set_event_buffer(event_buffer.clone());
+
+    // 创建RPC客户端（见 python_rpc 模块），注册到 AppState 供命令层调用
+    // `call_python`；每次 `start_python_api`（包括自动重启）都重新创建一个，
+    // 挂起请求表不会跨进程重启保留——旧进程的回复不会再送达。
+    let python_rpc = PythonRpcClient::new(api_state_mutex.clone());
+    app_handle
+        .state::<crate::AppState>()
+        .set_python_rpc(python_rpc.clone());
+
+    // sidecar 日志落盘到应用数据目录下的 logs/sidecar.log，供事后排查问题；
+    // 拿不到应用数据目录或打不开文件就退化为只保留console输出，不阻塞启动
+    let log_sink: Option<Arc<crate::log_sink::FileLogSink>> = app_handle
+        .path()
+        .app_data_dir()
+        .ok()
+        .and_then(|dir| crate::log_sink::FileLogSink::open(dir.join("logs").join("sidecar.log")))
+        .map(Arc::new);
+
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit("api-starting", Some(true));
+    }
+
+    tauri::async_runtime::spawn(async move {
+        let port_to_use: u16;
+        let host_to_use: String;
+        let db_path_to_use: String;
+
+        {
+            // Scope to ensure lock is released
+            let api_state_guard = api_state_mutex.lock().unwrap();
+            port_to_use = api_state_guard.port;
+            host_to_use = api_state_guard.host.clone();
+            db_path_to_use = api_state_guard.db_path.clone();
+        }
+
+        // 把这次实际选中的端口落盘，供下次启动时读取、或者诊断命令在
+        // sidecar 还没就绪时也能查到"这次用的是哪个端口"
+        persist_selected_port(&app_handle, port_to_use);
+
+        // 获取当前工作目录，用于调试
+        let current_dir = std::env::current_dir()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| "无法获取当前工作目录".to_string());
+        println!("当前工作目录: {}", current_dir);
+
+        // According to dev/production environment, choose different venv_parent_path: ../api or /path/to/app/app_data_dir
+        let venv_parent_path = if cfg!(debug_assertions) {
+            // 在当前工作目录的上一级目录中寻找api文件夹
+            match std::env::current_dir() {
+                Ok(mut path) => {
+                    path.pop(); // 移动到上一级目录
+                    path.pop(); // 移动到上一级目录
+                    path.push("core");
+                    path
+                }
+                Err(e) => {
+                    eprintln!("无法获取当前工作目录: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window
+                                .emit("api-error", Some(format!("无法获取当前工作目录: {}", e)));
+                        }
+                    }
+                    return;
+                }
+            }
+        } else {
+            match app_handle.path().app_data_dir() {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("无法获取应用数据目录: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window
+                                .emit("api-error", Some(format!("无法获取应用数据目录: {}", e)));
+                        }
+                    }
+                    return;
+                }
+            }
+        };
+        println!("venv_parent_path: {:?}", venv_parent_path);
+
+        // 如果是生产环境，复制BaseDirectory::Resource/core/pyproject.toml到app_data_dir；
+        // 开发环境里 pyproject.toml 本来就在 venv_parent_path 下，两种情况都先确定
+        // 出"资源侧"的源文件路径，用于下面算哈希判断是否需要真正同步
+        let pyproject_src_path = if !cfg!(debug_assertions) {
+            let resource_api_path = match app_handle.path().resolve("core", BaseDirectory::Resource)
+            {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("无法解析资源路径: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window
+                                .emit("api-error", Some(format!("无法解析资源路径: {}", e)));
+                        }
+                    }
+                    return;
+                }
+            };
+            resource_api_path.join("pyproject.toml")
+        } else {
+            venv_parent_path.join("pyproject.toml")
+        };
+
+        let manifest_path = crate::sync_manifest::manifest_path(&venv_parent_path);
+        let pyproject_hash = crate::file_scanner::hash_file_contents(&pyproject_src_path);
+        let skip_sync = pyproject_hash
+            .as_deref()
+            .map(|hash| crate::sync_manifest::is_up_to_date(&manifest_path, hash))
+            .unwrap_or(false);
+
+        if skip_sync {
+            println!("[API_STARTUP] pyproject.toml 哈希未变化，跳过复制与 uv sync，直接运行");
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.emit(
+                    "api-log",
+                    Some(
+                        "Python environment unchanged, skipping uv sync (cached fast path)"
+                            .to_string(),
+                    ),
+                );
+            }
+        } else {
+            if !cfg!(debug_assertions) {
+                let pyproject_dest_path = venv_parent_path.join("pyproject.toml");
+                println!("pyproject_src_path: {:?}", pyproject_src_path);
+                println!("pyproject_dest_path: {:?}", pyproject_dest_path);
+                // 总是复制文件，以便在部署新版本后能自动更新虚拟环境
+                if let Err(e) = std::fs::copy(&pyproject_src_path, &pyproject_dest_path) {
+                    eprintln!("复制pyproject.toml失败: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        let _ = window.emit(
+                            "api-error",
+                            Some(format!("duplicate pyproject.toml failed: {}", e)),
+                        );
+                    }
+                    return;
+                }
+            }
+
+            // 创建或更新虚拟环境
+            let sidecar_command = app_handle.shell().sidecar("uv").unwrap().args([
+                "sync",
+                "--index-strategy",
+                "unsafe-best-match",
+                "--no-progress",
+                "--directory",
+                venv_parent_path.to_str().unwrap(),
+            ]);
+            println!("Running command: {:?}", sidecar_command);
+
+            // 捕获 uv sync 的输出并发送到前端
+            match sidecar_command.spawn() {
+                Ok((mut sync_rx, _sync_child)) => {
+                    println!("uv sync 进程已启动");
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        let _ = window.emit(
+                            "api-log",
+                            Some("Syncing Python virtual environment (full sync)...".to_string()),
+                        );
+                    }
+
+                    // 监听 uv sync 的输出
+                    let app_handle_for_sync = app_handle.clone();
+                    let sync_task = tauri::async_runtime::spawn(async move {
+                        let mut sync_succeeded = false;
+                        while let Some(event) = sync_rx.recv().await {
+                            // 检查窗口是否仍然存在，避免向已销毁的窗口发送事件
+                            if let Some(window) = app_handle_for_sync.get_webview_window("main") {
+                                // 检查窗口是否真的可用（可能已经被销毁但引用仍存在）
+                                if window.is_visible().unwrap_or(false) {
+                                    match event {
+                                        CommandEvent::Stdout(line) => {
+                                            let line_str = String::from_utf8_lossy(&line);
+                                            if window.is_visible().unwrap_or(false) {
+                                                let _ = window
+                                                    .emit("api-log", Some(line_str.to_string()));
+                                            }
+                                        }
+                                        CommandEvent::Stderr(line) => {
+                                            let line_str = String::from_utf8_lossy(&line);
+                                            if line_str.contains("error")
+                                                || line_str.contains("Error")
+                                                || line_str.contains("ERROR")
+                                                || line_str.contains("failed")
+                                                || line_str.contains("Failed")
+                                                || line_str.contains("FAILED")
+                                            {
+                                                if window.is_visible().unwrap_or(false) {
+                                                    let _ = window.emit(
+                                                        "api-error",
+                                                        Some(line_str.to_string()),
+                                                    );
+                                                }
+                                            } else {
+                                                if window.is_visible().unwrap_or(false) {
+                                                    let _ = window
+                                                        .emit("api-log", Some(line_str.to_string()));
+                                                }
+                                            }
+                                        }
+                                        CommandEvent::Terminated(status) => {
+                                            println!(
+                                                "uv sync 进程终止，状态码: {}",
+                                                status.code.unwrap_or(-1)
+                                            );
+                                            if status.code.unwrap_or(-1) != 0 {
+                                                let _ = window.emit(
+                                                    "api-error",
+                                                    Some(format!(
+                                                        "uv sync failed，exit code: {}",
+                                                        status.code.unwrap_or(-1)
+                                                    )),
+                                                );
+                                            } else {
+                                                sync_succeeded = true;
+                                                let _ = window.emit(
+                                                    "api-log",
+                                                    Some(
+                                                        "Python virtual environment sync completed"
+                                                            .to_string(),
+                                                    ),
+                                                );
+                                            }
+                                            break;
+                                        }
+                                        _ => {}
+                                    }
+                                } else {
+                                    println!("主窗口不可见，停止发送 uv sync 日志事件");
+                                    break;
+                                }
+                            } else {
+                                println!("主窗口不存在，停止发送 uv sync 日志事件");
+                                break;
+                            }
+                        }
+                        sync_succeeded
+                    });
+
+                    // 等待 uv sync 完成；成功（exit code 0）才更新清单，避免把一次
+                    // 失败的同步错误地标记成"已是最新"从而让下次启动误跳过
+                    let sync_succeeded = sync_task.await.unwrap_or(false);
+                    if sync_succeeded {
+                        if let Some(hash) = pyproject_hash.as_deref() {
+                            crate::sync_manifest::write(&manifest_path, hash);
+                        }
+                    }
+                }
+                Err(e) => {
+                    eprintln!("启动 uv sync 失败: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ =
+                                window.emit("api-error", Some(format!("uv sync failed: {}", e)));
+                        }
+                    }
+                    return;
+                }
+            }
+        }
+
+        // 通过uv运行main.py
+        // 如果是开发环境main.py在../core/main.py，否则在BaseDirectory::Resource/core/main.py
+        let script_path = if cfg!(debug_assertions) {
+            venv_parent_path.join("main.py")
+        } else {
+            match app_handle
+                .path()
+                .resolve("core/main.py", BaseDirectory::Resource)
+            {
+                Ok(path) => path,
+                Err(e) => {
+                    eprintln!("无法解析main.py路径: {}", e);
+                    if let Some(window) = app_handle.get_webview_window("main") {
+                        if window.is_visible().unwrap_or(false) {
+                            let _ = window
+                                .emit("api-error", Some(format!("无法解析main.py路径: {}", e)));
+                        }
+                    }
+                    return;
+                }
+            }
+        };
+        println!("main_py_path: {:?}", script_path);
+
+        // 通过uv运行Python脚本
+        let sidecar_command = app_handle.shell().sidecar("uv").unwrap().args([
+            "run",
+            "--directory",
+            venv_parent_path.to_str().unwrap(),
+            script_path.to_str().unwrap(),
+            "--host",
+            host_to_use.as_str(),
+            "--port",
+            port_to_use.to_string().as_str(),
+            "--db-path",
+            db_path_to_use.as_str(),
+        ]);
+
+        println!("Running command: {:?}", sidecar_command);
+
+        match sidecar_command.spawn() {
+            Ok((mut rx, child)) => {
+                let child_pid = child.pid();
+                let configured_limits = {
+                    // Scope to ensure lock is released
+                    let mut api_state_guard = api_state_mutex.lock().unwrap();
+                    api_state_guard.pid = Some(child_pid);
+                    api_state_guard.process_child = Some(child);
+                    api_state_guard.health = ApiHealthState::Running;
+                    api_state_guard.resource_limits.clone()
+                };
+                // 进程已经拉起、PID已知，尽力而为地对它施加资源限制（见
+                // resource_limits 模块说明：sidecar API 不支持在spawn前挂钩，
+                // 只能事后设置）
+                crate::resource_limits::apply_best_effort(child_pid, &configured_limits);
+
+                // 把这个sidecar登记到统一的服务控制器（见 service_controller
+                // 模块）：起停/就绪探测的细节仍然留在本模块，这里只是让前端/
+                // 优雅关闭流程能统一枚举到它，而不用单独认得一个 AppState 字段
+                let service_controller = app_handle.state::<crate::AppState>().service_controller();
+                service_controller.register(
+                    PYTHON_API_SERVICE_ID,
+                    host_to_use.clone(),
+                    port_to_use,
+                    crate::service_controller::RestartPolicy::ExponentialBackoff,
+                    api_state_mutex.clone(),
+                );
+                println!(
+                    "API服务已启动. Port: {}, Host: {}",
+                    port_to_use, host_to_use
+                );
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit(
+                        "api-log",
+                        Some("Starting Python API service (uv run)...".to_string()),
+                    );
+                    let _ = window.emit(
+                        "api-log",
+                        Some(format!(
+                            "Initializing FastAPI server on {}:{}",
+                            host_to_use, port_to_use
+                        )),
+                    );
+                }
+
+                let app_handle_clone = app_handle.clone();
+                let api_state_mutex_clone = api_state_mutex.clone();
+
+                // 监听API进程事件
+                let event_buffer_clone = event_buffer.clone();
+                let python_rpc_clone = python_rpc.clone();
+                let log_sink_clone = log_sink.clone();
+                let configured_limits_clone = configured_limits.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(event) = rx.recv().await {
+                        if let Some(window) = app_handle_clone.get_webview_window("main") {
+                            // 检查窗口是否仍然可见/有效
+                            if !window.is_visible().unwrap_or(false) {
+                                println!("FastAPI事件处理: 窗口已不可见，停止发送事件");
+                                break;
+                            }
+
+                            match event {
+                                CommandEvent::Stdout(line) => {
+                                    let line_str = String::from_utf8_lossy(&line);
+
+                                    // RPC响应优先处理：命中就直接路由给挂起的
+                                    // call_python 调用，不再往下当桥接事件/
+                                    // 普通日志处理
+                                    if python_rpc_clone.try_handle_response_line(&line_str) {
+                                        continue;
+                                    }
+
+                                    // 检查是否是桥接事件通知
+                                    if let Some(event_data) = parse_bridge_event(&line_str) {
+                                        // 使用事件缓冲器处理桥接事件
+                                        println!(
+                                            "收到桥接事件: {} (通过缓冲器处理)",
+                                            event_data.event
+                                        );
+                                        event_buffer_clone.handle_event(event_data).await;
+                                    } else {
+                                        let is_error = classify_and_sink_line(
+                                            &line_str,
+                                            "stdout",
+                                            log_sink_clone.as_deref(),
+                                        );
+                                        if window.is_visible().unwrap_or(false) {
+                                            let target = if is_error { "api-error" } else { "api-log" };
+                                            let _ = window.emit(target, Some(line_str.to_string()));
+                                        }
+                                    }
+                                }
+                                CommandEvent::Stderr(line) => {
+                                    let line_str = String::from_utf8_lossy(&line);
+                                    let is_error = classify_and_sink_line(
+                                        &line_str,
+                                        "stderr",
+                                        log_sink_clone.as_deref(),
+                                    );
+                                    if window.is_visible().unwrap_or(false) {
+                                        let target = if is_error { "api-error" } else { "api-log" };
+                                        let _ = window.emit(target, Some(line_str.to_string()));
+                                    }
+                                }
+                                CommandEvent::Error(err) => {
+                                    eprintln!("Python API进程错误: {}", err);
+                                    if window.is_visible().unwrap_or(false) {
+                                        let _ = window.emit("api-error", Some(err.to_string()));
+                                    }
+                                    mark_process_dead(&app_handle_clone, &api_state_mutex_clone);
+                                }
+                                CommandEvent::Terminated(status) => {
+                                    println!(
+                                        "API进程已终止，状态码: {}",
+                                        status.code.unwrap_or(-1)
+                                    );
+                                    // 配置过资源限制时，区分"被限制杀死"和普通崩溃，
+                                    // 让重启监督者和用户都能看出真正原因
+                                    let resource_kill_reason = crate::resource_limits::classify_termination(
+                                        &configured_limits_clone,
+                                        status.signal,
+                                    );
+                                    if window.is_visible().unwrap_or(false) {
+                                        if let Some(reason) = resource_kill_reason {
+                                            let _ = window.emit(
+                                                "api-error",
+                                                Some(format!(
+                                                    "API process was killed by a resource limit: {}",
+                                                    reason
+                                                )),
+                                            );
+                                        } else {
+                                            let _ = window.emit(
+                                                "api-log",
+                                                Some(format!(
+                                                    "API process terminated with exit code: {}",
+                                                    status.code.unwrap_or(-1)
+                                                )),
+                                            );
+                                        }
+                                    }
+                                    mark_process_dead(&app_handle_clone, &api_state_mutex_clone);
+                                    maybe_trigger_restart(
+                                        app_handle_clone.clone(),
+                                        api_state_mutex_clone.clone(),
+                                    );
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                });
+
+                // 真正的就绪握手：进程拉起不等于API可路由，轮询 /health 拿到
+                // 首个2xx才通过 tx 通知调用方；超时则发送失败信号，避免调用方
+                // 永远挂在 rx.await 上
+                spawn_readiness_probe(
+                    app_handle.clone(),
+                    host_to_use.clone(),
+                    port_to_use,
+                    tx.clone(),
+                );
+
+                // 启动后台健康检查监督循环，持续轮询 /health，检测挂起但未退出的进程
+                spawn_health_supervisor(app_handle.clone(), api_state_mutex.clone());
+            }
+            Err(e) => {
+                eprintln!("启动API服务失败: {}", e);
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    if window.is_visible().unwrap_or(false) {
+                        let _ = window.emit("api-error", Some(format!("启动API服务失败: {}", e)));
+                    }
+                }
+                // API启动失败，发送失败信号
+                if let Some(sender) = tx.lock().unwrap().take() {
+                    let _ = sender.send(false);
+                }
+            }
+        }
+    });
+
+    rx // 返回接收端
+}
+
+/// 优雅停止当前 Python API 子进程，不重新拉起。直接复用
+/// `service_controller::ServiceController::stop`（终止整棵进程树 + 复位
+/// `ApiProcessState`），与 `ApiProcessManager::cleanup` 在应用退出时做的事
+/// 情是同一套底层逻辑，区别只是这里不连带清理 `ApiProcessManager` 自身持有
+/// 的其它资源——调用方（`commands::stop_api`）只是想让用户手动关掉这一个
+/// sidecar，而不是整个应用正在退出。
+pub fn stop_python_api(app_handle: &AppHandle) {
+    app_handle
+        .state::<crate::AppState>()
+        .service_controller()
+        .stop(PYTHON_API_SERVICE_ID);
+}
+
+/// 重启 Python API 子进程：先按 `stop_python_api` 同样的方式终止现有进程
+/// 树，再复用 `start_python_api` 重新拉起并等待它的就绪 oneshot。
+/// `api_state_mutex` 重启前后是同一个实例，`host`/`port`/`db_path` 等配置
+/// 字段不变，调用方（`commands::restart_api`）不需要关心新进程的 PID，
+/// 只要这个函数返回 `Ok(())` 就代表 `/health` 已经探测通过、可以路由了。
+pub async fn restart_python_api(
+    app_handle: AppHandle,
+    api_state_mutex: Arc<Mutex<ApiProcessState>>,
+) -> Result<(), String> {
+    stop_python_api(&app_handle);
+    let ready_rx = start_python_api(app_handle, api_state_mutex);
+    match ready_rx.await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("API 服务重启失败：就绪探测未通过".to_string()),
+        Err(_) => Err("API 服务重启失败：启动流程在就绪探测前就退出了".to_string()),
+    }
+}
+
+/// 将API进程标记为已死亡（收到 Terminated/Error 事件时调用）
+fn mark_process_dead(app_handle: &AppHandle, api_state_mutex: &Arc<Mutex<ApiProcessState>>) {
+    if let Ok(mut state) = api_state_mutex.lock() {
+        state.reset_after_stop();
+    }
+    app_handle
+        .state::<crate::AppState>()
+        .service_controller()
+        .mark_status(PYTHON_API_SERVICE_ID, crate::service_controller::ServiceStatus::Crashed);
+}
+
+/// 进程拉起之后轮询 `/health`，首次拿到2xx响应才算"真正就绪"（可路由），
+/// 并通过就绪oneshot通知调用方；在 `READINESS_TIMEOUT` 内始终没探测成功就
+/// 发送失败信号并发出 `api-error`，否则调用方会永远挂在 `rx.await` 上。
+fn spawn_readiness_probe(
+    app_handle: AppHandle,
+    host: String,
+    port: u16,
+    tx: Arc<Mutex<Option<oneshot::Sender<bool>>>>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+        let url = format!("http://{}:{}/health", host, port);
+        let deadline = Instant::now() + READINESS_TIMEOUT;
+
+        loop {
+            if let Ok(resp) = client.get(&url).timeout(Duration::from_secs(2)).send().await {
+                if resp.status().is_success() {
+                    println!("[API_STARTUP] 就绪探测成功，API已可路由: {}", url);
+                    app_handle
+                        .state::<crate::AppState>()
+                        .service_controller()
+                        .mark_status(PYTHON_API_SERVICE_ID, crate::service_controller::ServiceStatus::Running);
+                    if let Some(sender) = tx.lock().unwrap().take() {
+                        let _ = sender.send(true);
+                    }
+                    return;
+                }
+            }
+
+            if Instant::now() >= deadline {
+                eprintln!(
+                    "[API_STARTUP] 等待API就绪超时（{}秒），放弃探测: {}",
+                    READINESS_TIMEOUT.as_secs(),
+                    url
+                );
+                if let Some(sender) = tx.lock().unwrap().take() {
+                    let _ = sender.send(false);
+                }
+                if let Some(window) = app_handle.get_webview_window("main") {
+                    let _ = window.emit(
+                        "api-error",
+                        Some(format!(
+                            "API启动超时（{}秒内未通过健康检查）",
+                            READINESS_TIMEOUT.as_secs()
+                        )),
+                    );
+                }
+                return;
+            }
+
+            tokio::time::sleep(READINESS_POLL_INTERVAL).await;
+        }
+    });
+}
+
+/// 周期性对 `/health` 端点发起探测，检测"进程存在但已经失去响应"的情况，
+/// 这类挂起无法靠 `CommandEvent::Terminated` 发现，必须单独轮询；同时
+/// 每轮也用记录下来的 PID 直接探测进程是否还存活（见
+/// `process_tree::is_alive`），而不是只看 `process_child` 这个
+/// `CommandChild` 句柄还在不在——句柄本身不会在子进程被系统回收时自动
+/// 失效，如果子进程是被外部（比如用户在终端里 kill 掉、或者被 OOM
+/// killer 回收）偷偷摸掉的，只靠句柄是探测不到的，PID 存活检查能在下一轮
+/// 就立刻发现，不需要等够 `MAX_CONSECUTIVE_FAILURES` 次健康检查失败。
+fn spawn_health_supervisor(app_handle: AppHandle, api_state_mutex: Arc<Mutex<ApiProcessState>>) {
+    tauri::async_runtime::spawn(async move {
+        let client = reqwest::Client::new();
+        let mut consecutive_failures = 0u32;
+        const MAX_CONSECUTIVE_FAILURES: u32 = 3;
+
+        loop {
+            tokio::time::sleep(HEALTH_CHECK_INTERVAL).await;
+
+            let (host, port, pid, still_tracked) = {
+                let guard = api_state_mutex.lock().unwrap();
+                (
+                    guard.host.clone(),
+                    guard.port,
+                    guard.pid,
+                    guard.process_child.is_some(),
+                )
+            };
+
+            if !still_tracked {
+                // 进程已经被标记为死亡（由 Terminated/Error 事件处理），
+                // 对应的重启逻辑由 maybe_trigger_restart 负责，这个探测循环可以退出。
+                println!("[HEALTH_SUPERVISOR] 进程已不在跟踪中，健康检查循环退出");
+                return;
+            }
+
+            if let Some(pid) = pid {
+                if !crate::process_tree::is_alive(pid) {
+                    eprintln!(
+                        "[HEALTH_SUPERVISOR] PID {} 已不存在（被外部终止或被系统回收），立即触发重启",
+                        pid
+                    );
+                    mark_process_dead(&app_handle, &api_state_mutex);
+                    maybe_trigger_restart(app_handle.clone(), api_state_mutex.clone());
+                    return;
+                }
+            }
+
+            let url = format!("http://{}:{}/health", host, port);
+            match client
+                .get(&url)
+                .timeout(Duration::from_secs(2))
+                .send()
+                .await
+            {
+                Ok(resp) if resp.status().is_success() => {
+                    consecutive_failures = 0;
+                }
+                _ => {
+                    consecutive_failures += 1;
+                    println!(
+                        "[HEALTH_SUPERVISOR] 健康检查失败 ({}/{})",
+                        consecutive_failures, MAX_CONSECUTIVE_FAILURES
+                    );
+                    if consecutive_failures >= MAX_CONSECUTIVE_FAILURES {
+                        eprintln!("[HEALTH_SUPERVISOR] API连续多次健康检查失败，判定为挂起，触发重启");
+                        mark_process_dead(&app_handle, &api_state_mutex);
+                        maybe_trigger_restart(app_handle.clone(), api_state_mutex.clone());
+                        return;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// 在检测到API死亡后决定是否重启：用一个环形缓冲记录最近几次重启的时间戳，
+/// 裁掉滑动窗口外的旧记录后，如果窗口内的重启次数达到上限就放弃自动恢复
+/// 并发出终态的 `api-crashed` 事件；否则按指数退避重新调用 `start_python_api`，
+/// 并发出 `api-restarting` 事件。
+fn maybe_trigger_restart(app_handle: AppHandle, api_state_mutex: Arc<Mutex<ApiProcessState>>) {
+    let (restart_count, backoff) = {
+        let mut guard = api_state_mutex.lock().unwrap();
+        let now = Instant::now();
+
+        // 裁掉滑动窗口之外的旧时间戳，环形缓冲因此只保留近期可能计入限流的条目
+        while let Some(oldest) = guard.restart_timestamps.front() {
+            if now.duration_since(*oldest) > RESTART_WINDOW {
+                guard.restart_timestamps.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        if guard.restart_timestamps.len() as u32 >= MAX_RESTARTS_IN_WINDOW {
+            guard.health = ApiHealthState::Failed;
+            (guard.restart_timestamps.len(), None)
+        } else {
+            guard.restart_timestamps.push_back(now);
+            guard.health = ApiHealthState::Restarting;
+
+            let restart_count = guard.restart_timestamps.len();
+            let backoff = std::cmp::min(
+                INITIAL_BACKOFF * 2u32.pow((restart_count as u32).saturating_sub(1)),
+                MAX_BACKOFF,
+            );
+            (restart_count, Some(backoff))
+        }
+    };
+
+    let backoff = match backoff {
+        Some(b) => b,
+        None => {
+            eprintln!(
+                "[HEALTH_SUPERVISOR] 达到 {} 分钟内最多 {} 次重启上限（环形缓冲记录了 {} 次），放弃自动恢复",
+                RESTART_WINDOW.as_secs() / 60,
+                MAX_RESTARTS_IN_WINDOW,
+                restart_count
+            );
+            let reason = "API进程反复崩溃，已超过自动重启上限，请手动重启应用".to_string();
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.emit("api-crashed", Some(reason.clone()));
+            }
+            // 记录到生命周期事件存储：这是终态（不会再自动重启），供晚注册
+            // 监听器的窗口通过 sync_lifecycle 也能知道 API 已经彻底放弃恢复
+            app_handle
+                .state::<crate::AppState>()
+                .lifecycle_events()
+                .record_and_emit(&app_handle, "api-fatal", serde_json::json!(reason));
+            return;
+        }
+    };
+
+    println!(
+        "[HEALTH_SUPERVISOR] 第 {} 次自动重启，{:?} 后执行",
+        restart_count, backoff
+    );
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit("api-restarting", Some(restart_count));
+    }
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(backoff).await;
+
+        // 端口可能在上次失败后仍然绑不上（对方没有真正退出、或者被别的服务
+        // 抢走了），重启前重新探测一次，绑不上就换一个新端口，而不是无脑
+        // 重复复用同一个可能一直失败的端口
+        {
+            let mut guard = api_state_mutex.lock().unwrap();
+            if std::net::TcpListener::bind(("127.0.0.1", guard.port)).is_err() {
+                let new_port = find_available_port(guard.port);
+                println!(
+                    "[HEALTH_SUPERVISOR] 端口 {} 仍不可用，重启改用新端口 {}",
+                    guard.port, new_port
+                );
+                guard.port = new_port;
+            }
+        }
+
+        println!("[HEALTH_SUPERVISOR] 正在重新启动Python API...");
+        let _ = start_python_api(app_handle.clone(), api_state_mutex.clone());
+    });
+}
@@ -0,0 +1,112 @@
+//! 手写的 ZIP 中央目录读取，用于把 zip/jar/docx/xlsx/pptx 这类 ZIP 容器
+//! 归档的内部成员列举出来（文件名 + 解压后大小），供 `FileMonitor` 为每个
+//! 成员发送一条虚拟子文件的 `FileMetadata`。
+//!
+//! 这仓库到现在都没有 Cargo.toml，没法引入 `zip` 这种专门的归档解压 crate
+//! （参见 `integrity_check.rs`/`payload_compression.rs` 用手写格式逻辑替代
+//! 新依赖的先例）；好在这里只需要成员清单，不需要真正解压内容，用
+//! `integrity_check::check_zip` 同一套手写中央目录定位逻辑就够了。
+
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// 视为归档、值得展开内部成员的扩展名（均为 ZIP 容器：jar 是 ZIP，
+/// docx/xlsx/pptx 是内部按 Office Open XML 约定组织的 ZIP 容器）。
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip", "jar", "docx", "xlsx", "pptx"];
+
+/// 归档内的一个成员条目。
+#[derive(Debug, Clone)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub uncompressed_size: u64,
+}
+
+/// 列举 `path` 归档内的所有成员（目录条目除外）。任何一项 zip 炸弹防护
+/// 阈值（成员数 `max_members`、解压后总大小 `max_total_uncompressed_bytes`）
+/// 被突破，或者中央目录结构本身解析不出来，都返回 `None`——调用方把这种
+/// 情况当作"不展开这个归档"处理，而不是尝试截断到阈值为止（截断会让使用者
+/// 误以为归档只有这么多内容）。
+pub fn list_members(
+    path: &Path,
+    max_members: u32,
+    max_total_uncompressed_bytes: u64,
+) -> Option<Vec<ArchiveMember>> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let file_len = file.metadata().ok()?.len();
+    if file_len < 22 {
+        return None;
+    }
+
+    // EOCD 记录定长部分 22 字节，后面还可能跟一段最长 65535 字节的注释；
+    // 往回读这段范围找签名，和 `integrity_check::check_zip` 同样的做法。
+    let scan_len = file_len.min(22 + 65535);
+    file.seek(SeekFrom::End(-(scan_len as i64))).ok()?;
+    let mut tail = vec![0u8; scan_len as usize];
+    file.read_exact(&mut tail).ok()?;
+
+    const EOCD_SIG: [u8; 4] = [0x50, 0x4B, 0x05, 0x06];
+    let eocd_pos = tail.windows(4).rposition(|w| w == EOCD_SIG)?;
+    let eocd = &tail[eocd_pos..];
+    if eocd.len() < 22 {
+        return None;
+    }
+
+    let entry_count = u16::from_le_bytes([eocd[10], eocd[11]]) as u32;
+    let cd_size = u32::from_le_bytes([eocd[12], eocd[13], eocd[14], eocd[15]]) as u64;
+    let cd_offset = u32::from_le_bytes([eocd[16], eocd[17], eocd[18], eocd[19]]) as u64;
+    if cd_offset + cd_size > file_len {
+        return None;
+    }
+    if entry_count > max_members {
+        return None;
+    }
+
+    file.seek(SeekFrom::Start(cd_offset)).ok()?;
+    let mut cd_buf = vec![0u8; cd_size as usize];
+    file.read_exact(&mut cd_buf).ok()?;
+
+    const CENTRAL_DIR_SIG: [u8; 4] = [0x50, 0x4B, 0x01, 0x02];
+    let mut members = Vec::with_capacity(entry_count as usize);
+    let mut total_uncompressed: u64 = 0;
+    let mut offset = 0usize;
+
+    for _ in 0..entry_count {
+        if offset + 46 > cd_buf.len() {
+            break;
+        }
+        let entry = &cd_buf[offset..];
+        if entry[0..4] != CENTRAL_DIR_SIG {
+            break;
+        }
+
+        let uncompressed_size =
+            u32::from_le_bytes([entry[24], entry[25], entry[26], entry[27]]) as u64;
+        let name_len = u16::from_le_bytes([entry[28], entry[29]]) as usize;
+        let extra_len = u16::from_le_bytes([entry[30], entry[31]]) as usize;
+        let comment_len = u16::from_le_bytes([entry[32], entry[33]]) as usize;
+
+        let name_start = offset + 46;
+        let name_end = name_start + name_len;
+        if name_end > cd_buf.len() {
+            break;
+        }
+        let name = String::from_utf8_lossy(&cd_buf[name_start..name_end]).to_string();
+
+        // 目录条目（名字以 `/` 结尾）不是真正的成员文件，不计入清单，但仍然
+        // 要跳过它在中央目录里占用的字节继续往下读。
+        if !name.ends_with('/') {
+            total_uncompressed = total_uncompressed.saturating_add(uncompressed_size);
+            if total_uncompressed > max_total_uncompressed_bytes {
+                return None;
+            }
+            members.push(ArchiveMember {
+                name,
+                uncompressed_size,
+            });
+        }
+
+        offset = name_end + extra_len + comment_len;
+    }
+
+    Some(members)
+}
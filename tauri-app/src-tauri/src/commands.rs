@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
+use std::time::UNIX_EPOCH;
 use tauri::{
-    // Emitter,
+    Emitter,
     Manager,
     // Window,
 };
@@ -11,7 +12,7 @@ use tauri::{
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
 pub async fn refresh_monitoring_config(
     state: tauri::State<'_, crate::AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<serde_json::Value, crate::errors::AppError> {
     println!("[CMD] refresh_monitoring_config 被调用");
 
     // 获取文件监控器
@@ -19,7 +20,7 @@ pub async fn refresh_monitoring_config(
         let guard = state.file_monitor.lock().unwrap();
         match &*guard {
             Some(monitor) => monitor.clone(),
-            None => return Err("文件监控器未初始化".to_string()),
+            None => return Err(crate::errors::AppError::MonitorNotInitialized),
         }
     };
 
@@ -39,7 +40,7 @@ pub async fn refresh_monitoring_config(
         }
         Err(e) => {
             eprintln!("[CMD] refresh_monitoring_config 失败: {}", e);
-            Err(format!("配置刷新失败: {}", e))
+            Err(crate::errors::AppError::ConfigRefreshFailed { reason: e })
         }
     }
 }
@@ -48,10 +49,16 @@ pub async fn refresh_monitoring_config(
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
 pub async fn refresh_simplified_config(
     state: tauri::State<'_, crate::AppState>,
+    api_state: tauri::State<'_, crate::ApiState>,
 ) -> Result<serde_json::Value, String> {
     println!("[CMD] refresh_simplified_config 被调用");
 
-    match state.refresh_simplified_config().await {
+    let (api_host, api_port) = {
+        let guard = api_state.0.lock().unwrap();
+        (guard.host.clone(), guard.port)
+    };
+
+    match state.refresh_simplified_config(&api_host, api_port).await {
         Ok(()) => {
             // 获取更新后的配置摘要
             match state.get_simplified_config().await {
@@ -85,25 +92,33 @@ pub async fn refresh_simplified_config(
     }
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Clone)]
 pub struct DirectoryEntry {
     name: String,
     path: String,
     is_directory: bool,
+    // `read_directory_stream` 填充这些字段；一次性的 `read_directory` 不需要
+    // 文件大小/修改时间/深度，继续保持只返回目录名/路径的轻量结构。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    depth: Option<usize>,
 }
 
 #[tauri::command]
-pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String> {
+pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, crate::errors::AppError> {
     println!("[CMD] read_directory 被调用，路径: {}", path);
 
     let path_obj = Path::new(&path);
 
     if !path_obj.exists() {
-        return Err("路径不存在".to_string());
+        return Err(crate::errors::AppError::PathNotFound { path });
     }
 
     if !path_obj.is_dir() {
-        return Err("路径不是文件夹".to_string());
+        return Err(crate::errors::AppError::NotADirectory { path });
     }
 
     let mut entries = Vec::new();
@@ -126,6 +141,9 @@ pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
                                             name: name_str.to_string(),
                                             path: entry_path.to_string_lossy().to_string(),
                                             is_directory,
+                                            size: None,
+                                            modified: None,
+                                            depth: None,
                                         });
                                     }
                                 }
@@ -140,7 +158,10 @@ pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
             }
         }
         Err(e) => {
-            return Err(format!("无法读取目录: {}", e));
+            return Err(crate::errors::AppError::DirectoryReadFailed {
+                path,
+                reason: e.to_string(),
+            });
         }
     }
 
@@ -151,6 +172,179 @@ pub async fn read_directory(path: String) -> Result<Vec<DirectoryEntry>, String>
     Ok(entries)
 }
 
+/// `read_directory_stream` 每攒够这么多条目就通过事件刷新一批给前端，既能
+/// 增量渲染深层目录树，又避免事件通道被逐条 emit 打爆（同
+/// `file_scanner::PROGRESS_EMIT_INTERVAL` 的取舍）。
+const DIRECTORY_STREAM_FLUSH_INTERVAL: usize = 256;
+
+/// `read_directory_stream` 推送的一批目录项，`request_id` 原样回传调用方
+/// 传入的值，供前端在并发发起多个流式请求时区分批次归属哪一次调用。
+#[derive(Debug, Clone, Serialize)]
+struct DirectoryStreamBatch {
+    request_id: String,
+    entries: Vec<DirectoryEntry>,
+}
+
+/// 遍历过程中单条目读取失败时推送的独立错误事件，不再只是 `println!`，
+/// 深层子树里的权限错误这样才不会对前端不可见。
+#[derive(Debug, Clone, Serialize)]
+struct DirectoryStreamReadError {
+    request_id: String,
+    path: String,
+    error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct DirectoryStreamDone {
+    request_id: String,
+    total_entries: u64,
+    total_errors: u64,
+}
+
+/// 流式、递归的目录枚举：通过 `directory_stream_batch`/`directory_stream_error`/
+/// `directory_stream_done` 事件把结果分批推给前端，而不是像 `read_directory`
+/// 那样把整棵树缓冲进一个 `Vec` 再一次性返回——对深层目录树更友好，也能让
+/// UI 增量渲染。
+///
+/// - `max_depth`：限制递归深度（`None` 不限制），根目录自身是深度 0；
+/// - `include_files`：是否把普通文件也纳入结果（`read_directory` 只返回目录）；
+/// - `include_hidden`：是否包含以 `.` 开头的隐藏项，默认为 `false`（沿用
+///   `read_directory` 原本的隐藏过滤，但这里做成可选参数而不是写死）。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn read_directory_stream(
+    request_id: String,
+    path: String,
+    max_depth: Option<usize>,
+    include_files: bool,
+    include_hidden: Option<bool>,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    println!(
+        "[CMD] read_directory_stream 被调用，request_id: {}, 路径: {}, max_depth: {:?}, include_files: {}",
+        request_id, path, max_depth, include_files
+    );
+
+    let path_obj = Path::new(&path);
+    if !path_obj.exists() {
+        return Err("路径不存在".to_string());
+    }
+    if !path_obj.is_dir() {
+        return Err("路径不是文件夹".to_string());
+    }
+
+    let include_hidden = include_hidden.unwrap_or(false);
+
+    let mut walker = walkdir::WalkDir::new(path_obj).min_depth(1);
+    if let Some(depth) = max_depth {
+        walker = walker.max_depth(depth);
+    }
+    let walker = walker.into_iter().filter_entry(move |entry| {
+        include_hidden
+            || entry
+                .file_name()
+                .to_str()
+                .map(|name| !name.starts_with('.'))
+                .unwrap_or(true)
+    });
+
+    let mut batch: Vec<DirectoryEntry> = Vec::with_capacity(DIRECTORY_STREAM_FLUSH_INTERVAL);
+    let mut total_entries = 0u64;
+    let mut total_errors = 0u64;
+
+    for item in walker {
+        match item {
+            Ok(dir_entry) => {
+                let is_directory = dir_entry.file_type().is_dir();
+                if !is_directory && !include_files {
+                    continue;
+                }
+
+                let Some(name) = dir_entry.file_name().to_str() else {
+                    continue;
+                };
+
+                let metadata = dir_entry.metadata().ok();
+                let size = metadata.as_ref().filter(|m| !is_directory).map(|m| m.len());
+                let modified = metadata
+                    .as_ref()
+                    .and_then(|m| m.modified().ok())
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs());
+
+                batch.push(DirectoryEntry {
+                    name: name.to_string(),
+                    path: dir_entry.path().to_string_lossy().to_string(),
+                    is_directory,
+                    size,
+                    modified,
+                    depth: Some(dir_entry.depth()),
+                });
+                total_entries += 1;
+
+                if batch.len() >= DIRECTORY_STREAM_FLUSH_INTERVAL {
+                    let _ = app_handle.emit(
+                        "directory_stream_batch",
+                        DirectoryStreamBatch {
+                            request_id: request_id.clone(),
+                            entries: std::mem::take(&mut batch),
+                        },
+                    );
+                }
+            }
+            Err(walk_err) => {
+                total_errors += 1;
+                let error_path = walk_err
+                    .path()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                eprintln!(
+                    "[CMD] read_directory_stream 读取目录项失败: {} ({})",
+                    walk_err, error_path
+                );
+                let _ = app_handle.emit(
+                    "directory_stream_error",
+                    DirectoryStreamReadError {
+                        request_id: request_id.clone(),
+                        path: error_path,
+                        error: walk_err.to_string(),
+                    },
+                );
+            }
+        }
+    }
+
+    if !batch.is_empty() {
+        let _ = app_handle.emit(
+            "directory_stream_batch",
+            DirectoryStreamBatch {
+                request_id: request_id.clone(),
+                entries: batch,
+            },
+        );
+    }
+
+    println!(
+        "[CMD] read_directory_stream 完成，request_id: {}, 共 {} 项，{} 个错误",
+        request_id, total_entries, total_errors
+    );
+
+    let _ = app_handle.emit(
+        "directory_stream_done",
+        DirectoryStreamDone {
+            request_id: request_id.clone(),
+            total_entries,
+            total_errors,
+        },
+    );
+
+    Ok(serde_json::json!({
+        "status": "done",
+        "request_id": request_id,
+        "total_entries": total_entries,
+        "total_errors": total_errors
+    }))
+}
+
 /// 添加黑名单文件夹到队列（如果初始扫描已完成则立即处理队列）
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
 pub async fn queue_add_blacklist_folder(
@@ -171,25 +365,16 @@ pub async fn queue_add_blacklist_folder(
         folder_path: folder_path.clone(),
         folder_alias,
     };
-    state.add_pending_config_change(change);
-
-    // 检查初始扫描是否已完成
-    if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
-        // 触发队列处理
-        state.process_pending_config_changes();
-
-        Ok(serde_json::json!({
-            "status": "queued_for_processing",
-            "message": format!("黑名单文件夹 {} 已加入处理队列并即将执行", folder_path)
-        }))
-    } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将黑名单添加操作加入队列");
-        Ok(serde_json::json!({
-            "status": "queued",
-            "message": format!("黑名单文件夹 {} 已加入处理队列，将在初始扫描完成后处理", folder_path)
-        }))
-    }
+    let task_uid = state.add_pending_config_change(change);
+
+    // 不再立即触发处理：周期性调度器（见 AppState::spawn_config_change_scheduler）
+    // 会按固定节奏快照并合并队列中的变更，短时间内的多次操作因此会被合并成一批。
+    println!("[CONFIG_QUEUE] 黑名单添加操作已加入队列，等待调度器下一次快照处理");
+    Ok(serde_json::json!({
+        "status": "queued",
+        "task_uid": task_uid,
+        "message": format!("黑名单文件夹 {} 已加入处理队列，将在下一次调度周期内处理", folder_path)
+    }))
 }
 
 /// 删除文件夹（队列版本）
@@ -221,25 +406,15 @@ pub async fn queue_delete_folder(
         folder_path: folder_path.clone(),
         is_blacklist,
     };
-    state.add_pending_config_change(change);
-
-    // 如果初始扫描已完成，立即处理队列
-    if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
-        // 触发队列处理
-        state.process_pending_config_changes();
-
-        Ok(serde_json::json!({
-            "status": "queued_for_processing",
-            "message": format!("文件夹 {} 删除操作已加入处理队列并即将执行", folder_path)
-        }))
-    } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹删除操作加入队列");
-        Ok(serde_json::json!({
-            "status": "queued",
-            "message": format!("文件夹 {} 删除操作已加入处理队列，将在初始扫描完成后处理", folder_path)
-        }))
-    }
+    let task_uid = state.add_pending_config_change(change);
+
+    // 不再立即触发处理：交给周期性调度器按批次合并执行
+    println!("[CONFIG_QUEUE] 文件夹删除操作已加入队列，等待调度器下一次快照处理");
+    Ok(serde_json::json!({
+        "status": "queued",
+        "task_uid": task_uid,
+        "message": format!("文件夹 {} 删除操作已加入处理队列，将在下一次调度周期内处理", folder_path)
+    }))
 }
 
 /// 切换文件夹黑白名单状态（队列版本）
@@ -261,25 +436,15 @@ pub async fn queue_toggle_folder_status(
         is_blacklist,
         folder_path: folder_path.clone(),
     };
-    state.add_pending_config_change(change);
-
-    // 检查初始扫描是否已完成
-    if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
-        // 触发队列处理
-        state.process_pending_config_changes();
-
-        Ok(serde_json::json!({
-            "status": "queued_for_processing",
-            "message": format!("文件夹 {} 状态切换已加入处理队列并即将执行", folder_path)
-        }))
-    } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将文件夹状态切换操作加入队列");
-        Ok(serde_json::json!({
-            "status": "queued",
-            "message": format!("文件夹 {} 状态切换已加入处理队列，将在初始扫描完成后处理", folder_path)
-        }))
-    }
+    let task_uid = state.add_pending_config_change(change);
+
+    // 不再立即触发处理：交给周期性调度器按批次合并执行
+    println!("[CONFIG_QUEUE] 文件夹状态切换操作已加入队列，等待调度器下一次快照处理");
+    Ok(serde_json::json!({
+        "status": "queued",
+        "task_uid": task_uid,
+        "message": format!("文件夹 {} 状态切换已加入处理队列，将在下一次调度周期内处理", folder_path)
+    }))
 }
 
 /// 添加白名单文件夹（队列版本）
@@ -299,25 +464,15 @@ pub async fn queue_add_whitelist_folder(
         folder_path: folder_path.clone(),
         folder_alias,
     };
-    state.add_pending_config_change(change);
-
-    // 检查初始扫描是否已完成
-    if state.is_initial_scan_completed() {
-        println!("[CONFIG_QUEUE] 初始扫描已完成，配置变更已加入队列，即将处理");
-        // 触发队列处理
-        state.process_pending_config_changes();
-
-        Ok(serde_json::json!({
-            "status": "queued_for_processing",
-            "message": format!("白名单文件夹 {} 已加入处理队列并即将执行", folder_path)
-        }))
-    } else {
-        println!("[CONFIG_QUEUE] 初始扫描未完成，将白名单添加操作加入队列");
-        Ok(serde_json::json!({
-            "status": "queued",
-            "message": format!("白名单文件夹 {} 已加入处理队列，将在初始扫描完成后处理", folder_path)
-        }))
-    }
+    let task_uid = state.add_pending_config_change(change);
+
+    // 不再立即触发处理：交给周期性调度器按批次合并执行
+    println!("[CONFIG_QUEUE] 白名单添加操作已加入队列，等待调度器下一次快照处理");
+    Ok(serde_json::json!({
+        "status": "queued",
+        "task_uid": task_uid,
+        "message": format!("白名单文件夹 {} 已加入处理队列，将在下一次调度周期内处理", folder_path)
+    }))
 }
 
 /// 获取配置变更队列状态
@@ -338,26 +493,255 @@ pub fn queue_get_status(
     }))
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// 查询单个配置变更任务的进度（由 `queue_add_*`/`queue_delete_folder`/
+/// `queue_toggle_folder_status` 返回的 `task_uid`），供前端展示具体是哪个
+/// 文件夹操作成功/失败了，而不只是一个笼统的队列计数。
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_get_task(
+    uid: u64,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Option<crate::task_registry::ConfigChangeTask>, String> {
+    Ok(state.get_config_change_task(uid))
+}
+
+/// 按 uid 游标分页列出配置变更任务历史，可选按状态过滤，供前端展示队列的
+/// 处理进度/历史记录。
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_list_tasks(
+    status_filter: Option<crate::task_registry::TaskStatus>,
+    limit: usize,
+    after_uid: Option<u64>,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<Vec<crate::task_registry::ConfigChangeTask>, String> {
+    Ok(state.list_config_change_tasks(status_filter, limit, after_uid))
+}
+
+/// 撤回一个还没被调度器取走执行的配置变更（如用户加了白名单文件夹后又
+/// 马上移除，不需要让两个操作都真的执行一遍）。返回 `false` 表示这个
+/// uid 不存在，或者已经被取走开始执行，撤销不到了。
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_cancel_pending_change(
+    uid: u64,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<bool, String> {
+    Ok(state.cancel_pending_config_change(uid))
+}
+
+/// 把一个仍在排队的配置变更挪到新的位置（`new_index` 为 0 表示排到最先
+/// 执行）。返回 `false` 表示这个 uid 不在队列里了。
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_reorder_pending(
+    uid: u64,
+    new_index: usize,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<bool, String> {
+    Ok(state.reorder_pending_config_change(uid, new_index))
+}
+
+/// 清空所有仍在排队、尚未被调度器取走的配置变更，返回被清空的数量。
+#[tauri::command(rename_all = "snake_case")]
+pub fn queue_clear_pending(state: tauri::State<'_, crate::AppState>) -> Result<usize, String> {
+    Ok(state.clear_pending_config_changes())
+}
+
+/// 开启/关闭某个文件夹扫描时对 `.gitignore`/`.ignore` 文件的支持
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_folder_ignore_files_enabled(
+    folder_path: String,
+    enabled: bool,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    println!(
+        "[CMD] set_folder_ignore_files_enabled 被调用，路径: {}, 启用: {}",
+        folder_path, enabled
+    );
+    state.set_ignore_files_enabled(folder_path.clone(), enabled);
+    Ok(serde_json::json!({
+        "status": "ok",
+        "folder_path": folder_path,
+        "ignore_files_enabled": enabled
+    }))
+}
+
+/// 设置某个文件夹的 allow/ignore glob 过滤规则（有序列表，支持 `!` 取反和
+/// `**` 通配符）。两个列表都传空数组等价于移除该文件夹的过滤层。
+#[tauri::command(rename_all = "snake_case")]
+pub fn set_folder_path_filter(
+    folder_path: String,
+    allow: Vec<String>,
+    ignore: Vec<String>,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    println!(
+        "[CMD] set_folder_path_filter 被调用，路径: {}, allow: {:?}, ignore: {:?}",
+        folder_path, allow, ignore
+    );
+    state.set_path_filter(
+        folder_path.clone(),
+        crate::path_filter::PathFilterConfig { allow, ignore },
+    );
+    Ok(serde_json::json!({
+        "status": "ok",
+        "folder_path": folder_path
+    }))
+}
+
+/// 暂停文件监控（托盘菜单和前端共用这一个命令，保证行为一致）。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn pause_file_monitoring(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    println!("[CMD] pause_file_monitoring 被调用");
+
+    let monitor_to_stop = {
+        let guard = state.debounced_file_monitor.lock().unwrap();
+        guard.clone()
+    };
+    if let Some(mut monitor) = monitor_to_stop {
+        monitor.stop_monitoring().await?;
+    }
+
+    state.set_monitoring_paused(true);
+    crate::tray_menu::rebuild(&app_handle);
+    Ok(serde_json::json!({"status": "ok", "monitoring_paused": true}))
+}
+
+/// 恢复文件监控：重新读取白名单目录列表并重启防抖动监控器。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn resume_file_monitoring(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, String> {
+    println!("[CMD] resume_file_monitoring 被调用");
+
+    let file_monitor_instance = {
+        let guard = state.file_monitor.lock().unwrap();
+        guard.clone()
+    };
+    let Some(file_monitor_instance) = file_monitor_instance else {
+        return Err("文件监控器未初始化".to_string());
+    };
+
+    let directories: Vec<(String, Option<u32>)> = file_monitor_instance
+        .get_monitored_directories()
+        .into_iter()
+        .filter(|dir| !dir.is_blacklist)
+        .map(|dir| (dir.path, dir.max_depth))
+        .collect();
+
+    let debounced_monitor = {
+        let guard = state.debounced_file_monitor.lock().unwrap();
+        guard.clone()
+    };
+    let Some(mut debounced_monitor) = debounced_monitor else {
+        return Err("防抖动监控器未初始化".to_string());
+    };
+
+    if !directories.is_empty() {
+        debounced_monitor
+            .start_monitoring(directories, std::time::Duration::from_millis(2_000))
+            .await?;
+    }
+
+    state.set_monitoring_paused(false);
+    crate::tray_menu::rebuild(&app_handle);
+    Ok(serde_json::json!({"status": "ok", "monitoring_paused": false}))
+}
+
+/// 把前端或全局快捷键抓取到的选中文本送进知识库。实际的转发逻辑在
+/// `selection_capture` 模块里，和全局快捷键回调共用同一份实现。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn capture_selection(
+    text: String,
+    app_handle: tauri::AppHandle,
+) -> Result<serde_json::Value, String> {
+    println!(
+        "[CMD] capture_selection 被调用，文本长度: {}",
+        text.len()
+    );
+    crate::selection_capture::ingest_captured_text(text, app_handle).await
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FileInfo {
     pub id: i64,
     pub path: String,
     pub file_name: String,
     pub extension: Option<String>,
     pub tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub file_category: Option<String>,
+}
+
+/// `search_files_by_tags` 不带 `limit`/`offset` 时的默认分页大小；也是
+/// 缓存键的一部分（见 `tag_search_cache` 模块），提成常量而不是到处写
+/// 字面量 `50`。
+const TAG_SEARCH_DEFAULT_LIMIT: u32 = 50;
+
+/// `facets.category`/`facets.extension` 桶：值落在这个类别/扩展名下的
+/// 命中数，供前端搭建下钻侧栏，而不是只能看到一份压平的结果列表。
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct TagSearchFacets {
+    #[serde(default)]
+    pub category: std::collections::HashMap<String, u64>,
+    #[serde(default)]
+    pub extension: std::collections::HashMap<String, u64>,
+}
+
+/// `search_files_by_tags` 的响应形状：分页后的命中结果 + 总数 + 分面计数，
+/// 直接对应 `/tagging/search-files` 在带 `include_facets` 时返回的结构。
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagSearchResult {
+    pub hits: Vec<FileInfo>,
+    pub total: u64,
+    pub offset: u32,
+    pub limit: u32,
+    #[serde(default)]
+    pub facets: TagSearchFacets,
 }
 
 #[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+#[allow(clippy::too_many_arguments)]
 pub async fn search_files_by_tags(
     tag_names: Vec<String>,
     operator: String,
+    offset: Option<u32>,
+    limit: Option<u32>,
+    sort: Option<String>,
+    extensions: Option<Vec<String>>,
+    file_categories: Option<Vec<String>>,
+    include_facets: Option<bool>,
     app_handle: tauri::AppHandle,
-) -> Result<Vec<FileInfo>, String> {
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<TagSearchResult, crate::errors::AppError> {
+    let offset = offset.unwrap_or(0);
+    let limit = limit.unwrap_or(TAG_SEARCH_DEFAULT_LIMIT);
+    let extensions = extensions.unwrap_or_default();
+    let file_categories = file_categories.unwrap_or_default();
+    let include_facets = include_facets.unwrap_or(false);
+
     println!(
-        "[CMD] search_files_by_tags called with tags: {:?}, operator: {}",
-        tag_names, operator
+        "[CMD] search_files_by_tags called with tags: {:?}, operator: {}, offset: {}, limit: {}",
+        tag_names, operator, offset, limit
     );
 
+    let query = crate::tag_search_cache::TagSearchQuery {
+        tag_names: &tag_names,
+        operator: &operator,
+        offset,
+        limit,
+        sort: sort.as_deref(),
+        extensions: &extensions,
+        file_categories: &file_categories,
+        include_facets,
+    };
+
+    if let Some(cached) = state.tag_search_cache().get_search(&query) {
+        println!("[CMD] search_files_by_tags 命中缓存, {} 个命中", cached.hits.len());
+        return Ok(cached);
+    }
+
     // Get API host and port from state
     let (api_host, api_port) = {
         let api_state = app_handle.state::<crate::ApiState>();
@@ -372,19 +756,31 @@ pub async fn search_files_by_tags(
     let request_data = serde_json::json!({
         "tag_names": tag_names,
         "operator": operator,
-        "limit": 50 // Set a reasonable limit for real-time search
+        "offset": offset,
+        "limit": limit,
+        "sort": sort,
+        "extensions": extensions,
+        "file_categories": file_categories,
+        "include_facets": include_facets,
     });
 
     // Send the POST request
     match client.post(&url).json(&request_data).send().await {
         Ok(response) => {
             if response.status().is_success() {
-                match response.json::<Vec<FileInfo>>().await {
-                    Ok(files) => {
-                        println!("[CMD] search_files_by_tags found {} files", files.len());
-                        Ok(files)
+                match response.json::<TagSearchResult>().await {
+                    Ok(result) => {
+                        println!(
+                            "[CMD] search_files_by_tags found {} hits (total {})",
+                            result.hits.len(),
+                            result.total
+                        );
+                        state.tag_search_cache().put_search(&query, result.clone());
+                        Ok(result)
                     }
-                    Err(e) => Err(format!("Failed to parse response: {}", e)),
+                    Err(e) => Err(crate::errors::AppError::ParseFailed {
+                        reason: e.to_string(),
+                    }),
                 }
             } else {
                 let status = response.status();
@@ -392,13 +788,14 @@ pub async fn search_files_by_tags(
                     .text()
                     .await
                     .unwrap_or_else(|_| "Could not read error response".to_string());
-                Err(format!(
-                    "API request failed with status {}: {}",
-                    status, error_text
-                ))
+                Err(crate::errors::AppError::ApiRequestFailed {
+                    reason: format!("status {}: {}", status, error_text),
+                })
             }
         }
-        Err(e) => Err(format!("Failed to send request: {}", e)),
+        Err(e) => Err(crate::errors::AppError::ApiRequestFailed {
+            reason: e.to_string(),
+        }),
     }
 }
 
@@ -407,16 +804,22 @@ pub async fn search_files_by_tags(
 pub async fn get_tag_cloud_data(
     limit: Option<u32>,
     app_handle: tauri::AppHandle,
-) -> Result<serde_json::Value, String> {
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<serde_json::Value, crate::errors::AppError> {
     println!("[CMD] get_tag_cloud_data 被调用，limit: {:?}", limit);
 
+    if let Some(cached) = state.tag_search_cache().get_tag_cloud(limit) {
+        println!("[CMD] get_tag_cloud_data 命中缓存");
+        return Ok(cached);
+    }
+
     // 获取API信息
     let (api_host, api_port) = {
         let api_state = app_handle.state::<crate::ApiState>();
         let api_state_guard = api_state.0.lock().unwrap();
 
         if api_state_guard.process_child.is_none() {
-            return Err("API服务未运行".to_string());
+            return Err(crate::errors::AppError::ApiUnavailable);
         }
 
         (api_state_guard.host.clone(), api_state_guard.port)
@@ -438,9 +841,12 @@ pub async fn get_tag_cloud_data(
                 match response.json::<serde_json::Value>().await {
                     Ok(response_data) => {
                         // println!("[CMD] get_tag_cloud_data 成功获取标签云响应: {:?}", response_data);
+                        state.tag_search_cache().put_tag_cloud(limit, response_data.clone());
                         Ok(response_data)
                     }
-                    Err(e) => Err(format!("解析标签云数据失败: {}", e)),
+                    Err(e) => Err(crate::errors::AppError::ParseFailed {
+                        reason: e.to_string(),
+                    }),
                 }
             } else {
                 let status = response.status();
@@ -448,9 +854,205 @@ pub async fn get_tag_cloud_data(
                     .text()
                     .await
                     .unwrap_or_else(|_| "无法读取错误响应".to_string());
-                Err(format!("API请求失败 [{}]: {}", status, error_text))
+                Err(crate::errors::AppError::ApiRequestFailed {
+                    reason: format!("[{}]: {}", status, error_text),
+                })
             }
         }
-        Err(e) => Err(format!("发送请求失败: {}", e)),
+        Err(e) => Err(crate::errors::AppError::ApiRequestFailed {
+            reason: e.to_string(),
+        }),
     }
 }
+
+/// `api_status` 返回给前端的快照：能在 `service_controller` 里查到已登记
+/// 记录时用它的状态，查不到（比如应用刚启动、`start_python_api` 的 spawn
+/// 任务还没跑到注册那一步）时退化为直接读 `ApiState`，至少报告配置的
+/// host/port，状态视为 `Stopped`。
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiStatusResponse {
+    pub host: String,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub status: crate::service_controller::ServiceStatus,
+}
+
+/// 查询 Python API sidecar 当前的 host/port/PID/存活状态，供前端在收到
+/// `api-error` 之类事件后判断要不要提示用户手动重启。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn api_status(
+    app_handle: tauri::AppHandle,
+    api_state: tauri::State<'_, crate::ApiState>,
+) -> Result<ApiStatusResponse, String> {
+    let service_controller = app_handle.state::<crate::AppState>().service_controller();
+    if let Some(snapshot) = service_controller
+        .status()
+        .into_iter()
+        .find(|snapshot| snapshot.id == crate::api_startup::PYTHON_API_SERVICE_ID)
+    {
+        return Ok(ApiStatusResponse {
+            host: snapshot.host,
+            port: snapshot.port,
+            pid: snapshot.pid,
+            status: snapshot.status,
+        });
+    }
+
+    let guard = api_state.0.lock().unwrap();
+    Ok(ApiStatusResponse {
+        host: guard.host.clone(),
+        port: guard.port,
+        pid: guard.pid(),
+        status: crate::service_controller::ServiceStatus::Stopped,
+    })
+}
+
+/// 停止 Python API sidecar，不重新拉起。用户主动在设置里把它关掉（比如准备
+/// 修改数据库路径）时调用；和应用退出时的 `ApiProcessManager::cleanup` 是
+/// 同一套底层进程树终止逻辑，见 `api_startup::stop_python_api`。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn stop_api(app_handle: tauri::AppHandle) -> Result<(), String> {
+    crate::api_startup::stop_python_api(&app_handle);
+    Ok(())
+}
+
+/// 重启 Python API sidecar：终止现有进程树，重新拉起，等待 `/health` 探测
+/// 通过后才返回，让前端可以在改完设置（比如数据库路径）后原地刷新后端，
+/// 不需要重启整个 Tauri 应用。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn restart_api(
+    app_handle: tauri::AppHandle,
+    api_state: tauri::State<'_, crate::ApiState>,
+) -> Result<(), crate::errors::AppError> {
+    crate::api_startup::restart_python_api(app_handle, api_state.0.clone())
+        .await
+        .map_err(|reason| crate::errors::AppError::ApiRestartFailed { reason })
+}
+
+/// 前端在重新注册生命周期事件监听器（比如页面刷新、开发模式热重载）之后
+/// 调用，把 `api-ready`/`file-monitor-error`/`api-fatal` 等事件的最新状态
+/// 补发一遍，填平"后端早于前端监听器就绪"这个竞态——不这样做的话，如果
+/// 后端在前端挂上监听器之前就已经发完这些事件，前端会永远等不到它们。
+/// 见 `event_buffer::LifecycleEventStore`。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn sync_lifecycle(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, crate::AppState>,
+) -> Result<(), String> {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return Err("找不到主窗口".to_string());
+    };
+    state.lifecycle_events().replay_to(&window);
+    Ok(())
+}
+
+/// 运行期覆盖某个桥接事件的缓冲策略，供前端按自己当前关心的内容动态调整：
+/// 比如某个视图只看 `rag-progress`，就可以把它的节流窗口调紧到接近实时。
+/// `kind` 取 `"immediate"` / `"delayed_merge"` / `"throttle"`；后两种配合
+/// `window_ms` 指定窗口长度，不传则用各自的默认值。见
+/// `event_buffer::EventBuffer::set_strategy`。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn set_event_strategy(
+    app_handle: tauri::AppHandle,
+    event: String,
+    kind: String,
+    window_ms: Option<u64>,
+) -> Result<(), String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    let strategy = match kind.as_str() {
+        "immediate" => crate::event_buffer::EventBufferStrategy::Immediate,
+        "delayed_merge" => crate::event_buffer::EventBufferStrategy::DelayedMerge(
+            std::time::Duration::from_millis(window_ms.unwrap_or(500)),
+        ),
+        "throttle" => crate::event_buffer::EventBufferStrategy::Throttle(
+            std::time::Duration::from_millis(window_ms.unwrap_or(1000)),
+        ),
+        other => return Err(format!("未知的缓冲策略类型: {}", other)),
+    };
+    buffer.set_strategy(event, strategy).await;
+    Ok(())
+}
+
+/// 静音一个桥接事件：静音期间它在进入缓冲前就被丢弃，既不缓冲也不转发
+/// 给前端。给只想看自己关心的事件、不想被高频噪音事件刷屏的视图用。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn mute_event(app_handle: tauri::AppHandle, event: String) -> Result<(), String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    buffer.mute(event).await;
+    Ok(())
+}
+
+/// 取消静音，恢复正常处理
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn unmute_event(app_handle: tauri::AppHandle, event: String) -> Result<(), String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    buffer.unmute(&event).await;
+    Ok(())
+}
+
+/// 打开桥接事件的可观测性导出：把每个桥接事件以及缓冲器自己的生命周期
+/// 事件（合并/节流丢弃/预算驱逐/flush/发送失败）批量镜像到 `endpoint`，
+/// 让运维能在生产环境里看到真实用户会话下事件是怎么产生、缓冲策略是
+/// 怎么表现的，而不必只靠 stdout 里的 `println!`。见
+/// `diagnostics_export::DiagnosticsExporter`。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn enable_diagnostics_export(
+    app_handle: tauri::AppHandle,
+    endpoint: String,
+    auth_header: Option<String>,
+    batch_size: Option<usize>,
+    flush_interval_ms: Option<u64>,
+) -> Result<(), String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    buffer
+        .enable_diagnostics(crate::diagnostics_export::DiagnosticsExportConfig {
+            endpoint,
+            auth_header,
+            batch_size: batch_size.unwrap_or(50),
+            flush_interval: std::time::Duration::from_millis(flush_interval_ms.unwrap_or(5000)),
+        })
+        .await;
+    Ok(())
+}
+
+/// 关闭可观测性导出，回到零开销的默认状态
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn disable_diagnostics_export(app_handle: tauri::AppHandle) -> Result<(), String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    buffer.disable_diagnostics().await;
+    Ok(())
+}
+
+/// 拍一张 `EventBuffer` 的指标快照：接收/合并/发送/失败计数器（按事件名+
+/// 策略分桶）、当前缓冲区大小、合并到发送耗时的直方图。给前端的诊断面板
+/// 或者开发者工具用，衡量"每种缓冲策略到底拦下了多少事件"而不必靠猜。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn get_event_buffer_metrics(
+    app_handle: tauri::AppHandle,
+) -> Result<crate::event_metrics::EventBufferMetricsSnapshot, String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    Ok(buffer.metrics_snapshot())
+}
+
+/// 同 `get_event_buffer_metrics`，但渲染成 Prometheus 文本 exposition 格式，
+/// 方便直接喂给外部抓取方（没有独立 HTTP 服务器挂 `/metrics` 路由，所以
+/// 这里先以命令形式暴露文本内容，由调用方决定怎么对外提供）。
+#[tauri::command(rename_all = "snake_case", async, async_runtime = "tokio")]
+pub async fn get_event_buffer_metrics_text(app_handle: tauri::AppHandle) -> Result<String, String> {
+    let Some(buffer) = app_handle.state::<crate::AppState>().get_event_buffer() else {
+        return Err("事件缓冲器尚未初始化".to_string());
+    };
+    Ok(buffer.metrics_prometheus_text())
+}
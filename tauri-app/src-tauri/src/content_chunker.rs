@@ -0,0 +1,144 @@
+//! 基于内容定义分块（Content-Defined Chunking, CDC）的滚动哈希分块器。
+//!
+//! 和 `file_monitor::calculate_simple_hash`/`file_scanner::hash_file_contents`
+//! （固定读前几 KB 的"简单指纹" / 对全文件内容算一个整体哈希）不同，这里的
+//! 分块边界由一个在文件里滑动的窗口的滚动哈希值本身决定：在文件中间插入或
+//! 删除几个字节，只会重新切出被改动的那个窗口附近的分块，其余分块的偏移、
+//! 长度和哈希都完全不变。这样后端可以只重新处理变化的分块，并且能跨文件
+//! 去重内容相同的分块，而不只是判断"整份文件是否完全相同"。
+//!
+//! 滚动哈希用的是 buzhash：每个字节通过一张 256 项的固定表映射成一个 u64，
+//! 窗口滑动时用"旋转 1 位再异或"做增量更新，不需要重新扫描整个窗口。表是
+//! 固定种子生成的，保证同一份内容在不同机器、不同进程上切出同样的分块
+//! 边界（分块边界要能跨进程/跨机器复用，不能用运行时随机数生成）。
+
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::io::Read;
+use std::path::Path;
+
+const WINDOW_SIZE: usize = 64;
+const MIN_CHUNK_SIZE: usize = 16 * 1024; // 16KB
+const MAX_CHUNK_SIZE: usize = 4 * 1024 * 1024; // 4MB
+// 流式读取文件用的固定缓冲区大小，沿用 file_scanner.rs::HASH_BUFFER_SIZE 的
+// 做法：避免大文件被整个读进内存。
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// 单个内容分块的摘要：在文件里的起始偏移、长度、分块自身内容的 SHA-256。
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChunkDigest {
+    pub offset: u64,
+    pub length: u64,
+    pub hash: String,
+}
+
+/// 一个文件的完整分块结果：有序的分块摘要列表，加上一个全文件整体摘要，
+/// 方便不想逐块比较的调用方直接做"整份是否相同"的快速判断。
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContentChunks {
+    pub chunks: Vec<ChunkDigest>,
+    pub file_hash: String,
+    pub target_chunk_size_kb: u32,
+}
+
+// buzhash 的字节映射表：固定种子的简单 LCG 生成，只要求分布还不错，不需要
+// 密码学强度；种子和乘数固定，保证每次运行生成同一张表。
+fn buzhash_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x9E3779B97F4A7C15;
+    for slot in table.iter_mut() {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+        *slot = state;
+    }
+    table
+}
+
+// 目标平均分块大小对应的掩码：取大于等于 target_size 的最近一个 2 的幂，
+// 掩码就是它减一。滚动哈希低 log2(target) 位恰好全为 0 的概率是
+// 1/target，也就是期望的分块间隔长度。
+fn mask_for_target_size(target_size: usize) -> u64 {
+    let target = target_size.next_power_of_two().max(1) as u64;
+    target - 1
+}
+
+/// 对 `path` 做内容定义分块：滑动一个 `WINDOW_SIZE` 字节的窗口，只要滚动
+/// 哈希低位和掩码全部为 0 就声明一个分块边界；边界位置被 `MIN_CHUNK_SIZE`/
+/// `MAX_CHUNK_SIZE` 钳制，避免出现病态的超短或超长分块。
+/// `target_chunk_size_kb` 来自 `FileScanningConfig::content_chunking_target_size_kb`。
+pub fn chunk_file(path: &Path, target_chunk_size_kb: u32) -> Option<ContentChunks> {
+    let target_size =
+        ((target_chunk_size_kb as usize).max(1) * 1024).clamp(MIN_CHUNK_SIZE, MAX_CHUNK_SIZE);
+    let mask = mask_for_target_size(target_size);
+    let table = buzhash_table();
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut file_hasher = Sha256::new();
+    let mut chunks = Vec::new();
+
+    let mut window: VecDeque<u8> = VecDeque::with_capacity(WINDOW_SIZE);
+    let mut rolling: u64 = 0;
+    let mut chunk_offset: u64 = 0;
+    let mut absolute_offset: u64 = 0;
+    // 当前正在累积的分块原始字节，钳制在 MAX_CHUNK_SIZE 以内，切到边界时一次性
+    // 喂给一个新的 Sha256 实例算这一块的哈希。
+    let mut chunk_buffer: Vec<u8> = Vec::new();
+
+    let mut read_buffer = vec![0u8; READ_BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut read_buffer).ok()?;
+        if n == 0 {
+            break;
+        }
+        file_hasher.update(&read_buffer[..n]);
+
+        for &byte in &read_buffer[..n] {
+            chunk_buffer.push(byte);
+            window.push_back(byte);
+            rolling = rolling.rotate_left(1) ^ table[byte as usize];
+            if window.len() > WINDOW_SIZE {
+                let outgoing = window.pop_front().unwrap();
+                // 撤销 outgoing 对 rolling 的贡献：buzhash 的哈希宽度
+                // （64位）和窗口大小相等，outgoing 进入窗口后经历的旋转量
+                // 累加起来正好是一整圈（64 位旋转 64 次等于没转），所以直接
+                // 异或回去就抵消了它的贡献，不需要额外旋转。
+                rolling ^= table[outgoing as usize];
+            }
+            absolute_offset += 1;
+
+            let at_boundary = window.len() >= WINDOW_SIZE
+                && chunk_buffer.len() >= MIN_CHUNK_SIZE
+                && (rolling & mask) == 0;
+            let forced = chunk_buffer.len() >= MAX_CHUNK_SIZE;
+
+            if at_boundary || forced {
+                let mut hasher = Sha256::new();
+                hasher.update(&chunk_buffer);
+                chunks.push(ChunkDigest {
+                    offset: chunk_offset,
+                    length: chunk_buffer.len() as u64,
+                    hash: format!("{:x}", hasher.finalize()),
+                });
+                chunk_offset = absolute_offset;
+                chunk_buffer.clear();
+                window.clear();
+                rolling = 0;
+            }
+        }
+    }
+
+    if !chunk_buffer.is_empty() {
+        let mut hasher = Sha256::new();
+        hasher.update(&chunk_buffer);
+        chunks.push(ChunkDigest {
+            offset: chunk_offset,
+            length: chunk_buffer.len() as u64,
+            hash: format!("{:x}", hasher.finalize()),
+        });
+    }
+
+    Some(ContentChunks {
+        chunks,
+        file_hash: format!("{:x}", file_hasher.finalize()),
+        target_chunk_size_kb,
+    })
+}
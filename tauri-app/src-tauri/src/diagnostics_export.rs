@@ -0,0 +1,140 @@
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// 往外部可观测性后端（ZincObserve/Elasticsearch 兼容的批量 JSON-lines
+/// ingest API 那一类）镜像桥接事件的配置：端点、鉴权头、攒多少条/攒多久
+/// 发一批，全部可配置，运维按自己的后端调整即可。
+#[derive(Debug, Clone)]
+pub struct DiagnosticsExportConfig {
+    pub endpoint: String,
+    pub auth_header: Option<String>,
+    pub batch_size: usize,
+    pub flush_interval: Duration,
+}
+
+/// 镜像到外部后端的一条记录：既包括原始桥接事件本身（`kind = "received"`），
+/// 也包括缓冲器自己的生命周期事件（合并/节流丢弃/驱逐/flush/发送失败）。
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsRecord {
+    pub kind: String,
+    pub event: String,
+    pub payload: serde_json::Value,
+    pub timestamp: String,
+}
+
+impl DiagnosticsRecord {
+    pub fn new(kind: impl Into<String>, event: impl Into<String>, payload: serde_json::Value) -> Self {
+        Self {
+            kind: kind.into(),
+            event: event.into(),
+            payload,
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+}
+
+/// 导出队列的容量：满了就丢弃最新记录而不是阻塞调用方，可观测性本身
+/// 不应该拖慢桥接事件的主流程。
+const EXPORT_CHANNEL_CAPACITY: usize = 4096;
+
+/// 把桥接事件/缓冲器生命周期事件批量导出到外部 HTTP ingest 端点。导出走
+/// 独立的有界 channel + 后台任务，`record` 本身只是 `try_send`，从不阻塞
+/// 调用方（也就是 `event_buffer::EventBuffer::emit_event` 等关键路径）。
+pub struct DiagnosticsExporter {
+    tx: mpsc::Sender<DiagnosticsRecord>,
+}
+
+impl DiagnosticsExporter {
+    /// 启动导出器：起一个后台任务按 `config.batch_size`/`config.flush_interval`
+    /// 中先到者为准攒批发送，返回的句柄只负责往 channel 里塞记录。
+    pub fn spawn(config: DiagnosticsExportConfig) -> Arc<Self> {
+        let (tx, rx) = mpsc::channel(EXPORT_CHANNEL_CAPACITY);
+        tokio::spawn(Self::run(config, rx));
+        Arc::new(Self { tx })
+    }
+
+    /// 记一条镜像记录；channel 满了就丢弃并打日志，绝不阻塞调用方。
+    pub fn record(&self, record: DiagnosticsRecord) {
+        if self.tx.try_send(record).is_err() {
+            eprintln!("⚠️ 可观测性导出队列已满，丢弃一条记录: {}", record_kind_event(&record));
+        }
+    }
+
+    async fn run(config: DiagnosticsExportConfig, mut rx: mpsc::Receiver<DiagnosticsRecord>) {
+        let client = reqwest::Client::new();
+        let mut batch: Vec<DiagnosticsRecord> = Vec::with_capacity(config.batch_size);
+        let mut ticker = tokio::time::interval(config.flush_interval);
+
+        loop {
+            tokio::select! {
+                maybe_record = rx.recv() => {
+                    match maybe_record {
+                        Some(record) => {
+                            batch.push(record);
+                            if batch.len() >= config.batch_size {
+                                Self::flush(&client, &config, &mut batch).await;
+                            }
+                        }
+                        None => {
+                            // 发送端全部掉线（EventBuffer 被销毁），排空最后一批后退出
+                            Self::flush(&client, &config, &mut batch).await;
+                            return;
+                        }
+                    }
+                }
+                _ = ticker.tick() => {
+                    Self::flush(&client, &config, &mut batch).await;
+                }
+            }
+        }
+    }
+
+    /// 把当前批次编码成 ndjson（每行一条 JSON 记录）POST 给配置的端点；
+    /// 不管成功失败都清空批次——导出失败不重试，避免把本该尽力而为的
+    /// 可观测性通道变成又一个需要退避重试的关键路径。
+    async fn flush(
+        client: &reqwest::Client,
+        config: &DiagnosticsExportConfig,
+        batch: &mut Vec<DiagnosticsRecord>,
+    ) {
+        if batch.is_empty() {
+            return;
+        }
+
+        let body = batch
+            .iter()
+            .filter_map(|r| serde_json::to_string(r).ok())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let record_count = batch.len();
+
+        let mut request = client
+            .post(&config.endpoint)
+            .header("Content-Type", "application/x-ndjson")
+            .body(body);
+
+        if let Some(auth) = &config.auth_header {
+            request = request.header("Authorization", auth.clone());
+        }
+
+        match request.send().await {
+            Ok(resp) if resp.status().is_success() => {
+                println!("📡 已导出 {} 条可观测性记录", record_count);
+            }
+            Ok(resp) => {
+                eprintln!("⚠️ 可观测性导出端点返回非成功状态: {}", resp.status());
+            }
+            Err(e) => {
+                eprintln!("⚠️ 可观测性导出失败: {}", e);
+            }
+        }
+
+        batch.clear();
+    }
+}
+
+fn record_kind_event(record: &DiagnosticsRecord) -> String {
+    format!("{}/{}", record.kind, record.event)
+}
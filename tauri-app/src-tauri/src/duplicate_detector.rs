@@ -0,0 +1,100 @@
+//! 重复文件检测：按"大小 -> 局部哈希 -> 全文件哈希"三段式分组，逐级收窄候选
+//! 集合，避免对不可能重复的大文件整份计算 SHA-256。
+//!
+//! 和 `file_scanner::find_duplicates`（消费一次扫描已经产出的 `FileInfo`
+//! 列表，按"大小 -> 全文件哈希"两段分组）不同，这里的 [`find_duplicates`]
+//! 直接接收一组目录路径、自己遍历磁盘，适合不经过完整扫描/配置流程、单独
+//! 对某几个目录跑一次查重的场景；中间多出的"局部哈希"一段复用
+//! `file_monitor::FileMonitor::calculate_simple_hash` 已经在用的前 4KB 抽样
+//! 哈希，能在算代价最高的全文件哈希之前，再筛掉大部分"大小相同但内容一开始
+//! 就不同"的假阳性。隐藏文件和 macOS bundle 内部文件复用
+//! `file_scanner::is_hidden_file`/`is_macos_bundle_folder` 同一套判定剔除，
+//! 不会被当成候选文件展开。
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use walkdir::WalkDir;
+
+use crate::file_monitor::FileMonitor;
+use crate::file_scanner::{hash_file_contents, is_hidden_file, is_macos_bundle_folder};
+
+// 局部哈希抽样的字节数，和 FileMonitor 里"简单哈希"的既有用法（4KB）保持一致。
+const PARTIAL_HASH_BYTES: usize = 4096;
+
+// 遍历给定目录，收集候选文件路径；隐藏文件和 bundle 目录整棵子树都不展开。
+fn collect_candidate_files(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        for entry in WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| {
+                if is_hidden_file(e.path()) {
+                    return false;
+                }
+                if e.file_type().is_dir() && is_macos_bundle_folder(e.path()) {
+                    return false;
+                }
+                true
+            })
+            .filter_map(Result::ok)
+        {
+            if entry.file_type().is_file() {
+                files.push(entry.into_path());
+            }
+        }
+    }
+    files
+}
+
+/// 在 `dirs` 列出的目录树下查找内容完全相同的文件，返回每组重复文件各自的
+/// 完整路径集合（只包含至少两个文件的组，单份文件不算重复）。
+pub async fn find_duplicates(dirs: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    let candidate_files = collect_candidate_files(dirs);
+
+    // 第一阶段：按字节大小分组。大小不同的文件内容不可能相同，大小唯一的
+    // 分组直接丢弃，省得后面白跑一次哈希。
+    let mut by_size: HashMap<u64, Vec<PathBuf>> = HashMap::new();
+    for path in candidate_files {
+        if let Ok(meta) = std::fs::metadata(&path) {
+            by_size.entry(meta.len()).or_default().push(path);
+        }
+    }
+
+    // 第二阶段：同大小的文件再算一次局部哈希（文件开头 4KB），局部哈希也
+    // 唯一的继续丢弃——这一步比下面的全文件哈希便宜得多。
+    let mut by_partial_hash: HashMap<(u64, String), Vec<PathBuf>> = HashMap::new();
+    for (size, group) in by_size.into_iter().filter(|(_, g)| g.len() >= 2) {
+        for path in group {
+            let Some(partial) = FileMonitor::calculate_simple_hash(&path, PARTIAL_HASH_BYTES).await
+            else {
+                continue; // 读取失败（权限/文件已被删除等），跳过
+            };
+            by_partial_hash.entry((size, partial)).or_default().push(path);
+        }
+    }
+
+    // 第三阶段：大小和局部哈希都相同的文件才值得算一次全文件流式哈希，
+    // 真正确认内容完全一致。
+    let mut by_full_hash: HashMap<String, Vec<PathBuf>> = HashMap::new();
+    for (_, group) in by_partial_hash.into_iter().filter(|(_, g)| g.len() >= 2) {
+        for path in group {
+            let Some(full) = hash_file_contents(&path) else {
+                continue;
+            };
+            by_full_hash.entry(full).or_default().push(path);
+        }
+    }
+
+    let groups: Vec<Vec<PathBuf>> = by_full_hash
+        .into_values()
+        .filter(|group| group.len() >= 2)
+        .collect();
+
+    println!(
+        "[DUPLICATE_DETECTOR] 在 {} 个目录下找到 {} 组重复文件",
+        dirs.len(),
+        groups.len()
+    );
+
+    groups
+}
@@ -0,0 +1,112 @@
+//! Tauri 命令的结构化错误类型。
+//!
+//! 仓库里大多数命令历史上都是 `Result<T, String>`，前端拿到的只有一句话，
+//! 没法区分"路径不存在，该弹出选择器"和"API 还没启动，该自动重试"这类不同
+//! 的应对方式。`AppError` 给每种已知失败原因分配一个稳定的机器可读 `code`
+//! 和一个粗粒度的 `kind`（区分"客户端可以自己处理的请求问题"还是"后端内部
+//! 出了故障"），序列化成 `{ "code", "message", "kind" }` 返回给前端，
+//! `message` 仍然是给人看的、可以直接展示的句子。
+//!
+//! 目前只有少数命令（`read_directory`、`refresh_monitoring_config`、
+//! `search_files_by_tags`、`get_tag_cloud_data`）迁移到了这个类型，其余命令
+//! 暂时维持原有的 `Result<T, String>`；后续请求会按需逐步迁移，不强求一次
+//! 性改完整个模块。
+
+use serde::Serialize;
+
+/// 粗粒度的错误分类，供前端决定整体应对策略（比如 `Internal` 类错误可以
+/// 提示"稍后重试"，`InvalidRequest` 类错误应该引导用户改正输入）。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// 请求本身有问题（路径不存在、参数不合法等），重试没有意义。
+    InvalidRequest,
+    /// 后端/依赖服务出了故障（监控器未初始化、API 不可用等），通常可以重试。
+    Internal,
+}
+
+#[derive(Debug, Clone)]
+pub enum AppError {
+    MonitorNotInitialized,
+    PathNotFound { path: String },
+    NotADirectory { path: String },
+    DirectoryReadFailed { path: String, reason: String },
+    ApiUnavailable,
+    ApiRequestFailed { reason: String },
+    ApiRestartFailed { reason: String },
+    ConfigRefreshFailed { reason: String },
+    ParseFailed { reason: String },
+}
+
+impl AppError {
+    /// 稳定的机器可读错误码，前端用它做分支判断（如 `api_unavailable` 时
+    /// 自动重试、`path_not_found` 时弹出文件夹选择器），不应随措辞调整而变化。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::MonitorNotInitialized => "monitor_not_initialized",
+            Self::PathNotFound { .. } => "path_not_found",
+            Self::NotADirectory { .. } => "not_a_directory",
+            Self::DirectoryReadFailed { .. } => "directory_read_failed",
+            Self::ApiUnavailable => "api_unavailable",
+            Self::ApiRequestFailed { .. } => "api_request_failed",
+            Self::ApiRestartFailed { .. } => "api_restart_failed",
+            Self::ConfigRefreshFailed { .. } => "config_refresh_failed",
+            Self::ParseFailed { .. } => "parse_failed",
+        }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::PathNotFound { .. } | Self::NotADirectory { .. } => ErrorKind::InvalidRequest,
+            Self::MonitorNotInitialized
+            | Self::DirectoryReadFailed { .. }
+            | Self::ApiUnavailable
+            | Self::ApiRequestFailed { .. }
+            | Self::ApiRestartFailed { .. }
+            | Self::ConfigRefreshFailed { .. }
+            | Self::ParseFailed { .. } => ErrorKind::Internal,
+        }
+    }
+
+    /// 给人看的错误信息，和这个模块里其它 `Err(format!(...))` 的措辞保持一致。
+    pub fn message(&self) -> String {
+        match self {
+            Self::MonitorNotInitialized => "文件监控器未初始化".to_string(),
+            Self::PathNotFound { path } => format!("路径不存在: {}", path),
+            Self::NotADirectory { path } => format!("路径不是文件夹: {}", path),
+            Self::DirectoryReadFailed { path, reason } => {
+                format!("无法读取目录 {}: {}", path, reason)
+            }
+            Self::ApiUnavailable => "API服务未运行".to_string(),
+            Self::ApiRequestFailed { reason } => format!("API请求失败: {}", reason),
+            Self::ApiRestartFailed { reason } => format!("API服务重启失败: {}", reason),
+            Self::ConfigRefreshFailed { reason } => format!("配置刷新失败: {}", reason),
+            Self::ParseFailed { reason } => format!("解析响应失败: {}", reason),
+        }
+    }
+}
+
+impl std::fmt::Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}] {}", self.code(), self.message())
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// 手写实现而不是 `#[derive(Serialize)]`：各变体携带的字段只是用来拼
+/// `message()`，序列化出去的形状是固定的 `{ code, message, kind }` 三元组，
+/// 和内部枚举结构无关。
+impl Serialize for AppError {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message())?;
+        state.serialize_field("kind", &self.kind())?;
+        state.end()
+    }
+}
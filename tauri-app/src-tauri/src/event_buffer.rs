@@ -1,10 +1,12 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use tauri::{AppHandle, Emitter};
-use tokio::sync::RwLock;
-use tokio::time::interval;
+use tauri::{AppHandle, Emitter, WebviewWindow};
+use tokio::sync::{Notify, RwLock};
+
+use crate::diagnostics_export::{DiagnosticsExportConfig, DiagnosticsExporter, DiagnosticsRecord};
+use crate::event_metrics::{EventBufferMetrics, EventBufferMetricsSnapshot};
 
 /// 桥接事件数据结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -13,6 +15,31 @@ pub struct BridgeEventData {
     pub payload: serde_json::Value,
 }
 
+/// 事件实际如何送达前端的抽象：默认实现 `TauriEventSink` 就是调用真实的
+/// `AppHandle::emit`，但把它抽成 trait 是为了让 `EventBuffer` 里合并/节流/
+/// 重试这套纯逻辑不必绑死一个真实的 webview——注入一个记录调用的内存
+/// sink 就能在没有 Tauri 运行时的情况下验证这部分逻辑。`AppHandle::emit`
+/// 本身就是同步调用，这里不需要 `async fn`。
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: &str, payload: &serde_json::Value) -> Result<(), String>;
+}
+
+/// 生产环境下唯一会用到的 `EventSink`：直接转发给真实的 `AppHandle`。
+struct TauriEventSink(AppHandle);
+
+impl EventSink for TauriEventSink {
+    fn emit(&self, event: &str, payload: &serde_json::Value) -> Result<(), String> {
+        self.0.emit(event, payload).map_err(|e| e.to_string())
+    }
+}
+
+/// `emit_event` 失败重试的次数上限与退避基数：第 N 次重试前等待
+/// `EMIT_RETRY_BASE_DELAY * N`。emit 失败多半是瞬时状况（比如前端还没
+/// 完全准备好接收事件），重试几次往往就够了；重试耗尽后才真正放弃，
+/// 不再像过去那样失败一次就无声丢掉这条缓冲事件。
+const EMIT_MAX_ATTEMPTS: u32 = 3;
+const EMIT_RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+
 /// 事件缓冲策略类型
 #[derive(Debug, Clone, Copy)]
 pub enum EventBufferStrategy {
@@ -29,26 +56,256 @@ pub enum EventBufferStrategy {
 struct BufferedEvent {
     data: BridgeEventData,
     last_time: Instant,
+    /// 这个 key 第一次被缓冲的时间，不会在后续的合并/节流更新里被刷新——
+    /// 专门留给"从进入缓冲到真正发出去"的延迟直方图用，`last_time` 反而
+    /// 不适合做这个，因为它每次合并都会被覆盖成最新一次更新的时间。
+    first_seen: Instant,
     count: u32,
 }
+
+/// `BufferedStore::upsert` 的结果：预算状态之外，还带上这次是不是合并进了
+/// 一个已有的缓冲项（而不是新建），调用方据此决定要不要计一次"合并丢弃"。
+struct UpsertOutcome {
+    is_update: bool,
+    over_budget: bool,
+}
+
+/// 没有显式设置预算时的默认上限：8MiB 足够容纳正常场景下的高基数事件
+/// （比如逐文件的 `file-processed`）而不至于让一次批量任务把内存吃光。
+const DEFAULT_BUDGET_BYTES: usize = 8 * 1024 * 1024;
+
+/// 定期 flush 任务在 `strategies`/路由表里都查不到这个事件的窗口配置时
+/// 用的兜底值，和 `handle_event` 里未命中任何配置时的默认策略保持一致。
+const DEFAULT_FLUSH_WINDOW: Duration = Duration::from_millis(500);
+
+/// 缓冲区已经空了、没有任何待到期事件时，定期 flush 任务退避到的轮询
+/// 间隔——避免缓冲区空着也每秒醒一次白跑一趟。
+const FLUSH_IDLE_BACKOFF: Duration = Duration::from_secs(5);
+
+/// 缓冲区非空但算出来的最近到期时间小于这个值时的下限，避免极短窗口
+/// （或者时钟误差）让任务变成忙等。
+const FLUSH_MIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// `buffered_events` 的实际存储：事件 map 和字节预算记账放在同一把锁
+/// 后面，避免「预算计数」和「map 内容」分两把锁更新时互相脱节。灵感来自
+/// Fuchsia archivist 的日志预算模型——不精确统计内存占用，只用 payload
+/// 序列化后的近似字节数记账，超出预算就主动驱逐一部分缓冲事件来提前
+/// 发送、回收空间，而不是任由 map 随事件名基数无限增长。
+struct BufferedStore {
+    events: HashMap<String, BufferedEvent>,
+    used_bytes: usize,
+    max_bytes: usize,
+}
+
+impl BufferedStore {
+    fn new(max_bytes: usize) -> Self {
+        Self {
+            events: HashMap::new(),
+            used_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    /// 近似估算一个事件占用的字节数：序列化后的 payload 大小，外加事件名
+    /// 本身的长度。不需要精确，只需要在同一基准下可比较。
+    fn approx_size(data: &BridgeEventData) -> usize {
+        serde_json::to_vec(&data.payload).map(|v| v.len()).unwrap_or(0) + data.event.len()
+    }
+
+    /// 插入或更新一个缓冲项并同步调整字节预算。
+    fn upsert(&mut self, key: String, data: BridgeEventData, now: Instant) -> UpsertOutcome {
+        let new_size = Self::approx_size(&data);
+        let is_update = if let Some(existing) = self.events.get_mut(&key) {
+            let old_size = Self::approx_size(&existing.data);
+            existing.data = data;
+            existing.last_time = now;
+            existing.count += 1;
+            self.used_bytes = self.used_bytes.saturating_sub(old_size) + new_size;
+            true
+        } else {
+            self.used_bytes += new_size;
+            self.events.insert(
+                key,
+                BufferedEvent {
+                    data,
+                    last_time: now,
+                    first_seen: now,
+                    count: 1,
+                },
+            );
+            false
+        };
+        UpsertOutcome {
+            is_update,
+            over_budget: self.used_bytes > self.max_bytes,
+        }
+    }
+
+    fn remove(&mut self, key: &str) -> Option<BufferedEvent> {
+        let removed = self.events.remove(key)?;
+        self.used_bytes = self.used_bytes.saturating_sub(Self::approx_size(&removed.data));
+        Some(removed)
+    }
+
+    fn drain_all(&mut self) -> Vec<BufferedEvent> {
+        let drained: Vec<BufferedEvent> = self.events.values().cloned().collect();
+        self.events.clear();
+        self.used_bytes = 0;
+        drained
+    }
+
+    /// 按"最旧优先"驱逐，直到用量回到预算以内。选最旧而不是最大，是因为
+    /// 一个还在持续更新的小事件不该被一个早就不再更新的大 payload 一直
+    /// 占着预算；返回被驱逐的事件，调用方负责把它们发出去并记一次驱逐。
+    fn evict_to_budget(&mut self) -> Vec<BufferedEvent> {
+        let mut evicted = Vec::new();
+        while self.used_bytes > self.max_bytes {
+            let Some(oldest_key) = self
+                .events
+                .iter()
+                .min_by_key(|(_, b)| b.last_time)
+                .map(|(k, _)| k.clone())
+            else {
+                break;
+            };
+            if let Some(buffered) = self.remove(&oldest_key) {
+                evicted.push(buffered);
+            } else {
+                break;
+            }
+        }
+        evicted
+    }
+}
+
+/// 路由匹配方式：精确匹配事件名，或按前缀匹配。仓库里没有通用的 glob
+/// crate（见 archive_scan.rs 头部注释里类似的取舍），前缀匹配已经够用——
+/// `multivector-*` 这类"同一子系统下的一组事件"用前缀就能表达。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RouteMatch {
+    Exact,
+    Prefix,
+}
+
+/// 一条事件命中路由后应该怎么处理，命中即生效、不再落回
+/// `configure_strategies` 里写死的默认策略。
+#[derive(Clone)]
+pub enum RouteAction {
+    /// 立即转发到前端，不经过缓冲/节流
+    Immediate,
+    /// 延迟合并，窗口内只发送最后一次（复用现有的合并机制）
+    Buffered(Duration),
+    /// 节流，限制发送频率（复用现有的节流机制）
+    Throttled(Duration),
+    /// 直接丢弃：既不转发给前端，也不进入缓冲
+    Drop,
+    /// 转发给某个专门订阅这类事件的处理器，而不是走"发给前端"的默认路径，
+    /// 比如某个子系统想自己消费这个事件、不需要 webview 知道。处理器自己
+    /// 负责把 `payload` 反序列化成需要的类型。
+    Custom(Arc<dyn Fn(BridgeEventData) + Send + Sync>),
+}
+
+struct EventRoute {
+    pattern: String,
+    match_kind: RouteMatch,
+    action: RouteAction,
+}
+
+/// 按事件名路由的处理表：在 `EventBuffer::handle_event` 里，先检查这张表，
+/// 命中就按声明的 `RouteAction` 处理并提前返回；没命中任何路由的事件才
+/// 继续走 `configure_strategies` 写死的默认策略。新功能要接管自己的事件，
+/// 调用 `EventBuffer::register_route`/`register_prefix_route` 即可，不需要
+/// 回来改这个文件里的集中分发逻辑。
+#[derive(Default)]
+struct EventRouteTable {
+    routes: Vec<EventRoute>,
+}
+
+impl EventRouteTable {
+    fn register(&mut self, event_name: String, action: RouteAction) {
+        self.routes.push(EventRoute {
+            pattern: event_name,
+            match_kind: RouteMatch::Exact,
+            action,
+        });
+    }
+
+    fn register_prefix(&mut self, prefix: String, action: RouteAction) {
+        self.routes.push(EventRoute {
+            pattern: prefix,
+            match_kind: RouteMatch::Prefix,
+            action,
+        });
+    }
+
+    /// 按注册顺序找第一条匹配的路由；同一个事件名如果被多条路由命中，
+    /// 先注册的生效。
+    fn resolve(&self, event_name: &str) -> Option<RouteAction> {
+        self.routes
+            .iter()
+            .find(|route| match route.match_kind {
+                RouteMatch::Exact => route.pattern == event_name,
+                RouteMatch::Prefix => event_name.starts_with(route.pattern.as_str()),
+            })
+            .map(|route| route.action.clone())
+    }
+}
+
 /// 智能事件缓冲器
 pub struct EventBuffer {
-    app_handle: AppHandle,
-    buffered_events: Arc<RwLock<HashMap<String, BufferedEvent>>>,
-    strategies: HashMap<String, EventBufferStrategy>,
+    sink: Arc<dyn EventSink>,
+    buffered_events: Arc<RwLock<BufferedStore>>,
+    strategies: Arc<RwLock<HashMap<String, EventBufferStrategy>>>,
+    /// 显式静音的事件名集合：命中的事件在进入缓冲之前就被丢弃，既不缓冲
+    /// 也不转发给前端。默认是空集合，也就是默认所有事件都正常处理——
+    /// 只有调用过 `mute` 的事件才会被拦下，这样现有调用方不需要先手动
+    /// "订阅" 自己已经在用的事件名，行为不会因为引入这个机制而突变。
+    muted: Arc<RwLock<HashSet<String>>>,
+    route_table: Arc<RwLock<EventRouteTable>>,
+    /// 可选的可观测性导出器：没配置外部 ingest 端点时是 `None`，完全不
+    /// 影响现有行为；调用 `enable_diagnostics` 后每个桥接事件和缓冲器
+    /// 自己的生命周期事件才会被镜像一份过去。
+    exporter: Arc<RwLock<Option<Arc<DiagnosticsExporter>>>>,
+    /// 运行时指标：接收/合并/发送/失败计数器，缓冲区大小 gauge，合并到
+    /// 发送耗时的直方图。始终开启，开销是几个原子操作/一把轻量互斥锁，
+    /// 不像 `exporter` 那样需要显式打开。
+    metrics: Arc<EventBufferMetrics>,
+    /// 有新事件被缓冲（而不是立即转发）时唤醒 `start_flush_task`，让它
+    /// 重新按最新的 deadline 计算该睡多久——否则一个事件可能在任务刚进入
+    /// `FLUSH_IDLE_BACKOFF`（5秒）退避之后才到达，即使它自己配置的窗口只有
+    /// 几百毫秒，也得白等到下一次退避醒来才会被发现。
+    flush_wakeup: Arc<Notify>,
 }
 
 impl EventBuffer {
-    /// 创建新的事件缓冲器
+    /// 创建新的事件缓冲器，发送侧用真实的 `AppHandle`，字节预算用默认值
     pub fn new(app_handle: AppHandle) -> Self {
+        Self::new_with_budget(app_handle, DEFAULT_BUDGET_BYTES)
+    }
+
+    /// 创建新的事件缓冲器，并显式指定缓冲区的字节预算上限
+    pub fn new_with_budget(app_handle: AppHandle, max_bytes: usize) -> Self {
+        Self::with_sink(Arc::new(TauriEventSink(app_handle)), max_bytes)
+    }
+
+    /// 用指定的 `EventSink` 和字节预算构造缓冲器。`new`/`new_with_budget`
+    /// 只是在外面包了一层 `TauriEventSink`；这个入口单独留出来，是为了在
+    /// 不起一个真实 webview 的情况下就能验证合并/节流/重试/预算驱逐这套
+    /// 纯逻辑——注入一个记录调用、可以配置"先失败 N 次"的内存 sink 即可。
+    pub(crate) fn with_sink(sink: Arc<dyn EventSink>, max_bytes: usize) -> Self {
         let mut strategies = HashMap::new();
 
         // 配置不同事件的缓冲策略
         Self::configure_strategies(&mut strategies);
         let buffer = Self {
-            app_handle,
-            buffered_events: Arc::new(RwLock::new(HashMap::new())),
-            strategies,
+            sink,
+            buffered_events: Arc::new(RwLock::new(BufferedStore::new(max_bytes))),
+            strategies: Arc::new(RwLock::new(strategies)),
+            muted: Arc::new(RwLock::new(HashSet::new())),
+            route_table: Arc::new(RwLock::new(EventRouteTable::default())),
+            exporter: Arc::new(RwLock::new(None)),
+            metrics: Arc::new(EventBufferMetrics::new()),
+            flush_wakeup: Arc::new(Notify::new()),
         };
 
         // 启动定期清理任务
@@ -149,16 +406,153 @@ impl EventBuffer {
         // 注意：未在此配置的事件类型将使用默认策略（500ms延迟合并）
     }
 
+    /// 为某个事件名注册一条路由：命中时直接按 `action` 处理，不进入
+    /// `configure_strategies` 里写死的默认策略表。
+    pub async fn register_route(&self, event_name: impl Into<String>, action: RouteAction) {
+        self.route_table
+            .write()
+            .await
+            .register(event_name.into(), action);
+    }
+
+    /// 同 `register_route`，但按前缀匹配一组事件名（比如同一个子系统下所有
+    /// `multivector-*` 事件）。
+    pub async fn register_prefix_route(&self, prefix: impl Into<String>, action: RouteAction) {
+        self.route_table
+            .write()
+            .await
+            .register_prefix(prefix.into(), action);
+    }
+
+    /// 运行期覆盖某个事件的缓冲策略，立即生效——不需要等下一次发布重新
+    /// 构造 `EventBuffer`。modeled after Fuchsia archivist LogsRepository
+    /// 的 interest selector：前端某个视图只关心 `rag-progress` 时，可以把
+    /// 它的节流窗口调紧到接近实时，而不必在 `configure_strategies` 里
+    /// 把默认值写死。
+    pub async fn set_strategy(&self, event: impl Into<String>, strategy: EventBufferStrategy) {
+        self.strategies.write().await.insert(event.into(), strategy);
+    }
+
+    /// 静音一个事件：静音期间它会在 `handle_event` 里被直接丢弃，既不
+    /// 缓冲也不转发给前端——给只想看自己关心的事件、不想被
+    /// `file-tagging-progress` 这类高频噪音刷屏的后台视图用。
+    pub async fn mute(&self, event: impl Into<String>) {
+        self.muted.write().await.insert(event.into());
+    }
+
+    /// 取消静音，恢复成按 `strategies`（或路由表）正常处理
+    pub async fn unmute(&self, event: &str) {
+        self.muted.write().await.remove(event);
+    }
+
+    /// 打开可观测性导出：此后每个桥接事件、以及缓冲器自己的生命周期事件
+    /// （合并/节流丢弃/预算驱逐/flush/发送失败）都会额外镜像一份发给
+    /// `config.endpoint`。没调用这个之前，`exporter` 是 `None`，完全不
+    /// 产生额外开销。
+    pub async fn enable_diagnostics(&self, config: DiagnosticsExportConfig) {
+        *self.exporter.write().await = Some(DiagnosticsExporter::spawn(config));
+    }
+
+    /// 关闭可观测性导出，回到默认的零开销状态
+    pub async fn disable_diagnostics(&self) {
+        *self.exporter.write().await = None;
+    }
+
+    /// 往可观测性导出器镜像一条记录；没打开导出时是纯粹的读锁 + `None`
+    /// 检查，开销可以忽略。
+    async fn export(&self, kind: &str, event: &str, payload: serde_json::Value) {
+        if let Some(exporter) = self.exporter.read().await.clone() {
+            exporter.record(DiagnosticsRecord::new(kind, event, payload));
+        }
+    }
+
+    /// 拍一张当前指标快照：各计数器、缓冲区大小 gauge、合并到发送耗时的
+    /// 直方图，供需要观测缓冲效果的调用方使用（比如确认 `throttle` 到底
+    /// 拦下了多少次 `file-tagging-progress`）。
+    pub fn metrics_snapshot(&self) -> EventBufferMetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// 同 `metrics_snapshot`，但渲染成 Prometheus 文本 exposition 格式，
+    /// 方便直接喂给一个 `/metrics` 端点。
+    pub fn metrics_prometheus_text(&self) -> String {
+        self.metrics.render_prometheus_text()
+    }
+
+    /// 把当前缓冲区大小同步到 gauge；每次 `buffered_events` 的内容发生
+    /// 变化（插入/更新/移除/驱逐/排空）之后都应该调用一次。
+    async fn sync_buffered_gauge(&self) {
+        let store = self.buffered_events.read().await;
+        self.metrics.set_buffered(store.events.len(), store.used_bytes);
+    }
+
     /// 处理incoming事件
     pub async fn handle_event(&self, event_data: BridgeEventData) {
-        let strategy = self.strategies.get(&event_data.event).copied().unwrap_or(
-            EventBufferStrategy::DelayedMerge(Duration::from_millis(500)),
-        ); // 默认策略
+        self.export("received", &event_data.event, event_data.payload.clone())
+            .await;
+
+        if self.muted.read().await.contains(&event_data.event) {
+            println!("🔇 事件已静音，进入缓冲前丢弃: {}", event_data.event);
+            self.metrics.record_received(&event_data.event, "muted");
+            self.export("muted-dropped", &event_data.event, serde_json::Value::Null)
+                .await;
+            return;
+        }
+
+        let routed_action = self.route_table.read().await.resolve(&event_data.event);
+        if let Some(action) = routed_action {
+            match action {
+                RouteAction::Immediate => {
+                    println!("⚡ [路由] 立即转发事件: {}", event_data.event);
+                    self.metrics.record_received(&event_data.event, "routed-immediate");
+                    self.emit_event(&event_data).await;
+                }
+                RouteAction::Buffered(duration) => {
+                    println!(
+                        "🔄 [路由] 延迟合并事件: {} ({}秒窗口)",
+                        event_data.event,
+                        duration.as_secs()
+                    );
+                    self.metrics.record_received(&event_data.event, "routed-buffered");
+                    self.handle_delayed_merge(event_data, duration).await;
+                }
+                RouteAction::Throttled(duration) => {
+                    println!(
+                        "⏱️  [路由] 节流处理事件: {} ({}秒间隔)",
+                        event_data.event,
+                        duration.as_secs()
+                    );
+                    self.metrics.record_received(&event_data.event, "routed-throttled");
+                    self.handle_throttle(event_data, duration).await;
+                }
+                RouteAction::Drop => {
+                    println!("🗑️  [路由] 丢弃事件: {}", event_data.event);
+                    self.metrics.record_received(&event_data.event, "routed-drop");
+                    self.export("dropped", &event_data.event, serde_json::Value::Null)
+                        .await;
+                }
+                RouteAction::Custom(handler) => {
+                    println!("➡️  [路由] 转发给自定义处理器: {}", event_data.event);
+                    self.metrics.record_received(&event_data.event, "routed-custom");
+                    handler(event_data);
+                }
+            }
+            return;
+        }
+
+        let strategy = self
+            .strategies
+            .read()
+            .await
+            .get(&event_data.event)
+            .copied()
+            .unwrap_or(EventBufferStrategy::DelayedMerge(Duration::from_millis(500))); // 默认策略
 
         match strategy {
             EventBufferStrategy::Immediate => {
                 // 立即发送
                 println!("⚡ 立即转发事件: {}", event_data.event);
+                self.metrics.record_received(&event_data.event, "immediate");
                 self.emit_event(&event_data).await;
             }
             EventBufferStrategy::DelayedMerge(duration) => {
@@ -168,6 +562,7 @@ impl EventBuffer {
                     event_data.event,
                     duration.as_secs()
                 );
+                self.metrics.record_received(&event_data.event, "delayed_merge");
                 self.handle_delayed_merge(event_data, duration).await;
             }
             EventBufferStrategy::Throttle(duration) => {
@@ -177,6 +572,7 @@ impl EventBuffer {
                     event_data.event,
                     duration.as_secs()
                 );
+                self.metrics.record_received(&event_data.event, "throttle");
                 self.handle_throttle(event_data, duration).await;
             }
         }
@@ -184,129 +580,309 @@ impl EventBuffer {
 
     /// 处理延迟合并事件
     async fn handle_delayed_merge(&self, event_data: BridgeEventData, _duration: Duration) {
-        let mut events = self.buffered_events.write().await;
         let now = Instant::now();
-
         let event_key = event_data.event.clone();
 
-        if let Some(buffered) = events.get_mut(&event_key) {
-            // 更新existing缓冲事件
-            buffered.data = event_data; // 保持最新的payload
-            buffered.last_time = now;
-            buffered.count += 1;
-        } else {
-            // 创建新的缓冲事件
-            events.insert(
-                event_key,
-                BufferedEvent {
-                    data: event_data,
-                    last_time: now,
-                    count: 1,
-                },
-            );
+        let outcome = {
+            let mut store = self.buffered_events.write().await;
+            store.upsert(event_key, event_data.clone(), now)
+        };
+        self.sync_buffered_gauge().await;
+        // 唤醒定期 flush 任务：新插入的项可能比任务正在睡的 deadline 早得多
+        // （比如任务刚退避进 `FLUSH_IDLE_BACKOFF`），不叫醒它的话只能等到
+        // 那次退避结束才会被发现。
+        self.flush_wakeup.notify_one();
+
+        if outcome.is_update {
+            self.metrics.record_merged(&event_data.event);
+            self.export("merged", &event_data.event, event_data.payload.clone())
+                .await;
+        }
+
+        if outcome.over_budget {
+            self.evict_over_budget().await;
         }
     }
 
     /// 处理节流事件
     async fn handle_throttle(&self, event_data: BridgeEventData, duration: Duration) {
-        let mut events = self.buffered_events.write().await;
         let now = Instant::now();
         let event_key = event_data.event.clone();
 
-        if let Some(buffered) = events.get(&event_key) {
-            // 检查是否超过了节流间隔
-            if now.duration_since(buffered.last_time) < duration {
-                // 还在节流期内，更新数据但不发送
-                let mut updated = buffered.clone();
-                updated.data = event_data;
-                updated.last_time = now;
-                updated.count += 1;
-                events.insert(event_key, updated);
-                return;
-            }
+        let (should_emit, outcome) = {
+            let mut store = self.buffered_events.write().await;
+            let still_throttled = store
+                .events
+                .get(&event_key)
+                .is_some_and(|buffered| now.duration_since(buffered.last_time) < duration);
+            let outcome = store.upsert(event_key, event_data.clone(), now);
+            (!still_throttled, outcome)
+        };
+        self.sync_buffered_gauge().await;
+        self.flush_wakeup.notify_one();
+
+        if should_emit {
+            // 超过节流间隔或是首次发送，立即发送
+            self.emit_event(&event_data).await;
+        } else {
+            self.metrics.record_merged(&event_data.event);
+            self.export("throttled-dropped", &event_data.event, event_data.payload.clone())
+                .await;
+        }
+
+        if outcome.over_budget {
+            self.evict_over_budget().await;
+        }
+    }
+
+    /// 驱逐最旧的缓冲事件直到用量回到预算以内，并把被驱逐的事件正常发出去
+    /// （而不是静默丢弃），同时打一条驱逐日志方便排查是谁把预算吃满的。
+    async fn evict_over_budget(&self) {
+        let evicted = {
+            let mut store = self.buffered_events.write().await;
+            store.evict_to_budget()
+        };
+        self.sync_buffered_gauge().await;
+
+        if evicted.is_empty() {
+            return;
         }
 
-        // 超过节流间隔或是首次发送，立即发送并更新记录
-        events.insert(
-            event_key,
-            BufferedEvent {
-                data: event_data.clone(),
-                last_time: now,
-                count: 1,
-            },
+        eprintln!(
+            "⚠️ 事件缓冲区超出字节预算，提前驱逐 {} 个最旧的缓冲事件: {}",
+            evicted.len(),
+            evicted
+                .iter()
+                .map(|e| e.data.event.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
         );
 
-        // 发送事件
-        drop(events); // 提前释放锁
-        self.emit_event(&event_data).await;
+        let now = Instant::now();
+        for buffered in &evicted {
+            self.metrics
+                .observe_merge_to_emit_latency(now.duration_since(buffered.first_seen));
+            self.export("evicted", &buffered.data.event, buffered.data.payload.clone())
+                .await;
+            self.emit_event(&buffered.data).await;
+        }
     }
 
-    /// 发送事件到前端
+    /// 发送事件到前端，失败时按 `EMIT_MAX_ATTEMPTS` 做有限次数的退避重试
     async fn emit_event(&self, event_data: &BridgeEventData) {
-        if let Err(e) = self.app_handle.emit(&event_data.event, &event_data.payload) {
-            eprintln!("❌ 发送桥接事件到前端失败: {} - {}", event_data.event, e);
-        } else {
-            println!(
-                "📤 桥接事件已发送到前端: {} (payload: {}字节)",
-                event_data.event,
-                serde_json::to_string(&event_data.payload)
-                    .unwrap_or_default()
-                    .len()
-            );
+        let exporter = self.exporter.read().await.clone();
+        Self::emit_with_retry(&self.sink, event_data, &exporter, &self.metrics).await;
+    }
+
+    /// 对一次发送做有限次数的指数退避重试，重试耗尽后才打日志放弃；成功/
+    /// 最终失败都会镜像一条 `flushed`/`emit-failed` 记录给可观测性导出器
+    /// （如果打开了的话），并分别计入 `events_emitted`/`emit_failures` 计数器。
+    async fn emit_with_retry(
+        sink: &Arc<dyn EventSink>,
+        event_data: &BridgeEventData,
+        exporter: &Option<Arc<DiagnosticsExporter>>,
+        metrics: &Arc<EventBufferMetrics>,
+    ) {
+        for attempt in 1..=EMIT_MAX_ATTEMPTS {
+            match sink.emit(&event_data.event, &event_data.payload) {
+                Ok(()) => {
+                    println!(
+                        "📤 桥接事件已发送到前端: {} (payload: {}字节)",
+                        event_data.event,
+                        serde_json::to_string(&event_data.payload)
+                            .unwrap_or_default()
+                            .len()
+                    );
+                    metrics.record_emitted(&event_data.event);
+                    if let Some(exporter) = exporter {
+                        exporter.record(DiagnosticsRecord::new(
+                            "flushed",
+                            &event_data.event,
+                            event_data.payload.clone(),
+                        ));
+                    }
+                    return;
+                }
+                Err(e) if attempt < EMIT_MAX_ATTEMPTS => {
+                    let delay = EMIT_RETRY_BASE_DELAY * attempt;
+                    eprintln!(
+                        "⚠️ 发送桥接事件失败，{}ms 后重试（第 {}/{} 次）: {} - {}",
+                        delay.as_millis(),
+                        attempt,
+                        EMIT_MAX_ATTEMPTS,
+                        event_data.event,
+                        e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => {
+                    eprintln!(
+                        "❌ 发送桥接事件到前端失败，已重试 {} 次仍未成功: {} - {}",
+                        EMIT_MAX_ATTEMPTS, event_data.event, e
+                    );
+                    metrics.record_emit_failure(&event_data.event);
+                    if let Some(exporter) = exporter {
+                        exporter.record(DiagnosticsRecord::new(
+                            "emit-failed",
+                            &event_data.event,
+                            serde_json::json!({ "error": e.to_string() }),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// 立即发送所有缓冲中的事件，不等待各自的合并/节流窗口到期。
+    /// 用于优雅关闭时排空尚未发出的事件，避免进程退出时丢失。
+    pub async fn flush_all(&self) {
+        let drained: Vec<BufferedEvent> = {
+            let mut store = self.buffered_events.write().await;
+            store.drain_all()
+        };
+        self.sync_buffered_gauge().await;
+
+        println!("[SHUTDOWN] 排空事件缓冲区，待发送 {} 个事件", drained.len());
+        let now = Instant::now();
+        for buffered in drained {
+            self.metrics
+                .observe_merge_to_emit_latency(now.duration_since(buffered.first_seen));
+            self.emit_event(&buffered.data).await;
+        }
+    }
+
+    /// 某个缓冲项距上次更新多久之后应该被定期 flush 任务发送：优先读
+    /// `configure_strategies` 里为这个事件配的真实窗口（`DelayedMerge`/
+    /// `Throttle` 的那个 `Duration`），不再在这里重复写一份可能会和配置
+    /// 慢慢漂移的常量；查不到配置（比如是 route_table 临时指定窗口的
+    /// 事件）才退回 `DEFAULT_FLUSH_WINDOW`。
+    fn flush_window_for(
+        strategies: &HashMap<String, EventBufferStrategy>,
+        key: &str,
+    ) -> Duration {
+        match strategies.get(key) {
+            Some(EventBufferStrategy::DelayedMerge(d) | EventBufferStrategy::Throttle(d)) => *d,
+            Some(EventBufferStrategy::Immediate) => Duration::ZERO,
+            None => DEFAULT_FLUSH_WINDOW,
         }
     }
 
     /// 启动定期flush任务
     fn start_flush_task(&self) {
         let buffered_events = self.buffered_events.clone();
-        let app_handle = self.app_handle.clone();
+        let sink = self.sink.clone();
+        let strategies = self.strategies.clone();
+        let exporter = self.exporter.clone();
+        let metrics = self.metrics.clone();
+        let flush_wakeup = self.flush_wakeup.clone();
 
         tokio::spawn(async move {
-            let mut interval = interval(Duration::from_millis(1000)); // 每秒检查一次
+            // 下一次醒来前要睡多久：缓冲区里还有未到期的事件时，直接睡到
+            // 最近那个到期时间（类似 timer wheel，按需醒来而不是固定节拍）；
+            // 缓冲区空了就退避到 `FLUSH_IDLE_BACKOFF`，不再每秒空转一次。
+            // 退避期间如果有新事件被缓冲，`handle_delayed_merge`/
+            // `handle_throttle` 会通过 `flush_wakeup` 提前叫醒这个循环，
+            // 否则一个 500ms 窗口的事件可能在刚进入 5s 退避之后到达，得
+            // 白等到退避结束才被处理——`select!` 两者，谁先到就先醒。
+            let mut sleep_for = DEFAULT_FLUSH_WINDOW;
 
             loop {
-                interval.tick().await;
+                tokio::select! {
+                    _ = tokio::time::sleep(sleep_for) => {}
+                    _ = flush_wakeup.notified() => {}
+                }
 
-                let mut events_to_send = Vec::new();
+                let mut events_to_send: Vec<(BridgeEventData, Instant)> = Vec::new();
+                let mut next_deadline: Option<Duration> = None;
                 let now = Instant::now();
+                // 每个 tick 开始时拍一张策略快照：`set_strategy` 随时可能从
+                // 另一个任务改它，但同一次 tick 内部用同一份快照判断，不需要
+                // 在持有 `buffered_events` 写锁时再去抢 `strategies` 的锁。
+                let strategies_snapshot = strategies.read().await.clone();
+                let exporter_snapshot = exporter.read().await.clone();
 
-                // 获取需要发送的事件
+                // 获取需要发送的事件，一次 tick 里凑齐所有到期的事件一起处理
                 {
-                    let mut events = buffered_events.write().await;
+                    let mut store = buffered_events.write().await;
                     let mut keys_to_remove = Vec::new();
 
-                    for (key, buffered) in events.iter() {
+                    for (key, buffered) in store.events.iter() {
                         let age = now.duration_since(buffered.last_time);
+                        let window = Self::flush_window_for(&strategies_snapshot, key);
 
-                        // 如果事件超过一定时间未更新，就发送它
-                        let should_send = match key.as_str() {
-                            "tags-updated" | "database-updated" => age >= Duration::from_secs(5),
-                            "task-completed" => age >= Duration::from_secs(2),
-                            "file-processed" => age >= Duration::from_secs(2),
-                            _ => age >= Duration::from_secs(1), // 默认1秒
-                        };
-
-                        if should_send {
-                            events_to_send.push(buffered.data.clone());
+                        if age >= window {
+                            events_to_send.push((buffered.data.clone(), buffered.first_seen));
                             keys_to_remove.push(key.clone());
+                        } else {
+                            let remaining = window - age;
+                            next_deadline =
+                                Some(next_deadline.map_or(remaining, |d| d.min(remaining)));
                         }
                     }
 
                     // 移除已发送的事件
                     for key in keys_to_remove {
-                        events.remove(&key);
+                        store.remove(&key);
                     }
                 }
+                metrics.set_buffered(
+                    buffered_events.read().await.events.len(),
+                    buffered_events.read().await.used_bytes,
+                );
 
-                // 发送事件（在锁外部进行）
-                for event_data in events_to_send {
-                    if let Err(e) = app_handle.emit(&event_data.event, &event_data.payload) {
-                        eprintln!("❌ 定期flush时发送事件失败: {} - {}", event_data.event, e);
-                    } else {
-                        println!("⏰ 定期flush发送桥接事件: {} (延迟发送)", event_data.event);
-                    }
+                // 发送事件（在锁外部进行），一批一起处理完再决定下次睡多久
+                for (event_data, first_seen) in &events_to_send {
+                    metrics.observe_merge_to_emit_latency(now.duration_since(*first_seen));
+                    EventBuffer::emit_with_retry(&sink, event_data, &exporter_snapshot, &metrics)
+                        .await;
                 }
+
+                sleep_for = next_deadline
+                    .map(|d| d.max(FLUSH_MIN_POLL_INTERVAL))
+                    .unwrap_or(FLUSH_IDLE_BACKOFF);
             }
         });
     }
 }
+
+/// 生命周期事件的"最新状态"存储。和上面的 `EventBuffer`（面向 Python 桥接
+/// 事件的合并/节流，事件量大、时效性优先级各不相同）服务的是完全不同的
+/// 需求：`api-ready`/`file-monitor-error`/`api-fatal` 这类生命周期事件一次
+/// 应用生命周期里只会发生寥寥几次，但如果发生在主 webview 还没来得及注册
+/// 监听器之前，前端就会永远错过它、卡在等待状态——这里只是记住每个生命
+/// 周期事件名最近一次的 payload，新窗口/重新注册监听器之后可以随时通过
+/// `commands::sync_lifecycle` 把当前已知状态重放一遍。用 `std::sync::Mutex`
+/// 而不是上面的 `tokio::sync::RwLock`，是因为调用方既有异步的（API 就绪
+/// 流程），也有同步的（`api_startup::maybe_trigger_restart` 放弃自动恢复那
+/// 一刻），统一用轻量的同步锁更省心。
+#[derive(Default)]
+pub struct LifecycleEventStore {
+    latest: Mutex<HashMap<String, serde_json::Value>>,
+}
+
+impl LifecycleEventStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个生命周期事件的最新 payload，同时照常转发给所有当前监听器——
+    /// 已经在监听的窗口不需要等 `sync_lifecycle`，照样实时收到。
+    pub fn record_and_emit(&self, app_handle: &AppHandle, event: &str, payload: serde_json::Value) {
+        self.latest
+            .lock()
+            .unwrap()
+            .insert(event.to_string(), payload.clone());
+        if let Err(e) = app_handle.emit(event, &payload) {
+            eprintln!("❌ 发送生命周期事件失败: {} - {}", event, e);
+        }
+    }
+
+    /// 把当前记录的所有生命周期事件状态重放给某一个窗口，供刚注册完监听器
+    /// 的 webview 补上可能错过的事件。
+    pub fn replay_to(&self, window: &WebviewWindow) {
+        let latest = self.latest.lock().unwrap();
+        for (event, payload) in latest.iter() {
+            let _ = window.emit(event, payload);
+        }
+    }
+}
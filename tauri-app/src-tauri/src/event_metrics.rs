@@ -0,0 +1,250 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 极简的按标签分桶计数器：不追求 lock-free，只要在 `metrics_snapshot`
+/// 这种低频读取场景下够用。灵感来自 frugalos_mds 里 Counter/Gauge/
+/// Histogram 那套 Prometheus 风格的自用指标封装。
+#[derive(Default)]
+struct LabeledCounter {
+    values: Mutex<HashMap<String, u64>>,
+}
+
+impl LabeledCounter {
+    fn incr(&self, label: &str) {
+        *self.values.lock().unwrap().entry(label.to_string()).or_insert(0) += 1;
+    }
+
+    fn snapshot(&self) -> HashMap<String, u64> {
+        self.values.lock().unwrap().clone()
+    }
+}
+
+/// 直方图的桶边界（毫秒），固定边界、不做分位数估计——只要能看出"大多数
+/// 事件是不是在窗口内及时发出去了"就够用。超过最后一个边界的全部落进
+/// "+Inf" 桶（snapshot 里体现为比 `le_ms` 数组多出来的最后一个 bucket）。
+const HISTOGRAM_BUCKETS_MS: &[u64] = &[10, 50, 100, 250, 500, 1000, 2000, 5000, 10000];
+
+struct LatencyHistogram {
+    bucket_counts: Mutex<Vec<u64>>,
+    count: AtomicU64,
+    sum_ms: AtomicU64,
+}
+
+impl LatencyHistogram {
+    fn new() -> Self {
+        Self {
+            bucket_counts: Mutex::new(vec![0; HISTOGRAM_BUCKETS_MS.len() + 1]),
+            count: AtomicU64::new(0),
+            sum_ms: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, duration: Duration) {
+        let ms = duration.as_millis() as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.sum_ms.fetch_add(ms, Ordering::Relaxed);
+
+        let idx = HISTOGRAM_BUCKETS_MS
+            .iter()
+            .position(|&bound| ms <= bound)
+            .unwrap_or(HISTOGRAM_BUCKETS_MS.len());
+        self.bucket_counts.lock().unwrap()[idx] += 1;
+    }
+
+    fn snapshot(&self) -> HistogramSnapshot {
+        let bucket_counts = self.bucket_counts.lock().unwrap();
+        let mut buckets: Vec<HistogramBucket> = HISTOGRAM_BUCKETS_MS
+            .iter()
+            .zip(bucket_counts.iter())
+            .map(|(&le_ms, &count)| HistogramBucket { le_ms, count })
+            .collect();
+        buckets.push(HistogramBucket {
+            le_ms: u64::MAX,
+            count: bucket_counts[HISTOGRAM_BUCKETS_MS.len()],
+        });
+
+        HistogramSnapshot {
+            buckets,
+            count: self.count.load(Ordering::Relaxed),
+            sum_ms: self.sum_ms.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBucket {
+    pub le_ms: u64,
+    pub count: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramSnapshot {
+    pub buckets: Vec<HistogramBucket>,
+    pub count: u64,
+    pub sum_ms: u64,
+}
+
+/// `EventBuffer` 的可序列化指标快照，供 `metrics_snapshot()`/`/metrics`
+/// 文本 exposition 使用。各计数器的 key 是 `"事件名|策略标签"`（或者单纯
+/// 事件名，没有策略维度的地方），拍快照的时候原样带出去，渲染 Prometheus
+/// 文本时再拆成 label。
+#[derive(Debug, Clone, Serialize)]
+pub struct EventBufferMetricsSnapshot {
+    pub events_received: HashMap<String, u64>,
+    pub events_merged: HashMap<String, u64>,
+    pub events_emitted: HashMap<String, u64>,
+    pub emit_failures: HashMap<String, u64>,
+    pub buffered_count: i64,
+    pub buffered_bytes: i64,
+    pub merge_to_emit_latency_ms: HistogramSnapshot,
+}
+
+/// `EventBuffer` 的运行时指标：事件接收/合并丢弃/发送成功/发送失败各一个
+/// 按标签分桶的计数器，缓冲区当前大小的两个 gauge，以及一个"从进入
+/// 合并/节流缓冲到真正发出去"耗时的直方图——用来衡量每种缓冲策略到底
+/// 拦下了多少事件、拦了多久，而不是靠盯着 `println!` 猜。
+pub struct EventBufferMetrics {
+    events_received: LabeledCounter,
+    events_merged: LabeledCounter,
+    events_emitted: LabeledCounter,
+    emit_failures: LabeledCounter,
+    buffered_count: AtomicI64,
+    buffered_bytes: AtomicI64,
+    merge_to_emit_latency: LatencyHistogram,
+}
+
+impl EventBufferMetrics {
+    pub fn new() -> Self {
+        Self {
+            events_received: LabeledCounter::default(),
+            events_merged: LabeledCounter::default(),
+            events_emitted: LabeledCounter::default(),
+            emit_failures: LabeledCounter::default(),
+            buffered_count: AtomicI64::new(0),
+            buffered_bytes: AtomicI64::new(0),
+            merge_to_emit_latency: LatencyHistogram::new(),
+        }
+    }
+
+    /// 记一次事件接收，`strategy_label` 是这个事件按什么方式处理的
+    /// （比如 `"delayed_merge"`/`"throttle"`/`"routed-immediate"`/`"muted"`）。
+    pub fn record_received(&self, event: &str, strategy_label: &str) {
+        self.events_received.incr(&Self::label(event, strategy_label));
+    }
+
+    /// 记一次"合并丢弃"：一个新到的事件没有单独发出去，而是被并入了一个
+    /// 已有的缓冲项（`DelayedMerge` 的合并，或者 `Throttle` 还在节流期内
+    /// 的更新）。
+    pub fn record_merged(&self, event: &str) {
+        self.events_merged.incr(event);
+    }
+
+    pub fn record_emitted(&self, event: &str) {
+        self.events_emitted.incr(event);
+    }
+
+    pub fn record_emit_failure(&self, event: &str) {
+        self.emit_failures.incr(event);
+    }
+
+    /// 记一次"从进入缓冲到真正发出去"的耗时，用来衡量缓冲窗口的实际效果
+    pub fn observe_merge_to_emit_latency(&self, duration: Duration) {
+        self.merge_to_emit_latency.observe(duration);
+    }
+
+    pub fn set_buffered(&self, count: usize, bytes: usize) {
+        self.buffered_count.store(count as i64, Ordering::Relaxed);
+        self.buffered_bytes.store(bytes as i64, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> EventBufferMetricsSnapshot {
+        EventBufferMetricsSnapshot {
+            events_received: self.events_received.snapshot(),
+            events_merged: self.events_merged.snapshot(),
+            events_emitted: self.events_emitted.snapshot(),
+            emit_failures: self.emit_failures.snapshot(),
+            buffered_count: self.buffered_count.load(Ordering::Relaxed),
+            buffered_bytes: self.buffered_bytes.load(Ordering::Relaxed),
+            merge_to_emit_latency_ms: self.merge_to_emit_latency.snapshot(),
+        }
+    }
+
+    /// 渲染成 Prometheus 文本 exposition 格式，给一个可选的 `/metrics`
+    /// 端点用。
+    pub fn render_prometheus_text(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+
+        Self::render_counter(
+            &mut out,
+            "event_buffer_events_received_total",
+            &snapshot.events_received,
+        );
+        Self::render_counter(
+            &mut out,
+            "event_buffer_events_merged_total",
+            &snapshot.events_merged,
+        );
+        Self::render_counter(
+            &mut out,
+            "event_buffer_events_emitted_total",
+            &snapshot.events_emitted,
+        );
+        Self::render_counter(
+            &mut out,
+            "event_buffer_emit_failures_total",
+            &snapshot.emit_failures,
+        );
+
+        out.push_str(&format!(
+            "event_buffer_buffered_count {}\n",
+            snapshot.buffered_count
+        ));
+        out.push_str(&format!(
+            "event_buffer_buffered_bytes {}\n",
+            snapshot.buffered_bytes
+        ));
+
+        out.push_str(&format!(
+            "event_buffer_merge_to_emit_latency_ms_sum {}\n",
+            snapshot.merge_to_emit_latency_ms.sum_ms
+        ));
+        out.push_str(&format!(
+            "event_buffer_merge_to_emit_latency_ms_count {}\n",
+            snapshot.merge_to_emit_latency_ms.count
+        ));
+        for bucket in &snapshot.merge_to_emit_latency_ms.buckets {
+            let le = if bucket.le_ms == u64::MAX {
+                "+Inf".to_string()
+            } else {
+                bucket.le_ms.to_string()
+            };
+            out.push_str(&format!(
+                "event_buffer_merge_to_emit_latency_ms_bucket{{le=\"{}\"}} {}\n",
+                le, bucket.count
+            ));
+        }
+
+        out
+    }
+
+    fn label(event: &str, strategy_label: &str) -> String {
+        format!("{}|{}", event, strategy_label)
+    }
+
+    fn render_counter(out: &mut String, name: &str, values: &HashMap<String, u64>) {
+        for (label, value) in values {
+            if let Some((event, strategy)) = label.split_once('|') {
+                out.push_str(&format!(
+                    "{}{{event=\"{}\",strategy=\"{}\"}} {}\n",
+                    name, event, strategy, value
+                ));
+            } else {
+                out.push_str(&format!("{}{{event=\"{}\"}} {}\n", name, label, value));
+            }
+        }
+    }
+}
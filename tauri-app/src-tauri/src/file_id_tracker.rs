@@ -0,0 +1,46 @@
+//! 文件身份（file-id）探测：判断一次 notify 回调报告的路径当下是否还对应
+//! 一个真实存在的文件，以及（在 Unix 上）两次先后出现的路径是不是同一个
+//! 底层文件——用一次 `stat` 同时拿到"存在与否"和"身份"两个信息，避免
+//! `path.exists() && path.is_file()` 这种先后两次独立系统调用之间的竞态
+//! 窗口（文件可能在两次调用之间被删除/替换）。
+//!
+//! 本来这类"跨越防抖窗口追踪文件身份，把拆成两半的 rename 事件重新拼成
+//! 一次移动"的事，`notify-debouncer-full` 的 `FileIdMap` 天然支持，但这棵
+//! 树没有 Cargo.toml，没法引入这个额外依赖（同样的取舍见 `resource_limits`/
+//! `process_tree` 模块头注释）。Unix 上 inode+设备号（`st_dev`/`st_ino`）在
+//! 文件存活期间保持稳定、rename 前后不变，足够替代真正的 FileIdMap 做
+//! "这确实是同一个文件"的判断；Windows 对应的文件索引号需要
+//! `std::os::windows::fs::MetadataExt::file_index`，那是一个 unstable
+//! feature（`windows_by_handle`），稳定工具链拿不到，所以该平台上
+//! [`file_id`] 总是返回 `None`，调用方据此退化为只用"是否存在"判断。
+
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileId {
+    #[cfg(unix)]
+    dev: u64,
+    #[cfg(unix)]
+    ino: u64,
+}
+
+/// 对 `path` 做一次 `stat`：文件存在且是普通文件时返回 `Some`（Unix 上带
+/// 身份信息，其它平台上是一个不携带信息的占位值，仅用于表达"存在"）；
+/// 不存在、无权限或不是普通文件都归一为 `None`。
+pub fn file_id(path: &Path) -> Option<FileId> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_file() {
+        return None;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(FileId { dev: metadata.dev(), ino: metadata.ino() })
+    }
+
+    #[cfg(not(unix))]
+    {
+        Some(FileId {})
+    }
+}
@@ -13,6 +13,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue; // For extra_data in FileFilterRuleRust
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tauri::Emitter;
@@ -23,15 +24,18 @@ use walkdir::WalkDir;
 
 // --- Blacklist Trie for Hierarchical Blacklisting ---
 #[derive(Debug, Default, Clone)]
-struct BlacklistTrieNode {
+pub(crate) struct BlacklistTrieNode {
     children: std::collections::HashMap<String, BlacklistTrieNode>,
     is_blacklisted_here: bool, // True if the path ending at this node is explicitly blacklisted
 }
 
 impl BlacklistTrieNode {
-    // Inserts a path into the Trie.
+    // Inserts a path into the Trie. `case_sensitive` controls whether path
+    // components are folded to lowercase before becoming Trie keys (see
+    // `normalize_path_component`); pass the same value used when querying
+    // the same Trie, otherwise inserted and looked-up keys won't line up.
     // Paths are expected to be absolute and components UTF-8.
-    fn insert(&mut self, path: &Path) {
+    fn insert(&mut self, path: &Path, case_sensitive: bool) {
         let mut current_node = self;
         // Handle the case where the root "/" itself is blacklisted.
         if path.components().count() == 1 && path.has_root() {
@@ -45,7 +49,8 @@ impl BlacklistTrieNode {
             match component {
                 std::path::Component::Normal(os_str) => {
                     if let Some(name) = os_str.to_str() {
-                        current_node = current_node.children.entry(name.to_string()).or_default();
+                        let key = normalize_path_component(name, case_sensitive);
+                        current_node = current_node.children.entry(key).or_default();
                     } else {
                         eprintln!(
                             "[BLACKLIST_TRIE] Non-UTF8 path component in blacklist path: {:?}",
@@ -66,8 +71,10 @@ impl BlacklistTrieNode {
     }
 
     // Checks if the given path or any of its ancestors are in the Trie and marked as blacklisted.
+    // `case_sensitive` must match whatever was passed to `insert` when this
+    // Trie was built, see `normalize_path_component`.
     // Path is assumed to be absolute.
-    fn is_path_or_ancestor_blacklisted(&self, path: &Path) -> bool {
+    pub(crate) fn is_path_or_ancestor_blacklisted(&self, path: &Path, case_sensitive: bool) -> bool {
         let mut current_node = self;
 
         // Check if the root of the trie itself is blacklisted (e.g., if "/" was inserted).
@@ -79,7 +86,8 @@ impl BlacklistTrieNode {
             match component {
                 std::path::Component::Normal(os_str) => {
                     if let Some(name) = os_str.to_str() {
-                        if let Some(next_node) = current_node.children.get(name) {
+                        let key = normalize_path_component(name, case_sensitive);
+                        if let Some(next_node) = current_node.children.get(&key) {
                             if next_node.is_blacklisted_here {
                                 return true; // This path component or an ancestor forms a blacklisted path.
                             }
@@ -110,6 +118,37 @@ impl BlacklistTrieNode {
         false
     }
 }
+
+// 把一个路径分量折叠成 Trie 查找键：`case_sensitive` 为 false（默认，对应
+// macOS/Windows 默认的大小写不敏感文件系统）时统一转小写，让 `/Cache` 和
+// `/cache` 落在 Trie 的同一个节点上；为 true（用户把卷格式化成大小写敏感的
+// HFS+/APFS）时原样保留，恢复精确匹配。
+fn normalize_path_component(name: &str, case_sensitive: bool) -> String {
+    if case_sensitive {
+        name.to_string()
+    } else {
+        name.to_lowercase()
+    }
+}
+
+// 根据 monitored_folders 里标记为黑名单的条目重建一棵 Trie，用于按路径前缀
+// 快速判断"该路径或其祖先目录是否被显式拉黑"。file_scanner 的按需扫描在
+// WalkDir 的 filter_entry 里调用它做目录级别的剪枝（命中就整棵子树不再展开），
+// 语义和 FileMonitor 自己实时监控用的 `blacklist_trie` 字段一致，但各自独立
+// 重建，不共享同一份缓存实例。`case_sensitive` 见 `AllConfigurations::case_sensitive_paths`，
+// 必须和后续查询该 Trie 时传入的值一致。
+pub(crate) fn build_blacklist_trie(
+    monitored_folders: &[MonitoredDirectory],
+    case_sensitive: bool,
+) -> BlacklistTrieNode {
+    let mut trie = BlacklistTrieNode::default();
+    for dir in monitored_folders {
+        if dir.is_blacklist {
+            trie.insert(&PathBuf::from(&dir.path), case_sensitive);
+        }
+    }
+    trie
+}
 // --- End of Blacklist Trie ---
 
 // 文件监控统计信息
@@ -119,6 +158,36 @@ pub struct MonitorStats {
     pub filtered_files: u64,   // 被过滤的文件数量
     pub filtered_bundles: u64, // 处理的macOS包数量（改为只计数，不过滤）
     pub error_count: u64,      // 处理错误次数
+    // `filtered_files` 的子集：具体因为命中 `.gitignore`/`.ignore`/
+    // `.leafignore` 或全局 `ignore_patterns`（见 `is_ignored_by_ignore_files`）
+    // 被过滤掉的文件/目录数，而不是命中显式黑名单 Trie。和
+    // `file_scanner::ScanStats::ignore_pattern_filtered` 是同一个概念，只是
+    // 这边统计的是实时监控/初始扫描这条管线。
+    pub ignore_pattern_filtered: u64,
+    // 批量上传实际发过网的字节数和压缩前的原始字节数（两者都是累计值，
+    // 压缩比要看的话在这两个数之间自己除即可，不单独存一个比值字段，避免
+    // 累计值更新了但比值忘记同步的问题）。压缩关闭时两者相等。
+    pub batch_bytes_sent: u64,
+    pub batch_bytes_uncompressed: u64,
+}
+
+// 并发扫描（见 `run_scan_worker_pool`）期间，worker 池每处理这么多个文件
+// 广播一次进度事件，避免百万级文件量时每个文件都 emit 一次把事件通道打爆
+// （思路和 file_scanner.rs 的 `PROGRESS_EMIT_INTERVAL` 一致，但事件名不同，
+// 不会和那条独立的手动扫描管线的 "scan_progress" 事件混在一起）。
+const PARALLEL_SCAN_PROGRESS_INTERVAL: u64 = 200;
+// 并发扫描 worker 之间传递待处理路径的有界通道容量。
+const PARALLEL_SCAN_CHANNEL_CAPACITY: usize = 256;
+
+// 初始扫描/单目录扫描 worker 池的实时进度，推给前端展示扫描速度和总量，
+// 取代过去只有控制台 `[INITIAL_SCAN]`/`[SINGLE_SCAN] 扫描进度` 日志的情况。
+#[derive(Debug, Clone, Serialize)]
+struct ParallelScanProgress {
+    directory: String,
+    files_received: u64,
+    files_processed: u64,
+    files_skipped: u64,
+    files_per_second: f64,
 }
 
 // 批处理器统计信息
@@ -132,6 +201,11 @@ struct BatchProcessorStats {
     directory_skipped: u64,           // 跳过的目录
     bundle_skipped: u64,              // 跳过的macOS bundle文件
     processed_files: u64,             // 实际处理的文件数
+    batch_bytes_sent: u64,            // 实际发过网的字节数（启用压缩时是压缩后的大小）
+    batch_bytes_uncompressed: u64,    // 压缩前的原始 JSON 字节数
+    retried_batches: u64,  // 因为发送失败而触发过退避重试的批次数（累计重试次数，不是批次去重后的数量）
+    spooled_batches: u64,  // 重试耗尽后成功写入本地 spool 文件、等待连接恢复后重发的批次数
+    dropped_batches: u64,  // 重试耗尽、写 spool 也失败，真正丢失的批次数
 }
 
 // --- New Configuration Structs ---
@@ -156,6 +230,10 @@ pub enum RuleTypeRust {
     Structure,
     #[serde(alias = "os_bundle")]
     OSBundle,
+    #[serde(alias = "size")]
+    Size,
+    #[serde(alias = "time")]
+    Time,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -178,7 +256,7 @@ pub enum RuleActionRust {
     Label,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FileFilterRuleRust {
     pub id: i32,
     pub name: String,
@@ -213,6 +291,12 @@ pub struct AllConfigurations {
     pub full_disk_access: bool, // 是否有完全磁盘访问权限，特别是macOS
     #[serde(default)]
     pub bundle_extensions: Vec<String>, // 直接可用的 bundle 扩展名列表
+    // 黑名单 Trie 比较路径分量时是否区分大小写。macOS/Windows 的默认文件系统
+    // （APFS/HFS+/NTFS）大小写不敏感，`/Users/foo/Cache` 和 `.../cache` 是
+    // 同一个目录，所以默认 false（不区分）；部分用户会把卷格式化成大小写
+    // 敏感的 HFS+/APFS，这种情况下需要这个开关关闭折叠行为。
+    #[serde(default)]
+    pub case_sensitive_paths: bool,
 }
 
 // 简化的文件扫描配置结构（用于新的API端点）
@@ -224,9 +308,126 @@ pub struct FileScanningConfig {
     pub file_categories: Vec<FileCategoryRust>,                     // 文件分类信息
     #[serde(default)]
     pub error_message: Option<String>,         // 错误信息
+    // 内容定义分块（见 content_chunker 模块）总开关：关闭时只计算
+    // `FileMetadata.hash_value` 这个便宜的前缀哈希，和之前行为完全一样。
+    #[serde(default)]
+    pub content_chunking_enabled: bool,
+    // 目标平均分块大小（KB），会被 content_chunker 钳制到 16KB~4MB 之间。
+    #[serde(default = "default_content_chunking_target_size_kb")]
+    pub content_chunking_target_size_kb: u32,
+    // 批量上传（见 payload_compression 模块）压缩总开关：关闭时批处理器按
+    // 原来的行为发送未压缩 JSON，默认关闭，新部署/旧服务端无需感知这个特性。
+    #[serde(default)]
+    pub batch_compression_enabled: bool,
+    // 压缩等级（1~9），只影响 payload_compression 查找重复子串的努力程度。
+    #[serde(default = "default_batch_compression_level")]
+    pub batch_compression_level: u32,
+    // 归档内部成员展开（见 archive_scan 模块）总开关：关闭时 zip/jar/docx
+    // 等归档文件只当成一个不透明的文件处理，和之前行为一样。
+    #[serde(default)]
+    pub archive_scanning_enabled: bool,
+    // 单个归档最多展开的成员数，超过就整体跳过展开这个归档（zip 炸弹防护）。
+    #[serde(default = "default_archive_scan_max_members")]
+    pub archive_scan_max_members: u32,
+    // 单个归档所有成员解压后大小总和的上限（字节），超过同样整体跳过展开。
+    #[serde(default = "default_archive_scan_max_total_uncompressed_bytes")]
+    pub archive_scan_max_total_uncompressed_bytes: u64,
+    // 内容重复检测（见 `annotate_duplicate_groups`）总开关：关闭时批处理
+    // 管线不会为了去重额外读任何文件的全文件内容，和之前行为一样。
+    #[serde(default)]
+    pub duplicate_detection_enabled: bool,
+}
+
+fn default_content_chunking_target_size_kb() -> u32 {
+    1024 // 1MB，常见 CDC 实现（如 rsync/restic）的典型平均分块大小
+}
+
+fn default_batch_compression_level() -> u32 {
+    3 // 保守的默认等级：压缩有一定收益，但不会在大批量场景下明显拖慢查找
+}
+
+fn default_archive_scan_max_members() -> u32 {
+    2000
+}
+
+fn default_archive_scan_max_total_uncompressed_bytes() -> u64 {
+    200 * 1024 * 1024 // 200MB
 }
 // --- End of New Configuration Structs ---
 
+// 一次配置刷新引发的变化摘要，由 `refresh_all_configurations` 在每次刷新后
+// 通过 `subscribe_config_changes()` 返回的 broadcast 通道广播给所有订阅者。
+// 取代了原来 `notify_config_updated` 只打一行日志、下游完全看不出"具体变了
+// 什么"的做法——下游扫描器可以据此只对受影响的目录做增量处理（给新增的
+// 监控目录开 watch、给被移除的目录关 watch），而不必假设"配置变了就全量
+// 重新扫描"。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigChangeEvent {
+    pub added_monitored_dirs: Vec<String>,
+    pub removed_monitored_dirs: Vec<String>,
+    pub added_blacklist_dirs: Vec<String>,
+    pub removed_blacklist_dirs: Vec<String>,
+    pub bundle_extensions_changed: bool,
+    pub filter_rules_changed: bool,
+}
+
+impl ConfigChangeEvent {
+    fn is_empty(&self) -> bool {
+        self.added_monitored_dirs.is_empty()
+            && self.removed_monitored_dirs.is_empty()
+            && self.added_blacklist_dirs.is_empty()
+            && self.removed_blacklist_dirs.is_empty()
+            && !self.bundle_extensions_changed
+            && !self.filter_rules_changed
+    }
+}
+
+// 批处理器一次发送里，按原因分类的跳过文件数（见 BatchProcessorStats 里同名
+// 字段的注释）；`ScanEvent::BatchSent` 里带上这个细分，前端不用再去解析
+// `[BATCH_STATS]` 这行控制台日志就能画出跳过原因的分布。
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanSkipBreakdown {
+    pub hidden: u64,
+    pub rule_excluded: u64,
+    pub invalid_extension: u64,
+    pub ds_store: u64,
+    pub directory: u64,
+    pub bundle: u64,
+}
+
+// 扫描/批处理管线对外广播的事件，取代之前只能在控制台看
+// `[BATCH_STATS]`/`[INITIAL_SCAN] ... 扫描完成` 这些行、前端没法消费的情况。
+// 通过 `subscribe_scan_events()` 返回的 broadcast 通道分发给任意数量的 Rust
+// 订阅者，同时（有 app_handle 时）作为 Tauri 具名事件 "scan_event" 转发给
+// 前端。`println!`/`eprintln!` 日志继续保留作为兜底（这棵树里没有
+// `tracing` 这类结构化日志 crate，没法引入新依赖替换它）。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type")]
+pub enum ScanEvent {
+    // 一批文件元数据发送给 API（或者进了 spool）之后广播一次，带上这一批的
+    // 接收/处理/按原因分类的跳过数，对应 `[BATCH_STATS]` 那行日志。
+    BatchSent {
+        sent: usize,
+        processed: u64,
+        skipped_breakdown: ScanSkipBreakdown,
+    },
+    // 一个监控目录（初始扫描或单目录扫描）完整扫完一次广播一次，对应
+    // `[INITIAL_SCAN]`/`[SINGLE_SCAN] ... 扫描完成` 那行日志。
+    DirectoryCompleted {
+        path: String,
+        total_files: u64,
+        processed_files: u64,
+        skipped_files: u64,
+        skipped_bundles: u64,
+    },
+    // 扫描过程中的错误（目录不存在、API 不可达等），`context` 说明是在哪个
+    // 阶段出的错。
+    Error {
+        context: String,
+        message: String,
+    },
+}
+
 // 文件元数据结构，与Python端数据库匹配
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileMetadata {
@@ -250,6 +451,21 @@ pub struct FileMetadata {
     pub is_os_bundle: Option<bool>, // 是否是macOS bundle
 }
 
+// 内容重复检测（见 `annotate_duplicate_groups`）发现的一组重复文件，作为
+// 独立于 `FileMetadata` 的另一种元数据变体，和该批次的普通文件元数据一起
+// （但走各自的发送路径）汇报给后端，而不是只在每个文件的 extra_metadata
+// 里塞一个 duplicate_group_id——后端不需要自己重新聚合，直接拿到完整的
+// 重复组、可回收空间即可展示。
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroupSummary {
+    // 组内保留的"原件"：本批次里该组第一个（按路径排序）文件。
+    pub canonical_path: String,
+    // 除了 canonical_path 之外，组里其余被判定为内容相同的副本路径。
+    pub duplicate_paths: Vec<String>,
+    // 删掉 duplicate_paths 能回收的字节数（副本数量 * 文件大小）。
+    pub reclaimable_bytes: u64,
+}
+
 // API响应结构
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
@@ -268,6 +484,12 @@ pub struct MonitoredDirectory {
     pub is_blacklist: bool,
     pub created_at: Option<String>, // Added field
     pub updated_at: Option<String>, // Added field
+    // 递归扫描/监控的深度限制：None 表示不限制（完全递归，和原来行为一致），
+    // Some(1) 表示只看这个目录的直接子项，不展开更深的子目录。WalkDir 里
+    // 直接对应 max_depth；notify 监控只能表达"递归"或"不递归"两档，
+    // Some(depth) 且 depth <= 1 时按不递归处理。
+    #[serde(default)]
+    pub max_depth: Option<u32>,
 }
 
 // 初始化文件监控器
@@ -294,9 +516,310 @@ pub struct FileMonitor {
     stats: Arc<Mutex<MonitorStats>>,
     // New field for hierarchical blacklist
     blacklist_trie: Arc<Mutex<BlacklistTrieNode>>,
+    // 哪些监控根目录启用了 `.gitignore`/`.ignore`/`.leafignore` 支持（路径
+    // 字符串同 AppState::ignore_files_enabled_for，由 AppState 同步过来）
+    ignore_files_enabled_for: Arc<Mutex<std::collections::HashSet<String>>>,
+    // 按目录缓存的 IgnoreStack（见 ignore_matcher 模块），避免每次检查都
+    // 重新读盘解析一遍同一批 ignore 文件；监控根目录/黑名单变化时清空
+    ignore_stacks: Arc<Mutex<std::collections::HashMap<PathBuf, crate::ignore_matcher::IgnoreStack>>>,
+    // 内容定义分块（见 content_chunker 模块）的开关/目标分块大小，由
+    // AppState::update_simplified_config 从 FileScanningConfig 同步过来；
+    // 默认关闭，保持小部署场景下只算便宜的前缀哈希。
+    content_chunking: Arc<Mutex<ContentChunkingSettings>>,
+    // 本地配置层（见 local_config 模块）入口文件所在目录；未设置时
+    // （`None`）完全跳过本地层，行为和之前一样只用 API 配置。
+    local_config_dir: Arc<Mutex<Option<PathBuf>>>,
+    // 批量上传压缩（见 payload_compression 模块）的开关/等级，由
+    // AppState::update_simplified_config 从 FileScanningConfig 同步过来；
+    // 默认关闭，保持和之前一样发送未压缩 JSON。
+    batch_compression: Arc<Mutex<BatchCompressionSettings>>,
+    // 全局忽略模式（见 FileScanningConfig::ignore_patterns），由
+    // AppState::update_simplified_config 同步过来；和 `.gitignore`/
+    // `.ignore`/`.leafignore` 语法一样，但不依赖磁盘文件、对所有监控根
+    // 目录都生效，见 `is_ignored_by_ignore_files`。
+    global_ignore_patterns: Arc<Mutex<Vec<String>>>,
+    // 批量发送失败、重试耗尽后的本地 spool 文件所在目录；未设置（`None`）
+    // 时没有磁盘兜底，重试耗尽直接当作丢失处理（行为和之前一样）。见
+    // `send_batch_with_resilience`/`drain_spool_if_any`。
+    spool_dir: Arc<Mutex<Option<PathBuf>>>,
+    // 归档内部成员展开（见 archive_scan 模块）的开关/zip 炸弹防护阈值，由
+    // AppState::update_simplified_config 从 FileScanningConfig 同步过来；
+    // 默认关闭，保持归档文件和之前一样被当作一个不透明文件处理。
+    archive_scan: Arc<Mutex<ArchiveScanSettings>>,
+    // `perform_initial_scan` 定期落盘的扫描检查点所在目录；未设置（`None`）
+    // 时不写检查点，行为和之前一样——中断后下次启动必须从头全量重扫。见
+    // `load_scan_checkpoint`/`save_scan_checkpoint`/`clear_scan_checkpoint`。
+    scan_checkpoint_dir: Arc<Mutex<Option<PathBuf>>>,
+    // 内容重复检测（见 `annotate_duplicate_groups`）开关，由
+    // AppState::update_simplified_config 从 FileScanningConfig 同步过来；
+    // 默认关闭——批内去重要额外读每个候选文件的全文件内容算哈希，不是每个
+    // 部署都想要这个开销，关闭时批处理管线行为和之前完全一样。
+    duplicate_detection_enabled: Arc<Mutex<bool>>,
+    // 预编译的 regex 规则匹配器缓存，按 FileFilterRuleRust.id 查找；每次
+    // `fetch_and_store_all_config` 成功后整体重建一遍（见
+    // `rebuild_compiled_rules`）。`apply_initial_rules` 的每文件热路径直接
+    // 查这张表，不再对同一个 pattern 重复 `Regex::new`。
+    compiled_rules: Arc<Mutex<std::collections::HashMap<i32, regex::Regex>>>,
+    // 预编译的 glob 规则集合（`pattern_type == "glob"`），`None` 表示当前没有
+    // 启用的 glob 规则。见 `CompiledGlobRules`/`rebuild_compiled_globs`。
+    compiled_globs: Arc<Mutex<Option<CompiledGlobRules>>>,
     // 添加状态标志位，防止重复处理
     is_batch_processor_running: Arc<Mutex<bool>>,
     is_initial_scan_running: Arc<Mutex<bool>>,
+    // 配置变更事件的广播发送端；`subscribe_config_changes` 返回的
+    // Receiver 在每次 `refresh_all_configurations` 检测到实际变化后收到
+    // 一份 `ConfigChangeEvent`。克隆 FileMonitor（比如保存到多处 AppState
+    // 字段）共享同一个底层通道，和其它 Arc<Mutex<..>> 字段同样的"共享状态"
+    // 语义。
+    config_change_tx: tokio::sync::broadcast::Sender<ConfigChangeEvent>,
+    // 扫描/批处理事件（见 `ScanEvent`）的广播发送端，和 `config_change_tx`
+    // 同样的共享语义。`emit_scan_event` 往这里发，`subscribe_scan_events`
+    // 订阅。
+    scan_event_tx: tokio::sync::broadcast::Sender<ScanEvent>,
+}
+
+// 内容定义分块的开关和目标分块大小，见 FileScanningConfig 里对应的两个字段。
+#[derive(Debug, Clone, Copy)]
+struct ContentChunkingSettings {
+    enabled: bool,
+    target_size_kb: u32,
+}
+
+impl Default for ContentChunkingSettings {
+    fn default() -> Self {
+        ContentChunkingSettings {
+            enabled: false,
+            target_size_kb: default_content_chunking_target_size_kb(),
+        }
+    }
+}
+
+// 批量上传压缩的开关和等级，见 FileScanningConfig 里对应的两个字段。
+#[derive(Debug, Clone, Copy)]
+struct BatchCompressionSettings {
+    enabled: bool,
+    level: u32,
+}
+
+impl Default for BatchCompressionSettings {
+    fn default() -> Self {
+        BatchCompressionSettings {
+            enabled: false,
+            level: default_batch_compression_level(),
+        }
+    }
+}
+
+// 归档内部成员展开（见 archive_scan 模块）的开关和 zip 炸弹防护阈值，见
+// FileScanningConfig 里对应的三个字段。
+#[derive(Debug, Clone, Copy)]
+struct ArchiveScanSettings {
+    enabled: bool,
+    max_members: u32,
+    max_total_uncompressed_bytes: u64,
+}
+
+impl Default for ArchiveScanSettings {
+    fn default() -> Self {
+        ArchiveScanSettings {
+            enabled: false,
+            max_members: default_archive_scan_max_members(),
+            max_total_uncompressed_bytes: default_archive_scan_max_total_uncompressed_bytes(),
+        }
+    }
+}
+
+// 预编译的 glob 规则集合：和 `path_filter`/`ignore_matcher` 里的先例一样，
+// 这棵树里没有 Cargo.toml，引入不了 `globset`，所以继续用仓库里已经在用的
+// `regex` crate 手写 glob -> 正则的转换（见 `glob_to_regex`），再用
+// `regex::RegexSet`（标准库之外唯一已验证可用、语义上和 `GlobSet::matches`
+// 等价的原语：一次调用把一个字符串和一整批模式比较，返回全部命中的下标）
+// 把所有启用的 glob 规则编译成一个集合，而不是每条规则各自调用一次
+// `is_match`。`rule_ids[i]` 是下标 `i` 对应的 `FileFilterRuleRust::id`。
+struct CompiledGlobRules {
+    set: regex::RegexSet,
+    rule_ids: Vec<i32>,
+}
+
+/// 把一条 glob 模式转换成等价的、锚定到整串的正则表达式：`**` 跨层级匹配
+/// 任意深度，单个 `*`/`?` 不跨越 `/`，`{a,b,c}` 展开成 `(?:a|b|c)` 分支选择
+/// （`*.{log,bak}` 这类"多扩展名二选一"是 glob 规则最常见的写法），其余字符
+/// 按字面转义。和 `ignore_matcher::glob_to_regex`/`path_filter::glob_to_anchored_regex`
+/// 转换规则一致，只是多支持了花括号分支。
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    regex.push_str(".*");
+                    i += 2;
+                    if i < chars.len() && chars[i] == '/' {
+                        i += 1;
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '?' => {
+                regex.push_str("[^/]");
+                i += 1;
+            }
+            '{' => {
+                if let Some(close_offset) = chars[i + 1..].iter().position(|&c| c == '}') {
+                    let close = i + 1 + close_offset;
+                    let alternatives: Vec<String> = chars[i + 1..close]
+                        .iter()
+                        .collect::<String>()
+                        .split(',')
+                        .map(regex::escape)
+                        .collect();
+                    regex.push_str("(?:");
+                    regex.push_str(&alternatives.join("|"));
+                    regex.push(')');
+                    i = close + 1;
+                } else {
+                    // 没有匹配的 `}`：按字面量处理，而不是当成语法错误。
+                    regex.push_str(&regex::escape("{"));
+                    i += 1;
+                }
+            }
+            c => {
+                regex.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
+
+/// `size`/`time` 规则共用的比较符，解析自 pattern 里的前缀（`>`/`>=`/`<`/
+/// `<=`/`=`，不带前缀时按 `=` 处理）。
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RuleComparator {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+impl RuleComparator {
+    fn matches(self, actual: u64, threshold: u64) -> bool {
+        match self {
+            RuleComparator::Gt => actual > threshold,
+            RuleComparator::Ge => actual >= threshold,
+            RuleComparator::Lt => actual < threshold,
+            RuleComparator::Le => actual <= threshold,
+            RuleComparator::Eq => actual == threshold,
+        }
+    }
+}
+
+fn split_comparator(pattern: &str) -> (RuleComparator, &str) {
+    if let Some(rest) = pattern.strip_prefix(">=") {
+        (RuleComparator::Ge, rest)
+    } else if let Some(rest) = pattern.strip_prefix("<=") {
+        (RuleComparator::Le, rest)
+    } else if let Some(rest) = pattern.strip_prefix('>') {
+        (RuleComparator::Gt, rest)
+    } else if let Some(rest) = pattern.strip_prefix('<') {
+        (RuleComparator::Lt, rest)
+    } else if let Some(rest) = pattern.strip_prefix('=') {
+        (RuleComparator::Eq, rest)
+    } else {
+        (RuleComparator::Eq, pattern)
+    }
+}
+
+/// 把一条 `pattern_type == "size"` 规则的 pattern（如 `>100MB`、`<=1KB`、
+/// `=0`）解析成比较符和字节数阈值，仿照 `fd` 的 `SizeFilter`：二进制前缀
+/// （1K = 1024 字节），大小写不敏感，`b`/`ib` 后缀可省略。解析失败返回
+/// `None`，调用方把它当成"规则本身写得不对，不匹配任何文件"处理。
+fn parse_size_limit(pattern: &str) -> Option<(RuleComparator, u64)> {
+    let (comparator, rest) = split_comparator(pattern.trim());
+    let rest = rest.trim();
+    let split_at = rest
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(rest.len());
+    let (number_part, unit_part) = rest.split_at(split_at);
+    let number: f64 = number_part.parse().ok()?;
+    let multiplier: u64 = match unit_part.trim().to_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1024,
+        "m" | "mb" | "mib" => 1024 * 1024,
+        "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+        "t" | "tb" | "tib" => 1024 * 1024 * 1024 * 1024,
+        _ => return None,
+    };
+    Some((comparator, (number * multiplier as f64) as u64))
+}
+
+/// 把一条 `pattern_type == "time"` 规则的 pattern 解析成比较符和一个绝对
+/// Unix 时间戳阈值，仿照 `fd` 的 `TimeFilter`。阈值写法支持两种：相对时长
+/// （`30d`/`12h`/`45m`/`10s`，相对 `now` 换算成过去的一个时间点）和 RFC3339
+/// 绝对时间戳（`2024-01-01T00:00:00Z`）。比较符和 [`parse_size_limit`] 同一套，
+/// 套在"最后修改时间 vs 阈值"上：`<30d` 是"最后修改早于 30 天前"，即 `fd`
+/// 里说的 "older than 30 days"；`>30d` 是"晚于 30 天前"，即最近 30 天内改过。
+fn parse_time_limit(pattern: &str, now: u64) -> Option<(RuleComparator, u64)> {
+    let (comparator, rest) = split_comparator(pattern.trim());
+    let rest = rest.trim();
+
+    if let Some(unit_char) = rest.chars().last() {
+        if "dhms".contains(unit_char) && rest.len() > 1 {
+            if let Ok(number) = rest[..rest.len() - 1].parse::<u64>() {
+                let seconds = match unit_char {
+                    'd' => number.saturating_mul(86400),
+                    'h' => number.saturating_mul(3600),
+                    'm' => number.saturating_mul(60),
+                    's' => number,
+                    _ => unreachable!(),
+                };
+                return Some((comparator, now.saturating_sub(seconds)));
+            }
+        }
+    }
+
+    let dt = chrono::DateTime::parse_from_rfc3339(rest).ok()?;
+    Some((comparator, dt.timestamp().max(0) as u64))
+}
+
+// 批量发送重试耗尽后落盘的 spool 文件名，放在 `set_spool_dir` 设置的目录
+// 下，每行一条 JSON 序列化的 `FileMetadata`，见 `send_batch_with_resilience`。
+const SPOOL_FILE_NAME: &str = "batch_spool.jsonl";
+
+// 退避重试里叠加的抖动量（毫秒），避免大量监控实例在 API 同时恢复时又同时
+// 撞在一起重试。没有引入专门的随机数 crate，用当前纳秒时间戳取模当"够用"
+// 的抖动源——这里只是为了错峰，不需要密码学级别的随机性。
+fn jitter_ms(max_jitter_ms: u64) -> u64 {
+    if max_jitter_ms == 0 {
+        return 0;
+    }
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    (nanos as u64) % (max_jitter_ms + 1)
+}
+
+// `perform_initial_scan` 检查点文件名，放在 `set_scan_checkpoint_dir` 设置的
+// 目录下。见 `ScanCheckpoint`。
+const SCAN_CHECKPOINT_FILE_NAME: &str = "initial_scan_checkpoint.json";
+
+// `perform_initial_scan` 的持久化进度记录：哪些监控目录已经完整扫描过
+// （`completed_dirs`），以及正在扫描、尚未完成的那个目录和扫描到的游标
+// （按 WalkDir 遍历到的绝对路径字符串比较，见 `load_scan_checkpoint` 调用处
+// 的 fast-forward 逻辑）。中断后下次启动据此跳过已完成目录、在未完成目录
+// 内跳过游标之前的路径，而不是整棵树从头重扫一遍。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ScanCheckpoint {
+    completed_dirs: Vec<String>,
+    in_progress_dir: Option<String>,
+    cursor: Option<String>,
 }
 
 impl FileMonitor {
@@ -317,9 +840,53 @@ impl FileMonitor {
             batch_size: 50,
             batch_interval: Duration::from_secs(10),
             blacklist_trie: Arc::new(Mutex::new(BlacklistTrieNode::default())), // Initialize Trie
+            ignore_files_enabled_for: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            ignore_stacks: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            content_chunking: Arc::new(Mutex::new(ContentChunkingSettings::default())),
+            local_config_dir: Arc::new(Mutex::new(None)),
+            batch_compression: Arc::new(Mutex::new(BatchCompressionSettings::default())),
+            global_ignore_patterns: Arc::new(Mutex::new(Vec::new())),
+            spool_dir: Arc::new(Mutex::new(None)),
+            archive_scan: Arc::new(Mutex::new(ArchiveScanSettings::default())),
+            scan_checkpoint_dir: Arc::new(Mutex::new(None)),
+            duplicate_detection_enabled: Arc::new(Mutex::new(false)),
+            compiled_rules: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            compiled_globs: Arc::new(Mutex::new(None)),
             // 初始化状态标志位
             is_batch_processor_running: Arc::new(Mutex::new(false)),
             is_initial_scan_running: Arc::new(Mutex::new(false)),
+            // 容量 16：订阅者理应及时消费，这里只是给偶发的"刷新比订阅者处理
+            // 快"留一点缓冲，不是为了攒积压事件。
+            config_change_tx: tokio::sync::broadcast::channel(16).0,
+            // 容量同样给一点缓冲；批处理和扫描事件比配置变更频繁得多，但
+            // 仍然不是为了攒积压，订阅者跟不上时老事件会被丢弃（broadcast
+            // 通道的标准行为），下次再发新的就是了。
+            scan_event_tx: tokio::sync::broadcast::channel(64).0,
+        }
+    }
+
+    /// 订阅配置变更事件（见 [`ConfigChangeEvent`]）。每次
+    /// `refresh_all_configurations` 检测到实际变化（监控目录、黑名单、
+    /// Bundle 扩展名或过滤规则任一项）都会给所有订阅者广播一份。
+    pub fn subscribe_config_changes(&self) -> tokio::sync::broadcast::Receiver<ConfigChangeEvent> {
+        self.config_change_tx.subscribe()
+    }
+
+    /// 订阅扫描/批处理事件（见 [`ScanEvent`]）。同一份事件也会（在有
+    /// `app_handle` 的调用路径上）作为 Tauri 具名事件 "scan_event" 转发
+    /// 给前端；这个方法是给 Rust 侧的订阅者用的。
+    pub fn subscribe_scan_events(&self) -> tokio::sync::broadcast::Receiver<ScanEvent> {
+        self.scan_event_tx.subscribe()
+    }
+
+    /// 广播一个 `ScanEvent`：发给 `subscribe_scan_events` 的所有订阅者，并
+    /// 在提供了 `app_handle` 时额外作为 Tauri 具名事件转发给前端。没有
+    /// 订阅者或者没有 app_handle 都是正常情况，不当错误处理——`send`/`emit`
+    /// 的 `Err` 直接忽略。
+    fn emit_scan_event(&self, event: ScanEvent, app_handle: Option<&tauri::AppHandle>) {
+        let _ = self.scan_event_tx.send(event.clone());
+        if let Some(app_handle) = app_handle {
+            let _ = app_handle.emit("scan_event", &event);
         }
     }
 
@@ -351,7 +918,22 @@ impl FileMonitor {
                 Ok(response) => {
                     if response.status().is_success() {
                         match response.json::<AllConfigurations>().await {
-                            Ok(config_data) => {
+                            Ok(mut config_data) => {
+                                // 本地配置层：存在时按 later-layer-wins 合并到刚拉取的
+                                // API 配置之上（见 local_config 模块），这样离线自定义/
+                                // 覆盖也能参与到下面紧接着要重建的 blacklist_trie 里。
+                                if let Some(dir) = self.local_config_dir.lock().unwrap().clone() {
+                                    let entry_path = dir.join(crate::local_config::ENTRY_FILE_NAME);
+                                    if entry_path.exists() {
+                                        let overrides = crate::local_config::load(&entry_path);
+                                        overrides.apply_to(&mut config_data);
+                                        println!(
+                                            "[LOCAL_CONFIG] 已合并本地配置层: {:?}",
+                                            entry_path
+                                        );
+                                    }
+                                }
+
                                 println!("[CONFIG_FETCH] Successfully parsed AllConfigurations. Categories: {}, FilterRules: {}, ExtMaps: {}, MonitoredFolders: {}",
                                     config_data.file_categories.len(),
                                     config_data.file_filter_rules.len(),
@@ -360,6 +942,13 @@ impl FileMonitor {
                                 );
                                 let mut cache = self.config_cache.lock().unwrap();
                                 *cache = Some(config_data.clone()); // Store all fetched config
+                                drop(cache);
+
+                                // 配置刷新了，regex/glob 规则可能也变了，整体重建一遍预编译
+                                // 缓存（见 rebuild_compiled_rules/rebuild_compiled_globs），
+                                // 避免继续用上一轮的旧匹配器。
+                                self.rebuild_compiled_rules(&config_data.file_filter_rules);
+                                self.rebuild_compiled_globs(&config_data.file_filter_rules);
 
                                 // 更新监控目录和黑名单目录列表
                                 let mut monitored_dirs_lock = self.monitored_dirs.lock().unwrap();
@@ -369,6 +958,7 @@ impl FileMonitor {
                                 blacklist_dirs_lock.clear();
 
                                 // --- Build Blacklist Trie ---
+                                let case_sensitive = config_data.case_sensitive_paths;
                                 let mut new_blacklist_trie = BlacklistTrieNode::default();
                                 // --- End of Build Blacklist Trie ---
 
@@ -381,9 +971,7 @@ impl FileMonitor {
                                         blacklist_dirs_lock.push(dir.clone());
                                         // Add to Trie
                                         let blacklist_path = PathBuf::from(&dir.path);
-                                        // TODO: Ensure blacklist_path is absolute and normalized before inserting.
-                                        // Assuming paths from API are suitable for now.
-                                        new_blacklist_trie.insert(&blacklist_path);
+                                        new_blacklist_trie.insert(&blacklist_path, case_sensitive);
                                         println!(
                                             "[CONFIG_FETCH] Added to blacklist (Vec & Trie): {}",
                                             dir.path
@@ -409,6 +997,10 @@ impl FileMonitor {
                                 *self.blacklist_trie.lock().unwrap() = new_blacklist_trie;
                                 println!("[CONFIG_FETCH] Blacklist Trie rebuilt.");
 
+                                // 监控根目录/黑名单可能已经变化，之前缓存的 IgnoreStack
+                                // 可能是针对已经不再监控的目录构建的，清空让它们按需重建。
+                                self.ignore_stacks.lock().unwrap().clear();
+
                                 println!("[CONFIG_FETCH] Updated monitored_dirs with {} entries and blacklist_dirs with {} entries from /config/all. (Full disk access: {})",
                                     monitored_dirs_lock.len(), blacklist_dirs_lock.len(), config_data.full_disk_access);
                                 return Ok(());
@@ -525,6 +1117,16 @@ impl FileMonitor {
             .collect()
     }
 
+    /// 获取当前监控的目录列表及各自的递归深度限制，供
+    /// `DebouncedFileMonitor::start_monitoring` 决定用递归还是非递归模式监控。
+    pub fn get_monitored_dirs_with_depth(&self) -> Vec<(String, Option<u32>)> {
+        let monitored_dirs_guard = self.monitored_dirs.lock().unwrap();
+        monitored_dirs_guard
+            .iter()
+            .map(|dir| (dir.path.clone(), dir.max_depth))
+            .collect()
+    }
+
     // 获取元数据发送通道
     pub fn get_metadata_sender(&self) -> Option<Sender<FileMetadata>> {
         // 克隆当前的metadata_tx通道（如果存在）
@@ -611,8 +1213,9 @@ impl FileMonitor {
 
     // --- 配置刷新机制 ---
 
-    /// 刷新文件夹配置（重新获取监控目录和黑名单）
-    pub async fn refresh_folder_configuration(&self) -> Result<bool, String> {
+    /// 刷新文件夹配置（重新获取监控目录和黑名单），返回这次刷新具体改变了
+    /// 什么（见 [`ConfigChangeEvent`]），而不只是"变没变"的布尔值。
+    pub async fn refresh_folder_configuration(&self) -> Result<ConfigChangeEvent, String> {
         println!("[FILE_MONITOR] 开始刷新文件夹配置...");
 
         // 保存当前配置的快照
@@ -621,6 +1224,7 @@ impl FileMonitor {
             let blacklist_guard = self.blacklist_dirs.lock().unwrap();
             blacklist_guard.clone()
         };
+        let previous_config = self.config_cache.lock().unwrap().clone();
 
         // 从API重新获取配置
         if let Err(e) = self.fetch_and_store_all_config().await {
@@ -633,41 +1237,79 @@ impl FileMonitor {
             let blacklist_guard = self.blacklist_dirs.lock().unwrap();
             blacklist_guard.clone()
         };
+        let new_config = self.config_cache.lock().unwrap().clone();
 
-        // 对比变化
-        let monitored_changed = current_monitored_dirs.len() != new_monitored_dirs.len()
-            || current_monitored_dirs
-                .iter()
-                .any(|dir| !new_monitored_dirs.contains(dir));
-
-        let blacklist_changed = current_blacklist_dirs.len() != new_blacklist_dirs.len()
-            || current_blacklist_dirs
-                .iter()
-                .any(|dir| !new_blacklist_dirs.contains(dir));
-
-        let config_changed = monitored_changed || blacklist_changed;
+        let added_monitored_dirs: Vec<String> = new_monitored_dirs
+            .iter()
+            .filter(|dir| !current_monitored_dirs.contains(dir))
+            .cloned()
+            .collect();
+        let removed_monitored_dirs: Vec<String> = current_monitored_dirs
+            .iter()
+            .filter(|dir| !new_monitored_dirs.contains(dir))
+            .cloned()
+            .collect();
+
+        let current_blacklist_paths: Vec<String> =
+            current_blacklist_dirs.iter().map(|d| d.path.clone()).collect();
+        let new_blacklist_paths: Vec<String> =
+            new_blacklist_dirs.iter().map(|d| d.path.clone()).collect();
+        let added_blacklist_dirs: Vec<String> = new_blacklist_paths
+            .iter()
+            .filter(|path| !current_blacklist_paths.contains(path))
+            .cloned()
+            .collect();
+        let removed_blacklist_dirs: Vec<String> = current_blacklist_paths
+            .iter()
+            .filter(|path| !new_blacklist_paths.contains(path))
+            .cloned()
+            .collect();
+
+        let bundle_extensions_changed = previous_config.as_ref().map(|c| &c.bundle_extensions)
+            != new_config.as_ref().map(|c| &c.bundle_extensions);
+        let filter_rules_changed = previous_config.as_ref().map(|c| &c.file_filter_rules)
+            != new_config.as_ref().map(|c| &c.file_filter_rules);
+
+        let event = ConfigChangeEvent {
+            added_monitored_dirs,
+            removed_monitored_dirs,
+            added_blacklist_dirs,
+            removed_blacklist_dirs,
+            bundle_extensions_changed,
+            filter_rules_changed,
+        };
 
-        if config_changed {
+        if !event.is_empty() {
             println!("[FILE_MONITOR] 文件夹配置已更新:");
-            if monitored_changed {
+            if !event.added_monitored_dirs.is_empty() || !event.removed_monitored_dirs.is_empty() {
                 println!(
-                    "[FILE_MONITOR]   - 监控目录: {} -> {}",
+                    "[FILE_MONITOR]   - 监控目录: {} -> {} (新增 {}, 移除 {})",
                     current_monitored_dirs.len(),
-                    new_monitored_dirs.len()
+                    new_monitored_dirs.len(),
+                    event.added_monitored_dirs.len(),
+                    event.removed_monitored_dirs.len()
                 );
             }
-            if blacklist_changed {
+            if !event.added_blacklist_dirs.is_empty() || !event.removed_blacklist_dirs.is_empty() {
                 println!(
-                    "[FILE_MONITOR]   - 黑名单目录: {} -> {}",
+                    "[FILE_MONITOR]   - 黑名单目录: {} -> {} (新增 {}, 移除 {})",
                     current_blacklist_dirs.len(),
-                    new_blacklist_dirs.len()
+                    new_blacklist_dirs.len(),
+                    event.added_blacklist_dirs.len(),
+                    event.removed_blacklist_dirs.len()
                 );
             }
-            Ok(true)
+            if event.bundle_extensions_changed {
+                println!("[FILE_MONITOR]   - Bundle扩展名列表已变化");
+            }
+            if event.filter_rules_changed {
+                println!("[FILE_MONITOR]   - 文件过滤规则已变化");
+            }
         } else {
             println!("[FILE_MONITOR] 文件夹配置未变化");
-            Ok(false)
         }
+
+        Ok(event)
     }
 
     /// 刷新所有配置（通过单一API调用获取所有配置）
@@ -675,23 +1317,42 @@ impl FileMonitor {
         println!("[CONFIG_REFRESH_ALL] 开始刷新所有配置...");
 
         // 刷新文件夹配置（包含所有配置数据，包括Bundle扩展名）
-        if let Err(e) = self.refresh_folder_configuration().await {
-            eprintln!("[CONFIG_REFRESH_ALL] 配置刷新失败: {}", e);
-            return Err(e);
-        }
+        let event = match self.refresh_folder_configuration().await {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("[CONFIG_REFRESH_ALL] 配置刷新失败: {}", e);
+                return Err(e);
+            }
+        };
 
         println!("[CONFIG_REFRESH_ALL] 所有配置刷新成功");
 
-        // 配置刷新完成后，触发配置更新事件通知所有监听器
-        self.notify_config_updated();
+        // 配置刷新完成后，把这次刷新具体改变了什么广播给所有订阅者
+        self.notify_config_updated(event);
         Ok(())
     }
 
-    /// 通知配置已更新（配置变更完成后的通知）
-    fn notify_config_updated(&self) {
-        // 这里可以实现实际的配置更新通知机制
-        // 目前只是记录日志，将来可以添加实际的通知逻辑
-        println!("[CONFIG_NOTIFY] 配置已成功更新，后续扫描将使用新配置");
+    /// 通知配置已更新：把 `refresh_folder_configuration` 算出的
+    /// [`ConfigChangeEvent`] 广播给 `subscribe_config_changes` 的所有订阅者，
+    /// 这次刷新确实没有变化时不广播（没有订阅者在等一个空事件）。没有
+    /// 订阅者时 `send` 会返回 Err，这是正常情况（还没有下游注册监听），忽略
+    /// 即可。
+    fn notify_config_updated(&self, event: ConfigChangeEvent) {
+        if event.is_empty() {
+            println!("[CONFIG_NOTIFY] 配置未发生实际变化，不广播");
+            return;
+        }
+        match self.config_change_tx.send(event) {
+            Ok(receiver_count) => {
+                println!(
+                    "[CONFIG_NOTIFY] 配置变更事件已广播给 {} 个订阅者",
+                    receiver_count
+                );
+            }
+            Err(_) => {
+                println!("[CONFIG_NOTIFY] 配置已更新，但当前没有订阅者");
+            }
+        }
     }
 
     /// 获取当前配置状态摘要
@@ -733,7 +1394,7 @@ impl FileMonitor {
     // --- End of 配置刷新机制 ---
 
     // 计算简单文件哈希（使用文件前4KB内容）
-    async fn calculate_simple_hash(path: &Path, max_bytes: usize) -> Option<String> {
+    pub(crate) async fn calculate_simple_hash(path: &Path, max_bytes: usize) -> Option<String> {
         match fs::File::open(path).await {
             Ok(mut file) => {
                 use tokio::io::AsyncReadExt;
@@ -925,6 +1586,132 @@ impl FileMonitor {
         None // 不在bundle内部
     }
 
+    /// 从 `AppState::ignore_files_enabled_paths` 同步"哪些监控根目录启用了
+    /// `.gitignore`/`.ignore`/`.leafignore` 支持"的快照。启用状态变化后，
+    /// 之前缓存的 `IgnoreStack` 可能是在错误的假设下构建的（比如某个目录当时
+    /// 还没启用），所以顺带清空缓存，下次检查时按需重建。
+    pub fn set_ignore_files_enabled_for(&self, enabled_for: std::collections::HashSet<String>) {
+        *self.ignore_files_enabled_for.lock().unwrap() = enabled_for;
+        self.ignore_stacks.lock().unwrap().clear();
+    }
+
+    /// 同步内容定义分块的开关/目标分块大小（来自
+    /// `FileScanningConfig::content_chunking_enabled`/
+    /// `content_chunking_target_size_kb`）。实时监控的 `process_file_event`
+    /// 在这里开启时才会为新/改动的文件额外算一份分块摘要。
+    pub fn set_content_chunking(&self, enabled: bool, target_size_kb: u32) {
+        *self.content_chunking.lock().unwrap() = ContentChunkingSettings {
+            enabled,
+            target_size_kb,
+        };
+    }
+
+    /// 设置本地配置层（见 local_config 模块）入口文件所在目录；下次
+    /// `fetch_and_store_all_config` 会在 `dir` 里找 `local_config::ENTRY_FILE_NAME`
+    /// 并合并到 API 配置之上。传 `None` 恢复成只用 API 配置。
+    pub fn set_local_config_dir(&self, dir: Option<PathBuf>) {
+        *self.local_config_dir.lock().unwrap() = dir;
+    }
+
+    /// 同步批量上传压缩（见 payload_compression 模块）的开关/等级（来自
+    /// `FileScanningConfig::batch_compression_enabled`/
+    /// `batch_compression_level`）。`send_batch_metadata_to_api` 在这里开启
+    /// 时才会压缩请求体，否则和之前一样发送未压缩 JSON。
+    pub fn set_batch_compression(&self, enabled: bool, level: u32) {
+        *self.batch_compression.lock().unwrap() = BatchCompressionSettings { enabled, level };
+    }
+
+    /// 同步全局忽略模式（来自 `FileScanningConfig::ignore_patterns`）。和
+    /// `set_ignore_files_enabled_for` 一样，模式变化后之前缓存的
+    /// `IgnoreStack` 可能已经过期，顺带清空缓存。
+    pub fn set_global_ignore_patterns(&self, patterns: Vec<String>) {
+        *self.global_ignore_patterns.lock().unwrap() = patterns;
+        self.ignore_stacks.lock().unwrap().clear();
+    }
+
+    /// 设置批量发送重试耗尽后的本地 spool 文件所在目录；传 `None` 恢复成
+    /// 没有磁盘兜底（重试耗尽直接丢弃，和之前行为一样）。
+    pub fn set_spool_dir(&self, dir: Option<PathBuf>) {
+        *self.spool_dir.lock().unwrap() = dir;
+    }
+
+    /// 同步归档内部成员展开（来自 `FileScanningConfig::archive_scanning_enabled`/
+    /// `archive_scan_max_members`/`archive_scan_max_total_uncompressed_bytes`）。
+    /// `process_file_event` 在这里开启时才会为 zip/jar/docx 等归档文件额外
+    /// 发送每个内部成员的虚拟子文件元数据。
+    pub fn set_archive_scanning(&self, enabled: bool, max_members: u32, max_total_uncompressed_bytes: u64) {
+        *self.archive_scan.lock().unwrap() = ArchiveScanSettings {
+            enabled,
+            max_members,
+            max_total_uncompressed_bytes,
+        };
+    }
+
+    /// 设置 `perform_initial_scan` 扫描检查点的落盘目录；传 `None` 恢复成
+    /// 不写检查点（中断后下次启动从头全量重扫，和之前行为一样）。
+    pub fn set_scan_checkpoint_dir(&self, dir: Option<PathBuf>) {
+        *self.scan_checkpoint_dir.lock().unwrap() = dir;
+    }
+
+    /// 同步内容重复检测开关（来自 `FileScanningConfig::duplicate_detection_enabled`）。
+    /// 关闭时 `annotate_duplicate_groups` 直接跳过，批处理管线行为和之前
+    /// 完全一样；不影响已经写进 `extra_metadata` 的其它字段。
+    pub fn set_duplicate_detection_enabled(&self, enabled: bool) {
+        *self.duplicate_detection_enabled.lock().unwrap() = enabled;
+    }
+
+    /// 判断 `path` 是否命中某条忽略规则：要么是某个监控根目录下
+    /// `.gitignore`/`.ignore`/`.leafignore` 文件里的规则（仅对启用了 ignore
+    /// 文件支持的根目录生效），要么是 `global_ignore_patterns` 里配置下发的
+    /// 全局模式（对所有监控根目录都生效，不依赖磁盘文件——"到处忽略
+    /// `*.tmp`" 这类场景就是靠它，不需要用户在每个根目录下手写
+    /// `.leafignore`）。两者都没配置、或者 `path` 不属于任何监控根目录时
+    /// 直接视为未命中，不做任何磁盘 IO。
+    fn is_ignored_by_ignore_files(&self, path: &Path) -> bool {
+        let enabled_roots = self.ignore_files_enabled_for.lock().unwrap().clone();
+        let global_patterns = self.global_ignore_patterns.lock().unwrap().clone();
+        if enabled_roots.is_empty() && global_patterns.is_empty() {
+            return false;
+        }
+
+        let root = {
+            let monitored_dirs = self.monitored_dirs.lock().unwrap();
+            monitored_dirs
+                .iter()
+                .map(|dir| PathBuf::from(&dir.path))
+                .filter(|root| path.starts_with(root))
+                // 多个监控根目录互相嵌套时，取路径最长（最深）的一个。
+                .max_by_key(|root| root.as_os_str().len())
+        };
+
+        let Some(root) = root else {
+            return false;
+        };
+
+        let file_based_enabled = enabled_roots.contains(&root.to_string_lossy().to_string());
+        if !file_based_enabled && global_patterns.is_empty() {
+            return false;
+        }
+
+        let is_dir = path.is_dir();
+        let entry_dir = if is_dir {
+            path.to_path_buf()
+        } else {
+            path.parent().unwrap_or(&root).to_path_buf()
+        };
+
+        let mut stacks = self.ignore_stacks.lock().unwrap();
+        let stack = stacks.entry(entry_dir.clone()).or_insert_with(|| {
+            let base = if file_based_enabled {
+                crate::ignore_matcher::IgnoreStack::build(&root, &entry_dir)
+            } else {
+                crate::ignore_matcher::IgnoreStack::default()
+            };
+            base.with_global_patterns(&root, &global_patterns)
+        });
+        stack.is_ignored(path, is_dir)
+    }
+
     // 检查路径是否在黑名单内 (New implementation using Trie)
     fn is_in_blacklist(&self, path: &Path) -> bool {
         // Ensure path is absolute for consistent Trie checking.
@@ -945,18 +1732,120 @@ impl FileMonitor {
             path.to_path_buf()
         };
 
-        let trie_guard = self.blacklist_trie.lock().unwrap();
-        let result = trie_guard.is_path_or_ancestor_blacklisted(&path_to_check);
+        let case_sensitive = self
+            .config_cache
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|c| c.case_sensitive_paths)
+            .unwrap_or(false);
+        let trie_result = {
+            let trie_guard = self.blacklist_trie.lock().unwrap();
+            trie_guard.is_path_or_ancestor_blacklisted(&path_to_check, case_sensitive)
+        };
 
-        // if result {
+        // if trie_result {
         //     println!("[BLACKLIST_TRIE_CHECK] Path {:?} IS IN BLACKLIST", path_to_check);
         // } else {
         //     println!("[BLACKLIST_TRIE_CHECK] Path {:?} is NOT in blacklist", path_to_check);
         // }
-        result
+
+        // 显式 API 黑名单（Trie）优先：命中就不用再去读 ignore 文件；没命中
+        // 再看这个路径所在的（已启用 ignore 文件支持的）监控根目录下有没有
+        // 匹配的 `.gitignore`/`.ignore`/`.leafignore` 规则。
+        trie_result || self.is_ignored_by_ignore_files(&path_to_check)
     }
 
     // 初步应用规则进行分类
+    /// 根据最新的 `file_filter_rules` 重新编译一遍 `pattern_type == "regex"`
+    /// 的启用规则，整体替换 `compiled_rules` 缓存。编译失败的规则在这里打印
+    /// 一次错误然后跳过（不会出现在缓存里），比放在热路径里每个文件都报一遍
+    /// 同一条非法 pattern 划算得多。
+    fn rebuild_compiled_rules(&self, rules: &[FileFilterRuleRust]) {
+        let mut compiled = std::collections::HashMap::new();
+        for rule in rules {
+            if !rule.enabled || rule.pattern_type != "regex" {
+                continue;
+            }
+            match regex::Regex::new(&rule.pattern) {
+                Ok(re) => {
+                    compiled.insert(rule.id, re);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[COMPILED_RULES] 规则 '{}' (id={}) 的正则表达式非法，已跳过: {}",
+                        rule.name, rule.id, e
+                    );
+                }
+            }
+        }
+        println!("[COMPILED_RULES] 已编译 {} 条 regex 规则", compiled.len());
+        *self.compiled_rules.lock().unwrap() = compiled;
+    }
+
+    /// 根据最新的 `file_filter_rules` 重新编译一遍 `pattern_type == "glob"`
+    /// 的启用规则，整体替换 `compiled_globs` 缓存。先逐条校验每个模式（校验
+    /// 失败的打印一次错误、跳过），再把剩下合法的模式一次性编译进一个
+    /// `RegexSet`；这样一条坏规则不会像直接 `RegexSet::new` 整体失败那样,
+    /// 拖累同一批次里其他合法的 glob 规则。
+    fn rebuild_compiled_globs(&self, rules: &[FileFilterRuleRust]) {
+        let mut patterns = Vec::new();
+        let mut rule_ids = Vec::new();
+        for rule in rules {
+            if !rule.enabled || rule.pattern_type != "glob" {
+                continue;
+            }
+            let pattern = glob_to_regex(&rule.pattern);
+            if let Err(e) = regex::Regex::new(&pattern) {
+                eprintln!(
+                    "[COMPILED_RULES] 规则 '{}' (id={}) 的 glob 模式非法，已跳过: {}",
+                    rule.name, rule.id, e
+                );
+                continue;
+            }
+            patterns.push(pattern);
+            rule_ids.push(rule.id);
+        }
+
+        if patterns.is_empty() {
+            println!("[COMPILED_RULES] 没有启用的 glob 规则");
+            *self.compiled_globs.lock().unwrap() = None;
+            return;
+        }
+
+        match regex::RegexSet::new(&patterns) {
+            Ok(set) => {
+                println!("[COMPILED_RULES] 已编译 {} 条 glob 规则", rule_ids.len());
+                *self.compiled_globs.lock().unwrap() = Some(CompiledGlobRules { set, rule_ids });
+            }
+            Err(e) => {
+                // 每条模式已经单独校验过，理论上不会再失败；保险起见还是处理
+                // 一下，退化为"本轮没有 glob 规则"而不是 panic。
+                eprintln!("[COMPILED_RULES] 编译 glob 规则集合失败，本轮 glob 规则全部跳过: {}", e);
+                *self.compiled_globs.lock().unwrap() = None;
+            }
+        }
+    }
+
+    /// 按 rule id 查预编译的 regex 匹配器；两次配置刷新之间新增的规则还不
+    /// 在缓存里，这里退化为现场编译一次（牺牲缓存带来的性能收益，但保证
+    /// 新规则依然生效，不用等下一次全量刷新）。
+    fn compiled_regex_for(&self, rule: &FileFilterRuleRust) -> Option<regex::Regex> {
+        if let Some(re) = self.compiled_rules.lock().unwrap().get(&rule.id) {
+            return Some(re.clone());
+        }
+        match regex::Regex::new(&rule.pattern) {
+            Ok(re) => Some(re),
+            Err(e) => {
+                eprintln!(
+                    "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
+                    rule.name, e
+                );
+                None
+            }
+        }
+    }
+
     async fn apply_initial_rules(&self, metadata: &mut FileMetadata) {
         let config_guard = self.config_cache.lock().unwrap();
         if config_guard.is_none() {
@@ -1029,6 +1918,37 @@ impl FileMonitor {
         // 检查是否是macOS bundle文件
         let mut is_bundle_file = metadata.is_os_bundle.unwrap_or(false);
 
+        // 预编译的 glob 规则集合（见 CompiledGlobRules）各自对 filename/完整
+        // 路径做一次 `RegexSet::matches`，把命中的规则 id 收集起来；规则循环
+        // 内部只查这两个集合，不会对同一个文件重复调用 `matches()`。
+        let (glob_matches_by_filename, glob_matches_by_path): (
+            std::collections::HashSet<i32>,
+            std::collections::HashSet<i32>,
+        ) = match self.compiled_globs.lock().unwrap().as_ref() {
+            Some(globs) => (
+                globs
+                    .set
+                    .matches(&filename)
+                    .into_iter()
+                    .map(|idx| globs.rule_ids[idx])
+                    .collect(),
+                globs
+                    .set
+                    .matches(&metadata.file_path)
+                    .into_iter()
+                    .map(|idx| globs.rule_ids[idx])
+                    .collect(),
+            ),
+            None => (Default::default(), Default::default()),
+        };
+
+        // size/time 规则的阈值解析都需要"现在"这个时间点；在循环外取一次，
+        // 避免每条规则各自调用一次系统时钟。
+        let now_unix = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
         // Apply FileFilterRuleRust
         for filter_rule in &config.file_filter_rules {
             if !filter_rule.enabled {
@@ -1047,74 +1967,64 @@ impl FileMonitor {
                             // println!("[APPLY_RULES] Matched filename keyword rule '{}' for: {}", filter_rule.name, filename);
                         }
                     } else if filter_rule.pattern_type == "regex" {
-                        // 正则表达式匹配
-                        match regex::Regex::new(&filter_rule.pattern) {
-                            Ok(regex) => {
-                                if regex.is_match(&filename) {
-                                    matched_this_rule = true;
-                                    // println!("[APPLY_RULES] Matched filename regex rule '{}' for: {}", filter_rule.name, filename);
-                                }
-                            }
-                            Err(e) => {
-                                eprintln!(
-                                    "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                    filter_rule.name, e
-                                );
+                        // 正则表达式匹配（预编译缓存，见 compiled_regex_for）
+                        if let Some(regex) = self.compiled_regex_for(filter_rule) {
+                            if regex.is_match(&filename) {
+                                matched_this_rule = true;
+                                // println!("[APPLY_RULES] Matched filename regex rule '{}' for: {}", filter_rule.name, filename);
                             }
                         }
+                    } else if filter_rule.pattern_type == "glob" {
+                        // glob 匹配（预编译的 RegexSet，见 glob_matches_by_filename）
+                        if glob_matches_by_filename.contains(&filter_rule.id) {
+                            matched_this_rule = true;
+                            // println!("[APPLY_RULES] Matched filename glob rule '{}' for: {}", filter_rule.name, filename);
+                        }
                     }
                 }
                 RuleTypeRust::OSBundle => {
                     // 检查文件名是否匹配macOS Bundle模式
                     if filter_rule.pattern_type == "regex" {
-                        match regex::Regex::new(&filter_rule.pattern) {
-                            Ok(regex) => {
-                                if regex.is_match(&filename) {
-                                    matched_this_rule = true;
-                                    println!(
-                                        "[APPLY_RULES] Matched OS_BUNDLE regex rule '{}' for: {}",
-                                        filter_rule.name, filename
-                                    );
-
-                                    // 对于OSBundle类型，标记为bundle而不是排除
-                                    is_bundle_file = true;
-
-                                    // 记录bundle规则信息
-                                    extra_data.insert(
-                                        "macos_bundle_rule_id".to_string(),
-                                        serde_json::Value::Number(serde_json::Number::from(
-                                            filter_rule.id,
-                                        )),
-                                    );
-                                    extra_data.insert(
-                                        "macos_bundle_rule_name".to_string(),
-                                        serde_json::Value::String(filter_rule.name.clone()),
-                                    );
-                                    extra_data.insert(
-                                        "is_macos_bundle".to_string(),
-                                        serde_json::Value::Bool(true),
-                                    );
-
-                                    // 将bundle文件添加到标牌中
-                                    if metadata.labels.is_none() {
-                                        metadata.labels = Some(Vec::new());
+                        if let Some(regex) = self.compiled_regex_for(filter_rule) {
+                            if regex.is_match(&filename) {
+                                matched_this_rule = true;
+                                println!(
+                                    "[APPLY_RULES] Matched OS_BUNDLE regex rule '{}' for: {}",
+                                    filter_rule.name, filename
+                                );
+
+                                // 对于OSBundle类型，标记为bundle而不是排除
+                                is_bundle_file = true;
+
+                                // 记录bundle规则信息
+                                extra_data.insert(
+                                    "macos_bundle_rule_id".to_string(),
+                                    serde_json::Value::Number(serde_json::Number::from(
+                                        filter_rule.id,
+                                    )),
+                                );
+                                extra_data.insert(
+                                    "macos_bundle_rule_name".to_string(),
+                                    serde_json::Value::String(filter_rule.name.clone()),
+                                );
+                                extra_data.insert(
+                                    "is_macos_bundle".to_string(),
+                                    serde_json::Value::Bool(true),
+                                );
+
+                                // 将bundle文件添加到标牌中
+                                if metadata.labels.is_none() {
+                                    metadata.labels = Some(Vec::new());
+                                }
+                                if let Some(labels) = &mut metadata.labels {
+                                    if !labels.contains(&filter_rule.name) {
+                                        labels.push(filter_rule.name.clone());
                                     }
-                                    if let Some(labels) = &mut metadata.labels {
-                                        if !labels.contains(&filter_rule.name) {
-                                            labels.push(filter_rule.name.clone());
-                                        }
-                                        if !labels.contains(&"macos_bundle".to_string()) {
-                                            labels.push("macos_bundle".to_string());
-                                        }
+                                    if !labels.contains(&"macos_bundle".to_string()) {
+                                        labels.push("macos_bundle".to_string());
                                     }
                                 }
                             }
-                            Err(e) => {
-                                eprintln!(
-                                    "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                    filter_rule.name, e
-                                );
-                            }
                         }
                     }
                 }
@@ -1126,32 +2036,62 @@ impl FileMonitor {
                             matched_this_rule = true;
                             // println!("[APPLY_RULES] Matched extension rule '{}' for: {}", filter_rule.name, ext_val);
                         } else if filter_rule.pattern_type == "regex" {
-                            // 扩展名的正则表达式匹配
-                            match regex::Regex::new(&filter_rule.pattern) {
-                                Ok(regex) => {
-                                    if regex.is_match(ext_val) {
-                                        matched_this_rule = true;
-                                        // println!("[APPLY_RULES] Matched extension regex rule '{}' for: {}", filter_rule.name, ext_val);
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!(
-                                        "[APPLY_RULES] Invalid regex pattern in rule '{}': {}",
-                                        filter_rule.name, e
-                                    );
+                            // 扩展名的正则表达式匹配（预编译缓存，见 compiled_regex_for）
+                            if let Some(regex) = self.compiled_regex_for(filter_rule) {
+                                if regex.is_match(ext_val) {
+                                    matched_this_rule = true;
+                                    // println!("[APPLY_RULES] Matched extension regex rule '{}' for: {}", filter_rule.name, ext_val);
                                 }
                             }
+                        } else if filter_rule.pattern_type == "glob" {
+                            // glob 模式（如 `*.{log,bak}`）描述的是整个文件名的形状，不是
+                            // 裸扩展名，所以和 Filename 分支一样拿 filename 做匹配，只是
+                            // 规则归类在 Extension 类型下（和 keyword/regex 分支的扩展名
+                            // 精确匹配不同语义，但复用同一套预编译结果）。
+                            if glob_matches_by_filename.contains(&filter_rule.id) {
+                                matched_this_rule = true;
+                                // println!("[APPLY_RULES] Matched extension glob rule '{}' for: {}", filter_rule.name, filename);
+                            }
                         }
                     }
                 }
-                // Folder and Structure rules might need more context than a single FileMetadata
-                _ => {}
-            }
-
-            if matched_this_rule {
-                rule_matches.push(filter_rule.name.clone());
-
-                // 只为非OSBundle类型的规则应用排除逻辑
+                RuleTypeRust::Folder => {
+                    // 路径/文件夹 glob 规则：对完整文件路径做匹配，例如
+                    // `**/node_modules/**` 排除某个目录子树下的所有文件。
+                    if filter_rule.pattern_type == "glob"
+                        && glob_matches_by_path.contains(&filter_rule.id)
+                    {
+                        matched_this_rule = true;
+                        // println!("[APPLY_RULES] Matched folder glob rule '{}' for: {}", filter_rule.name, metadata.file_path);
+                    }
+                }
+                // Structure 规则需要比单个 FileMetadata 更多的上下文，这里暂不处理
+                RuleTypeRust::Structure => {}
+                RuleTypeRust::Size => {
+                    if let Some((comparator, threshold)) = parse_size_limit(&filter_rule.pattern)
+                    {
+                        if comparator.matches(metadata.file_size, threshold) {
+                            matched_this_rule = true;
+                            // println!("[APPLY_RULES] Matched size rule '{}' for: {}", filter_rule.name, metadata.file_path);
+                        }
+                    }
+                }
+                RuleTypeRust::Time => {
+                    if let Some((comparator, threshold)) =
+                        parse_time_limit(&filter_rule.pattern, now_unix)
+                    {
+                        if comparator.matches(metadata.modified_time, threshold) {
+                            matched_this_rule = true;
+                            // println!("[APPLY_RULES] Matched time rule '{}' for: {}", filter_rule.name, metadata.file_path);
+                        }
+                    }
+                }
+            }
+
+            if matched_this_rule {
+                rule_matches.push(filter_rule.name.clone());
+
+                // 只为非OSBundle类型的规则应用排除逻辑
                 if filter_rule.rule_type != RuleTypeRust::OSBundle {
                     match filter_rule.action {
                         RuleActionRust::Label => {
@@ -1296,6 +2236,15 @@ impl FileMonitor {
         }
     }
 
+    // 把累计到 self.stats（MonitorStats）里的批量上传字节数同步进批处理器自己
+    // 的本地统计，方便 [BATCH_STATS]/[BATCH_PROC] 的周期性日志里一起打印。
+    fn sync_batch_byte_stats(&self, stats: &mut BatchProcessorStats) {
+        if let Ok(monitor_stats) = self.stats.lock() {
+            stats.batch_bytes_sent = monitor_stats.batch_bytes_sent;
+            stats.batch_bytes_uncompressed = monitor_stats.batch_bytes_uncompressed;
+        }
+    }
+
     // 批量发送文件元数据到API
     async fn send_batch_metadata_to_api(
         &self,
@@ -1333,7 +2282,66 @@ impl FileMonitor {
         // let keys: Vec<String> = request_body.keys().cloned().collect();
         // println!("[TEST_DEBUG] send_batch_metadata_to_api: Request body for batch keys: {:?}", keys);
 
-        match self.client.post(&url).json(&request_body).send().await {
+        let body_json = serde_json::to_vec(&request_body)
+            .map_err(|e| format!("Failed to serialize request body: {}", e))?;
+        let compression = *self.batch_compression.lock().unwrap();
+        let response_result = if compression.enabled {
+            let compressed = crate::payload_compression::compress(&body_json, compression.level);
+            println!(
+                "[BATCH_PROC] 压缩批量请求体: {} -> {} 字节 (等级 {})",
+                body_json.len(),
+                compressed.len(),
+                compression.level
+            );
+            let result = self
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header(
+                    "Content-Encoding",
+                    crate::payload_compression::CONTENT_ENCODING_TOKEN,
+                )
+                .body(compressed.clone())
+                .send()
+                .await;
+            match result {
+                // 服务端不认识这个自定义编码时通常会回 415；退化成发一次未压缩的，
+                // 不把这次当成最终失败。
+                Ok(resp) if resp.status() == reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE => {
+                    println!("[BATCH_PROC] API 不支持 {} 压缩编码，回退为未压缩发送", crate::payload_compression::CONTENT_ENCODING_TOKEN);
+                    if let Ok(mut stats) = self.stats.lock() {
+                        stats.batch_bytes_uncompressed += body_json.len() as u64;
+                        stats.batch_bytes_sent += body_json.len() as u64;
+                    }
+                    self.client
+                        .post(&url)
+                        .header("Content-Type", "application/json")
+                        .body(body_json.clone())
+                        .send()
+                        .await
+                }
+                other => {
+                    if let Ok(mut stats) = self.stats.lock() {
+                        stats.batch_bytes_uncompressed += body_json.len() as u64;
+                        stats.batch_bytes_sent += compressed.len() as u64;
+                    }
+                    other
+                }
+            }
+        } else {
+            if let Ok(mut stats) = self.stats.lock() {
+                stats.batch_bytes_uncompressed += body_json.len() as u64;
+                stats.batch_bytes_sent += body_json.len() as u64;
+            }
+            self.client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .body(body_json.clone())
+                .send()
+                .await
+        };
+
+        match response_result {
             Ok(response) => {
                 let status = response.status();
                 // println!("[TEST_DEBUG] send_batch_metadata_to_api: Received response with status: {}", status);
@@ -1474,7 +2482,23 @@ impl FileMonitor {
                 } else {
                     dir.path.clone()
                 };
-                path_str.starts_with(&expanded_path)
+                if !path_str.starts_with(&expanded_path) {
+                    return false;
+                }
+                // 非递归监控（max_depth <= 1，见 file_monitor_debounced 里
+                // 对应的 `RecursiveMode::NonRecursive`）只看这个目录的直接
+                // 子项：事件的父目录不是监控根目录本身就直接拒绝，不用再为
+                // 注定要丢弃的深层事件去取文件元数据。
+                if dir.max_depth.map_or(false, |depth| depth <= 1) {
+                    let parent_is_root = path
+                        .parent()
+                        .map(|parent| parent == Path::new(&expanded_path))
+                        .unwrap_or(false);
+                    if !parent_is_root {
+                        return false;
+                    }
+                }
+                true
             });
             // println!("[DEBUG] 匹配结果: {}", belongs);
             belongs
@@ -1613,8 +2637,15 @@ impl FileMonitor {
         // 这样可以避免对黑名单中的路径进行不必要的文件元数据操作
         if self.is_in_blacklist(&path) {
             println!("[PROCESS_EVENT] Path {:?} is in blacklist. Ignoring.", path);
+            // 区分一下具体原因：显式黑名单 Trie 没命中、但 ignore 文件/全局
+            // ignore_patterns 命中了，就单独计入 ignore_pattern_filtered，
+            // 方便和黑名单剪枝的数量区分开。
+            let ignore_file_matched = self.is_ignored_by_ignore_files(&path);
             if let Ok(mut stats) = self.stats.lock() {
                 stats.filtered_files += 1;
+                if ignore_file_matched {
+                    stats.ignore_pattern_filtered += 1;
+                }
             }
             return None;
         }
@@ -1669,15 +2700,414 @@ impl FileMonitor {
 
         // println!("[TEST_DEBUG] process_file_event: Metadata AFTER applying rules for {:?}: {:?}", path, metadata); // "粗筛"结果
 
+        // 内容定义分块（opt-in）：必须放在 apply_initial_rules 之后，因为它
+        // 会整个重建 extra_metadata；这里往已有对象里追加一个字段，而不是
+        // 覆盖掉规则匹配阶段写入的 excluded_by_rule_id 等信息。
+        let chunking = *self.content_chunking.lock().unwrap();
+        if chunking.enabled && !metadata.is_dir {
+            if let Some(chunks) =
+                crate::content_chunker::chunk_file(&path, chunking.target_size_kb)
+            {
+                if let Ok(chunks_value) = serde_json::to_value(&chunks) {
+                    let mut extra_data = match metadata.extra_metadata.take() {
+                        Some(JsonValue::Object(map)) => map,
+                        _ => serde_json::Map::new(),
+                    };
+                    extra_data.insert("content_chunks".to_string(), chunks_value);
+                    metadata.extra_metadata = Some(JsonValue::Object(extra_data));
+                }
+            }
+        }
+
+        // 损坏/无法解析探测（见 integrity_check 模块）：只对已知格式家族
+        // （图片/ZIP 家族/PDF/音频）生效，扩展名不在这些分类里时直接跳过，
+        // 不往 extra_metadata 里写任何字段。放在内容分块之后，同样是往已有
+        // 对象里追加一个字段，而不是覆盖掉前面阶段写入的信息。
+        if !metadata.is_dir {
+            if let Some(report) =
+                crate::integrity_check::check_file(&path, metadata.extension.as_deref())
+            {
+                if let Ok(report_value) = serde_json::to_value(&report) {
+                    let mut extra_data = match metadata.extra_metadata.take() {
+                        Some(JsonValue::Object(map)) => map,
+                        _ => serde_json::Map::new(),
+                    };
+                    extra_data.insert("integrity".to_string(), report_value);
+                    metadata.extra_metadata = Some(JsonValue::Object(extra_data));
+                }
+            }
+        }
+
+        // 归档内部成员展开（见 archive_scan 模块，opt-in）：zip/jar/docx 等
+        // 归档文件额外为每个内部成员发送一条虚拟子文件的 FileMetadata，路径
+        // 形如 `outer.zip!/inner/path.txt`，走同一条 metadata_tx 通道汇入
+        // 批处理管线——复用现有的规则匹配/白名单扩展名过滤，和真实文件一视
+        // 同仁，不单独搭一套展示逻辑。这里只负责把虚拟成员发出去，函数本身
+        // 仍然只为归档自身这一条 metadata 返回 Some。
+        let archive_scan = *self.archive_scan.lock().unwrap();
+        if archive_scan.enabled && !metadata.is_dir {
+            let is_archive = metadata
+                .extension
+                .as_deref()
+                .map(|ext| crate::archive_scan::ARCHIVE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+                .unwrap_or(false);
+            if is_archive {
+                if let Some(members) = crate::archive_scan::list_members(
+                    &path,
+                    archive_scan.max_members,
+                    archive_scan.max_total_uncompressed_bytes,
+                ) {
+                    if let Some(tx) = self.metadata_tx.clone() {
+                        for member in members {
+                            let member_path = Path::new(&member.name);
+                            let mut virtual_metadata = FileMetadata {
+                                file_path: format!("{}!/{}", metadata.file_path, member.name),
+                                file_name: member_path
+                                    .file_name()
+                                    .map(|n| n.to_string_lossy().to_string())
+                                    .unwrap_or_else(|| member.name.clone()),
+                                extension: member_path
+                                    .extension()
+                                    .map(|e| e.to_string_lossy().to_lowercase()),
+                                file_size: member.uncompressed_size,
+                                created_time: metadata.created_time,
+                                modified_time: metadata.modified_time,
+                                is_dir: false,
+                                is_hidden: false,
+                                hash_value: None,
+                                category_id: None,
+                                labels: None,
+                                initial_rule_matches: None,
+                                extra_metadata: None,
+                                is_os_bundle: None,
+                            };
+
+                            self.apply_initial_rules(&mut virtual_metadata).await;
+                            if let Some(extra_meta) = &virtual_metadata.extra_metadata {
+                                if extra_meta.get("excluded_by_rule_id").is_some() {
+                                    continue;
+                                }
+                            }
+
+                            let mut extra_data = match virtual_metadata.extra_metadata.take() {
+                                Some(JsonValue::Object(map)) => map,
+                                _ => serde_json::Map::new(),
+                            };
+                            extra_data.insert(
+                                "archive_path".to_string(),
+                                JsonValue::String(metadata.file_path.clone()),
+                            );
+                            virtual_metadata.extra_metadata = Some(JsonValue::Object(extra_data));
+
+                            if let Err(e) = tx.send(virtual_metadata).await {
+                                eprintln!("[PROCESS_EVENT] 归档虚拟子文件元数据发送失败: {}", e);
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
         Some(metadata)
     }
 
+    // 在一批待发送的 FileMetadata 里找内容重复的文件：先按 (file_size,
+    // hash_value) 分桶——hash_value 此时还只是 `calculate_simple_hash` 算出
+    // 的前 4KB 局部哈希，桶内只是"疑似重复"的候选——只有桶大小 > 1 才值得
+    // 再对桶内每个文件算一次全文件流式哈希（见
+    // `file_scanner::hash_file_contents`，混入了文件长度）确认内容是否真的
+    // 相同，避免对绝大多数（并不重复的）文件都去读一遍全文件内容。全文件
+    // 哈希是阻塞 I/O，丢给 `spawn_blocking` 在专门的阻塞线程池里算，不占用
+    // 本来在跑这个批处理任务的 tokio worker 线程。确认后把一个稳定的
+    // `duplicate_group_id`（文件大小拼上全文件哈希，同样的内容不管出现在
+    // 哪一批里都会算出同一个 id）写进每个文件的 extra_metadata，同时汇总成
+    // `DuplicateGroupSummary` 列表返回，供调用方再单独上报给后端。默认关闭
+    // （见 `duplicate_detection_enabled`），关闭时直接跳过，不读取任何文件。
+    async fn annotate_duplicate_groups(&self, batch: &mut [FileMetadata]) -> Vec<DuplicateGroupSummary> {
+        if !*self.duplicate_detection_enabled.lock().unwrap() {
+            return Vec::new();
+        }
+
+        let mut by_size_and_partial: std::collections::HashMap<(u64, String), Vec<usize>> =
+            std::collections::HashMap::new();
+        for (idx, item) in batch.iter().enumerate() {
+            if item.is_dir {
+                continue;
+            }
+            if let Some(partial) = &item.hash_value {
+                by_size_and_partial
+                    .entry((item.file_size, partial.clone()))
+                    .or_default()
+                    .push(idx);
+            }
+        }
+
+        let mut summaries = Vec::new();
+
+        for (_, indices) in by_size_and_partial
+            .into_iter()
+            .filter(|(_, idxs)| idxs.len() > 1)
+        {
+            let mut by_full_hash: std::collections::HashMap<String, Vec<usize>> =
+                std::collections::HashMap::new();
+            for idx in indices {
+                let path = PathBuf::from(&batch[idx].file_path);
+                let full_hash =
+                    tokio::task::spawn_blocking(move || crate::file_scanner::hash_file_contents(&path))
+                        .await
+                        .ok()
+                        .flatten();
+                if let Some(full_hash) = full_hash {
+                    by_full_hash.entry(full_hash).or_default().push(idx);
+                }
+            }
+
+            for (full_hash, mut group_indices) in
+                by_full_hash.into_iter().filter(|(_, idxs)| idxs.len() > 1)
+            {
+                group_indices.sort_by(|&a, &b| batch[a].file_path.cmp(&batch[b].file_path));
+                let group_id = format!("{}:{}", batch[group_indices[0]].file_size, full_hash);
+                for &idx in &group_indices {
+                    let mut extra_data = match batch[idx].extra_metadata.take() {
+                        Some(JsonValue::Object(map)) => map,
+                        _ => serde_json::Map::new(),
+                    };
+                    extra_data.insert(
+                        "duplicate_group_id".to_string(),
+                        JsonValue::String(group_id.clone()),
+                    );
+                    batch[idx].extra_metadata = Some(JsonValue::Object(extra_data));
+                }
+
+                let file_size = batch[group_indices[0]].file_size;
+                let (canonical_idx, copy_indices) = group_indices.split_first().unwrap();
+                summaries.push(DuplicateGroupSummary {
+                    canonical_path: batch[*canonical_idx].file_path.clone(),
+                    duplicate_paths: copy_indices
+                        .iter()
+                        .map(|&idx| batch[idx].file_path.clone())
+                        .collect(),
+                    reclaimable_bytes: file_size * copy_indices.len() as u64,
+                });
+            }
+        }
+
+        summaries
+    }
+
+    // 把 `annotate_duplicate_groups` 汇总出的重复组上报给后端。这是一次性
+    // 尽力而为的旁路上报，不走 `send_batch_with_resilience` 那套退避重试和
+    // spool 兜底——重复组信息下次批次还会重新算一遍，没必要为它做持久化
+    // 补偿。
+    async fn send_duplicate_groups_to_api(&self, groups: Vec<DuplicateGroupSummary>) {
+        if groups.is_empty() {
+            return;
+        }
+        let url = format!(
+            "http://{}:{}/file-screening/duplicate-groups",
+            self.api_host, self.api_port
+        );
+        match self.client.post(&url).json(&groups).send().await {
+            Ok(resp) if resp.status().is_success() => {
+                println!("[BATCH_PROC] 上报了 {} 组重复文件", groups.len());
+            }
+            Ok(resp) => {
+                eprintln!("[BATCH_PROC] 上报重复文件组失败，状态码: {}", resp.status());
+            }
+            Err(e) => {
+                eprintln!("[BATCH_PROC] 上报重复文件组失败: {}", e);
+            }
+        }
+    }
+
+    // 把 `batch` 追加写入本地 spool 文件（见 `SPOOL_FILE_NAME`），每个文件
+    // 一行 JSON，供下次 `drain_spool_if_any` 读回重发。没有配置 `spool_dir`
+    // 时直接报错，调用方把这种情况当成"这批真的丢了"处理。
+    fn spool_batch(&self, batch: &[FileMetadata]) -> std::io::Result<()> {
+        let spool_dir = self.spool_dir.lock().unwrap().clone();
+        let dir = spool_dir.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "未配置 spool 目录")
+        })?;
+        std::fs::create_dir_all(&dir)?;
+
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dir.join(SPOOL_FILE_NAME))?;
+        for item in batch {
+            let line = serde_json::to_string(item).map_err(|e| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, e.to_string())
+            })?;
+            writeln!(file, "{}", line)?;
+        }
+        Ok(())
+    }
+
+    // 批量发送的弹性外壳：失败时按指数退避（叠加抖动）重试有限次数，重试
+    // 耗尽后把这一批写入本地 spool 文件（见 `spool_batch`），而不是像之前
+    // 那样直接丢弃——这样 API 短暂下线、或者比监控器晚启动，都不会丢数据，
+    // 只是延迟送达。`BatchProcessorStats` 里的 retried/spooled/dropped 三个
+    // 计数分别对应"触发过重试"、"最终靠 spool 兜底"、"spool 也失败、真的
+    // 丢失"三种结局。
+    async fn send_batch_with_resilience(&self, batch: &[FileMetadata], stats: &mut BatchProcessorStats) {
+        if batch.is_empty() {
+            return;
+        }
+
+        const MAX_ATTEMPTS: u32 = 4; // 首次发送 + 最多 3 次退避重试
+        const BASE_BACKOFF_MS: u64 = 500;
+        const MAX_BACKOFF_MS: u64 = 8000;
+
+        let mut attempt: u32 = 0;
+        loop {
+            match self.send_batch_metadata_to_api(batch.to_vec()).await {
+                Ok(_) => {
+                    if attempt > 0 {
+                        println!("[BATCH_PROC] 批量发送在第 {} 次重试后成功", attempt);
+                    }
+                    return;
+                }
+                Err(e) => {
+                    attempt += 1;
+                    if attempt >= MAX_ATTEMPTS {
+                        eprintln!(
+                            "[BATCH_PROC] 批量发送重试 {} 次后仍然失败，写入本地 spool: {}",
+                            attempt - 1,
+                            e
+                        );
+                        match self.spool_batch(batch) {
+                            Ok(()) => {
+                                stats.spooled_batches += 1;
+                                println!(
+                                    "[BATCH_PROC] 已将 {} 条记录写入 spool，等待连接恢复后重发",
+                                    batch.len()
+                                );
+                            }
+                            Err(spool_err) => {
+                                stats.dropped_batches += 1;
+                                eprintln!(
+                                    "[BATCH_PROC] 写入 spool 也失败，这批 {} 条记录将丢失: {}",
+                                    batch.len(),
+                                    spool_err
+                                );
+                            }
+                        }
+                        return;
+                    }
+
+                    stats.retried_batches += 1;
+                    let backoff = BASE_BACKOFF_MS
+                        .saturating_mul(1u64 << (attempt - 1))
+                        .min(MAX_BACKOFF_MS);
+                    let delay_ms = backoff + jitter_ms(backoff / 2);
+                    eprintln!(
+                        "[BATCH_PROC] 批量发送失败（第 {} 次尝试）：{}；{}ms 后重试",
+                        attempt, e, delay_ms
+                    );
+                    tokio::time::sleep(Duration::from_millis(delay_ms)).await;
+                }
+            }
+        }
+    }
+
+    // 尝试把 spool 文件里积压的记录重新发送出去：成功就清空 spool 文件，
+    // 失败就原样留着，等下一次（下一个批处理间隔）再试。只尝试一次，不在
+    // 这里做退避重试——真正失败不了就是 API 还没恢复，没必要在这里空等。
+    async fn drain_spool_if_any(&self) {
+        let spool_dir = self.spool_dir.lock().unwrap().clone();
+        let Some(dir) = spool_dir else {
+            return;
+        };
+        let path = dir.join(SPOOL_FILE_NAME);
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return;
+        };
+        if content.trim().is_empty() {
+            return;
+        }
+
+        let spooled_items: Vec<FileMetadata> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        if spooled_items.is_empty() {
+            let _ = std::fs::remove_file(&path);
+            return;
+        }
+
+        println!(
+            "[BATCH_PROC] 尝试重新发送 spool 里积压的 {} 条记录",
+            spooled_items.len()
+        );
+        match self.send_batch_metadata_to_api(spooled_items).await {
+            Ok(_) => {
+                if let Err(e) = std::fs::remove_file(&path) {
+                    eprintln!("[BATCH_PROC] 重发成功但清理 spool 文件失败: {}", e);
+                } else {
+                    println!("[BATCH_PROC] spool 已清空，积压记录全部重新发送成功");
+                }
+            }
+            Err(e) => {
+                eprintln!("[BATCH_PROC] spool 重发仍然失败，留到下次再试: {}", e);
+            }
+        }
+    }
+
+    // 读取 `perform_initial_scan` 的扫描检查点；没有配置 `scan_checkpoint_dir`、
+    // 文件不存在或内容解析失败，都当作"没有检查点"处理，返回默认值（从头
+    // 全量扫描），不是错误。
+    fn load_scan_checkpoint(&self) -> ScanCheckpoint {
+        let Some(dir) = self.scan_checkpoint_dir.lock().unwrap().clone() else {
+            return ScanCheckpoint::default();
+        };
+        let path = dir.join(SCAN_CHECKPOINT_FILE_NAME);
+        std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    // 把扫描检查点落盘，覆盖写入（检查点文件很小，没必要做增量更新）。没有
+    // 配置 `scan_checkpoint_dir` 时静默跳过，不影响扫描本身。
+    fn save_scan_checkpoint(&self, checkpoint: &ScanCheckpoint) {
+        let Some(dir) = self.scan_checkpoint_dir.lock().unwrap().clone() else {
+            return;
+        };
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!("[INITIAL_SCAN] 创建检查点目录失败: {}", e);
+            return;
+        }
+        let Ok(json) = serde_json::to_string(checkpoint) else {
+            return;
+        };
+        if let Err(e) = std::fs::write(dir.join(SCAN_CHECKPOINT_FILE_NAME), json) {
+            eprintln!("[INITIAL_SCAN] 写入扫描检查点失败: {}", e);
+        }
+    }
+
+    // 所有监控目录都扫描完成后调用，清掉检查点文件——下次启动就是正常的
+    // 全新初始扫描，而不是误当成"还有未完成目录"去恢复。
+    fn clear_scan_checkpoint(&self) {
+        let Some(dir) = self.scan_checkpoint_dir.lock().unwrap().clone() else {
+            return;
+        };
+        let path = dir.join(SCAN_CHECKPOINT_FILE_NAME);
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                eprintln!("[INITIAL_SCAN] 清理扫描检查点失败: {}", e);
+            }
+        }
+    }
+
     // 批处理文件元数据发送
     async fn batch_processor(
         &self,
         mut rx: Receiver<FileMetadata>,
         batch_size: usize,
         batch_interval: Duration,
+        app_handle: Option<tauri::AppHandle>,
     ) {
         // 检查批处理器是否已经在运行
         {
@@ -1706,6 +3136,11 @@ impl FileMonitor {
             directory_skipped: 0,
             bundle_skipped: 0,
             processed_files: 0,
+            batch_bytes_sent: 0,
+            batch_bytes_uncompressed: 0,
+            retried_batches: 0,
+            spooled_batches: 0,
+            dropped_batches: 0,
         };
 
         println!(
@@ -1796,16 +3231,20 @@ impl FileMonitor {
                         if batch.len() >= batch_size {
                             // println!("[BATCH_PROC] 批处理达到大小限制 ({} 项)，正在发送到API", batch.len());
 
-                            // 发送数据到API
-                            if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                                eprintln!("[BATCH_PROC] 批量发送错误: {}", e);
-                            }
+                            // 发送前在本批内找一遍重复文件
+                            let duplicate_groups = self.annotate_duplicate_groups(&mut batch).await;
+                            self.send_duplicate_groups_to_api(duplicate_groups).await;
+
+                            // 发送数据到API（失败时自动退避重试、最终兜底写 spool）
+                            self.send_batch_with_resilience(&batch, &mut stats).await;
 
+                            let sent_count = batch.len();
                             batch.clear();
                             last_send = tokio::time::Instant::now();
+                            self.sync_batch_byte_stats(&mut stats);
 
                             // 每次发送后输出统计信息
-                            println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                            println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}), 累计发送字节: {} (压缩前: {})",
                                 stats.received_files,
                                 stats.processed_files,
                                 stats.received_files - stats.processed_files,
@@ -1814,23 +3253,45 @@ impl FileMonitor {
                                 stats.invalid_extension_skipped,
                                 stats.ds_store_skipped,
                                 stats.directory_skipped,
-                                stats.bundle_skipped
+                                stats.bundle_skipped,
+                                stats.batch_bytes_sent,
+                                stats.batch_bytes_uncompressed
+                            );
+                            self.emit_scan_event(
+                                ScanEvent::BatchSent {
+                                    sent: sent_count,
+                                    processed: stats.processed_files,
+                                    skipped_breakdown: ScanSkipBreakdown {
+                                        hidden: stats.hidden_files_skipped,
+                                        rule_excluded: stats.rule_excluded_files_skipped,
+                                        invalid_extension: stats.invalid_extension_skipped,
+                                        ds_store: stats.ds_store_skipped,
+                                        directory: stats.directory_skipped,
+                                        bundle: stats.bundle_skipped,
+                                    },
+                                },
+                                app_handle.as_ref(),
                             );
                         }
                     } else {
                         // 通道关闭
+                        let mut final_sent_count = 0usize;
                         if !batch.is_empty() {
                             println!("[BATCH_PROC] 通道关闭，正在发送剩余批处理 ({} 项)", batch.len());
 
-                            // 发送剩余数据到API
-                            if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                                eprintln!("[BATCH_PROC] 最终批量发送错误: {}", e);
-                            }
+                            // 发送前在本批内找一遍重复文件
+                            let duplicate_groups = self.annotate_duplicate_groups(&mut batch).await;
+                            self.send_duplicate_groups_to_api(duplicate_groups).await;
+
+                            // 发送剩余数据到API（失败时自动退避重试、最终兜底写 spool）
+                            self.send_batch_with_resilience(&batch, &mut stats).await;
+                            final_sent_count = batch.len();
                             batch.clear();
                         }
+                        self.sync_batch_byte_stats(&mut stats);
 
                         // 输出最终统计信息
-                        println!("[BATCH_PROC] 最终统计: 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                        println!("[BATCH_PROC] 最终统计: 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}), 累计发送字节: {} (压缩前: {})",
                             stats.received_files,
                             stats.processed_files,
                             stats.received_files - stats.processed_files,
@@ -1839,7 +3300,24 @@ impl FileMonitor {
                             stats.invalid_extension_skipped,
                             stats.ds_store_skipped,
                             stats.directory_skipped,
-                            stats.bundle_skipped
+                            stats.bundle_skipped,
+                            stats.batch_bytes_sent,
+                            stats.batch_bytes_uncompressed
+                        );
+                        self.emit_scan_event(
+                            ScanEvent::BatchSent {
+                                sent: final_sent_count,
+                                processed: stats.processed_files,
+                                skipped_breakdown: ScanSkipBreakdown {
+                                    hidden: stats.hidden_files_skipped,
+                                    rule_excluded: stats.rule_excluded_files_skipped,
+                                    invalid_extension: stats.invalid_extension_skipped,
+                                    ds_store: stats.ds_store_skipped,
+                                    directory: stats.directory_skipped,
+                                    bundle: stats.bundle_skipped,
+                                },
+                            },
+                            app_handle.as_ref(),
                         );
 
                         println!("[BATCH_PROC] 元数据通道关闭。退出批处理器。");
@@ -1847,18 +3325,26 @@ impl FileMonitor {
                     }
                 },
                 _ = sleep(batch_interval) => {
+                    // 每个间隔先顺手试一次 spool 重发——连接恢复后不用等到下一批
+                    // 新文件凑够了才有机会把积压的记录送出去。
+                    self.drain_spool_if_any().await;
+
                     if !batch.is_empty() && tokio::time::Instant::now().duration_since(last_send) >= batch_interval {
                                         println!("[BATCH_PROC] 达到批处理间隔，正在发送批处理 ({} 项)", batch.len());
 
-                        // 发送数据到API
-                        if let Err(e) = self.send_batch_metadata_to_api(batch.clone()).await {
-                            eprintln!("[BATCH_PROC] 批量发送错误: {}", e);
-                        }
+                        // 发送前在本批内找一遍重复文件
+                        let duplicate_groups = self.annotate_duplicate_groups(&mut batch).await;
+                        self.send_duplicate_groups_to_api(duplicate_groups).await;
+
+                        // 发送数据到API（失败时自动退避重试、最终兜底写 spool）
+                        self.send_batch_with_resilience(&batch, &mut stats).await;
+                        let sent_count = batch.len();
                         batch.clear();
                         last_send = tokio::time::Instant::now();
+                        self.sync_batch_byte_stats(&mut stats);
 
                         // 每次发送后输出统计信息
-                        println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {})",
+                        println!("[BATCH_STATS] 接收: {}, 处理: {}, 跳过: {} (隐藏: {}, 规则排除: {}, 无效扩展名: {}, .DS_Store: {}, 目录: {}, Bundle: {}), 累计发送字节: {} (压缩前: {})",
                             stats.received_files,
                             stats.processed_files,
                             stats.received_files - stats.processed_files,
@@ -1867,7 +3353,24 @@ impl FileMonitor {
                             stats.invalid_extension_skipped,
                             stats.ds_store_skipped,
                             stats.directory_skipped,
-                            stats.bundle_skipped
+                            stats.bundle_skipped,
+                            stats.batch_bytes_sent,
+                            stats.batch_bytes_uncompressed
+                        );
+                        self.emit_scan_event(
+                            ScanEvent::BatchSent {
+                                sent: sent_count,
+                                processed: stats.processed_files,
+                                skipped_breakdown: ScanSkipBreakdown {
+                                    hidden: stats.hidden_files_skipped,
+                                    rule_excluded: stats.rule_excluded_files_skipped,
+                                    invalid_extension: stats.invalid_extension_skipped,
+                                    ds_store: stats.ds_store_skipped,
+                                    directory: stats.directory_skipped,
+                                    bundle: stats.bundle_skipped,
+                                },
+                            },
+                            app_handle.as_ref(),
                         );
                     }
                 }
@@ -1875,6 +3378,103 @@ impl FileMonitor {
         }
     }
 
+    // 把 `paths` 里已经通过过滤（隐藏文件/黑名单/bundle/扩展名等，由调用方
+    // 在构造 `paths` 时做完）的路径，通过一个有界 mpsc 通道分发给一个大小为
+    // `available_parallelism()` 的 worker 任务池并发执行 `process_file_event`，
+    // 取代过去 `perform_initial_scan`/`scan_single_directory` 各自单任务内
+    // 逐条 `.await` 的串行处理方式。`process_file_event` 本身是 `async fn`
+    // （内部有配置缓存锁、可能的 HTTP 调用、哈希计算等 await 点），所以这里
+    // 用 `tokio::spawn` 任务而不是 file_scanner.rs 那套面向同步函数的
+    // `std::thread::scope` 方案。返回 (处理成功数, 跳过数)，调用方负责把它们
+    // 并入 `self.stats`。
+    async fn run_scan_worker_pool(
+        &self,
+        directory: &str,
+        paths: impl Iterator<Item = PathBuf>,
+        tx_metadata: &Sender<FileMetadata>,
+        app_handle: &tauri::AppHandle,
+    ) -> (u64, u64) {
+        let worker_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4);
+
+        let (path_tx, path_rx) = mpsc::channel::<PathBuf>(PARALLEL_SCAN_CHANNEL_CAPACITY);
+        let path_rx = Arc::new(tokio::sync::Mutex::new(path_rx));
+
+        let received = Arc::new(AtomicU64::new(0));
+        let processed = Arc::new(AtomicU64::new(0));
+        let skipped = Arc::new(AtomicU64::new(0));
+        let start = std::time::Instant::now();
+
+        let mut workers = Vec::with_capacity(worker_count);
+        for _ in 0..worker_count {
+            let self_clone = self.clone();
+            let path_rx = Arc::clone(&path_rx);
+            let tx_metadata = tx_metadata.clone();
+            let app_handle = app_handle.clone();
+            let directory = directory.to_string();
+            let received = Arc::clone(&received);
+            let processed = Arc::clone(&processed);
+            let skipped = Arc::clone(&skipped);
+
+            workers.push(tokio::spawn(async move {
+                loop {
+                    let path = {
+                        let mut rx_guard = path_rx.lock().await;
+                        rx_guard.recv().await
+                    };
+                    let Some(path) = path else {
+                        break;
+                    };
+
+                    if let Some(metadata) = self_clone
+                        .process_file_event(
+                            path,
+                            notify::EventKind::Create(notify::event::CreateKind::Any),
+                            &app_handle,
+                        )
+                        .await
+                    {
+                        let _ = tx_metadata.send(metadata).await;
+                        let done = processed.fetch_add(1, Ordering::Relaxed) + 1;
+                        if done % PARALLEL_SCAN_PROGRESS_INTERVAL == 0 {
+                            let elapsed_secs = start.elapsed().as_secs_f64().max(0.001);
+                            let _ = app_handle.emit(
+                                "file_monitor_scan_progress",
+                                ParallelScanProgress {
+                                    directory: directory.clone(),
+                                    files_received: received.load(Ordering::Relaxed),
+                                    files_processed: done,
+                                    files_skipped: skipped.load(Ordering::Relaxed),
+                                    files_per_second: done as f64 / elapsed_secs,
+                                },
+                            );
+                        }
+                    } else {
+                        skipped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            }));
+        }
+
+        for path in paths {
+            received.fetch_add(1, Ordering::Relaxed);
+            if path_tx.send(path).await.is_err() {
+                break;
+            }
+        }
+        drop(path_tx);
+
+        for worker in workers {
+            let _ = worker.await;
+        }
+
+        (
+            processed.load(Ordering::Relaxed),
+            skipped.load(Ordering::Relaxed),
+        )
+    }
+
     // 执行初始扫描
     async fn perform_initial_scan(
         &self,
@@ -1894,6 +3494,10 @@ impl FileMonitor {
 
         let directories = self.monitored_dirs.lock().unwrap().clone();
 
+        // 加载上次中断时留下的扫描检查点（没有配置 `scan_checkpoint_dir` 或
+        // 没有检查点文件时就是默认值，等价于从头全量扫描）。
+        let mut checkpoint = self.load_scan_checkpoint();
+
         // 获取完全磁盘访问权限状态
         let full_disk_access = {
             let cache_guard = self.config_cache.lock().unwrap();
@@ -1917,10 +3521,42 @@ impl FileMonitor {
                 continue;
             }
 
+            // 这个目录已经在检查点里标记完成过（上次中断前已经扫完），本次
+            // 直接跳过，不再重新全量扫一遍。
+            if checkpoint.completed_dirs.iter().any(|d| d == &dir.path) {
+                println!(
+                    "[INITIAL_SCAN] 目录已在扫描检查点中标记完成，跳过: {}",
+                    dir.path
+                );
+                continue;
+            }
+
+            // 如果上次中断时正扫到这个目录，带上保存的游标做 fast-forward；
+            // 否则（全新目录）没有游标，从头扫。
+            let resume_cursor = if checkpoint.in_progress_dir.as_deref() == Some(dir.path.as_str())
+            {
+                checkpoint.cursor.clone()
+            } else {
+                None
+            };
+            if let Some(cursor) = &resume_cursor {
+                println!(
+                    "[INITIAL_SCAN] 从检查点恢复目录 {} 的扫描，游标: {}",
+                    dir.path, cursor
+                );
+            }
+
             println!("[INITIAL_SCAN] 扫描目录: {}", dir.path);
             let path = PathBuf::from(&dir.path);
             if !path.exists() {
                 println!("[INITIAL_SCAN] 目录不存在: {}", dir.path);
+                self.emit_scan_event(
+                    ScanEvent::Error {
+                        context: format!("perform_initial_scan:{}", dir.path),
+                        message: "目录不存在".to_string(),
+                    },
+                    Some(app_handle),
+                );
                 continue;
             }
 
@@ -1932,10 +3568,34 @@ impl FileMonitor {
             let mut processed_files = 0;
             let mut skipped_bundles = 0;
 
-            println!("[INITIAL_SCAN] 开始递归扫描目录: {}", dir.path);
+            println!(
+                "[INITIAL_SCAN] 开始递归扫描目录: {} (最大深度: {})",
+                dir.path,
+                dir.max_depth
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "不限".to_string())
+            );
+
+            // 按 MonitoredDirectory.max_depth 限制递归层数：None 保持和之前
+            // 一样完全递归，Some(n) 直接对应 WalkDir::max_depth(n)（同一个
+            // 字段也用来决定 file_monitor_debounced 里实时监控是否递归）。
+            let mut walker_builder = WalkDir::new(&path);
+            if let Some(depth) = dir.max_depth {
+                walker_builder = walker_builder.max_depth(depth as usize);
+            }
 
             // 修改扫描方法，使用过滤器来排除不需要处理的路径
-            let walker = WalkDir::new(&path).into_iter().filter_entry(|e| {
+            let walker = walker_builder.into_iter().filter_entry(|e| {
+                // 从检查点恢复时，跳过游标之前的路径（按绝对路径字符串的字典序
+                // 比较）——已经在上次扫描里处理过或已知排在前面，不需要重扫。
+                // 这是一个简化近似：目录也会被整体剪掉，哪怕目录内部个别文件名
+                // 实际排在游标之后，换取恢复逻辑足够简单。
+                if let Some(cursor) = &resume_cursor {
+                    if e.path().to_string_lossy().as_ref() < cursor.as_str() {
+                        return false;
+                    }
+                }
+
                 // 不扫描隐藏文件
                 if Self::is_hidden_file(e.path()) {
                     return false;
@@ -2014,50 +3674,62 @@ impl FileMonitor {
                 true
             });
 
-            // 正常处理剩下的文件
-            let mut files_processed_count = 0;
-            for entry_result in walker {
-                // 忽略错误条目
+            // 把过滤后剩下的路径喂给 worker 池并发处理，取代过去单任务内逐条
+            // `.await` `process_file_event` 的串行循环。黑名单配置的周期性
+            // 复查（每 1000 个条目一次）留在这里的生产者端完成，这样被动态
+            // 加入黑名单的路径根本不会被派发给 worker。
+            let mut files_processed_count: u64 = 0;
+            let path_iter = walker.filter_map(|entry_result| {
                 let entry = match entry_result {
                     Ok(e) => e,
-                    Err(_) => continue,
+                    Err(_) => return None,
                 };
 
                 total_files += 1;
                 let entry_path = entry.path().to_path_buf();
 
-                // 每处理1000个文件时重新检查黑名单配置（防止配置更新后继续扫描已加入黑名单的路径）
                 files_processed_count += 1;
                 if files_processed_count % 1000 == 0 {
-                    // 动态检查路径是否现在在黑名单中（配置可能已更新）
+                    // 复用同一个 1000 条的节奏落一次扫描检查点，记录"扫到这个
+                    // 目录、这个路径了"，中断后下次启动能从这里 fast-forward，
+                    // 而不是整棵树重扫。
+                    checkpoint.in_progress_dir = Some(dir.path.clone());
+                    checkpoint.cursor = Some(entry_path.to_string_lossy().to_string());
+                    self.save_scan_checkpoint(&checkpoint);
+
                     if self.is_in_blacklist(&entry_path) {
                         println!(
                             "[INITIAL_SCAN] 检测到配置更新，跳过新加入黑名单的路径: {:?}",
                             entry_path
                         );
                         skipped_files += 1;
-                        continue;
+                        return None;
                     }
                 }
 
-                // 处理文件事件
-                if let Some(metadata) = self
-                    .process_file_event(
-                        entry_path,
-                        notify::EventKind::Create(notify::event::CreateKind::Any),
-                        app_handle,
-                    )
-                    .await
-                {
-                    let _ = tx_metadata.send(metadata).await;
-                    processed_files += 1;
-                } else {
-                    skipped_files += 1;
-                }
-            }
+                Some(entry_path)
+            });
 
-            println!("[INITIAL_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
-                     dir.path, total_files, processed_files, skipped_files, skipped_bundles);
+            let (pool_processed, pool_skipped) = self
+                .run_scan_worker_pool(&dir.path, path_iter, tx_metadata, app_handle)
+                .await;
+            processed_files += pool_processed as i32;
+            skipped_files += pool_skipped as i32;
+
+            println!("[INITIAL_SCAN] 目录 {} 扫描完成 (最大深度: {}): 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})",
+                     dir.path,
+                     dir.max_depth.map(|d| d.to_string()).unwrap_or_else(|| "不限".to_string()),
+                     total_files, processed_files, skipped_files, skipped_bundles);
+            self.emit_scan_event(
+                ScanEvent::DirectoryCompleted {
+                    path: dir.path.clone(),
+                    total_files: total_files as u64,
+                    processed_files: processed_files as u64,
+                    skipped_files: skipped_files as u64,
+                    skipped_bundles: skipped_bundles as u64,
+                },
+                Some(app_handle),
+            );
 
             // 更新全局统计信息
             if let Ok(mut stats) = self.stats.lock() {
@@ -2065,8 +3737,19 @@ impl FileMonitor {
                 stats.filtered_files += skipped_files as u64;
                 stats.filtered_bundles += skipped_bundles as u64;
             }
+
+            // 这个目录完整扫完了，从"进行中"挪到"已完成"，清掉游标。
+            checkpoint.completed_dirs.push(dir.path.clone());
+            checkpoint.in_progress_dir = None;
+            checkpoint.cursor = None;
+            self.save_scan_checkpoint(&checkpoint);
         }
 
+        // 所有监控目录都扫完了，检查点已经没用了，清掉文件而不是留着一个
+        // "全部完成"的检查点——下次启动走正常的全新初始扫描判断逻辑
+        // （`is_initial_scan_running`），不依赖检查点内容。
+        self.clear_scan_checkpoint();
+
         Ok(())
     }
 
@@ -2114,9 +3797,10 @@ impl FileMonitor {
         let batch_size = self.batch_size;
         let batch_interval = self.batch_interval;
         let self_clone_for_batch = self.clone();
+        let app_handle_for_batch = app_handle.clone();
         tokio::spawn(async move {
             self_clone_for_batch
-                .batch_processor(metadata_rx, batch_size, batch_interval)
+                .batch_processor(metadata_rx, batch_size, batch_interval, Some(app_handle_for_batch))
                 .await;
         });
 
@@ -2130,6 +3814,13 @@ impl FileMonitor {
                 .await
             {
                 eprintln!("[INITIAL_SCAN] Error: {}", e);
+                self_clone_for_scan.emit_scan_event(
+                    ScanEvent::Error {
+                        context: "perform_initial_scan".to_string(),
+                        message: e,
+                    },
+                    Some(&app_handle_for_scan),
+                );
             }
 
             // 初始扫描后批处理器会自动发送数据到API
@@ -2174,17 +3865,41 @@ impl FileMonitor {
         let batch_size = self.batch_size;
         let batch_interval = self.batch_interval;
         let self_clone_for_batch = self.clone();
+        let app_handle_for_batch = app_handle.cloned();
         tokio::spawn(async move {
             self_clone_for_batch
-                .batch_processor(metadata_rx, batch_size, batch_interval)
+                .batch_processor(metadata_rx, batch_size, batch_interval, app_handle_for_batch)
                 .await;
         });
 
+        // 和 perform_initial_scan 一样按对应 MonitoredDirectory.max_depth 限制
+        // 递归层数：这个函数只拿到 path 字符串，从 monitored_dirs 里按路径查
+        // 一下配置；查不到（比如临时扫一个没加入监控列表的目录）就不限制。
+        let max_depth = self
+            .monitored_dirs
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|d| d.path == path)
+            .and_then(|d| d.max_depth);
+
         // 扫描目录
-        println!("[SINGLE_SCAN] 开始扫描目录: {}", path);
+        println!(
+            "[SINGLE_SCAN] 开始扫描目录: {} (最大深度: {})",
+            path,
+            max_depth.map(|d| d.to_string()).unwrap_or_else(|| "不限".to_string())
+        );
         let path_buf = PathBuf::from(path);
         if !path_buf.exists() {
-            return Err(format!("目录不存在: {}", path));
+            let message = format!("目录不存在: {}", path);
+            self.emit_scan_event(
+                ScanEvent::Error {
+                    context: format!("scan_single_directory:{}", path),
+                    message: message.clone(),
+                },
+                app_handle,
+            );
+            return Err(message);
         }
 
         let mut total_files = 0;
@@ -2193,7 +3908,11 @@ impl FileMonitor {
         let mut skipped_bundles = 0;
 
         // 使用 WalkDir 执行递归扫描
-        let walker = WalkDir::new(&path_buf).into_iter().filter_entry(|e| {
+        let mut walker_builder = WalkDir::new(&path_buf);
+        if let Some(depth) = max_depth {
+            walker_builder = walker_builder.max_depth(depth as usize);
+        }
+        let walker = walker_builder.into_iter().filter_entry(|e| {
             // 不扫描隐藏文件
             if Self::is_hidden_file(e.path()) {
                 return false;
@@ -2216,57 +3935,87 @@ impl FileMonitor {
                 return false;
             }
 
+            // 黑名单（含 .gitignore/.ignore/.leafignore、见 is_in_blacklist）
+            // 剪枝：和 perform_initial_scan 的 filter_entry 一样在这里判断，
+            // 命中就不展开整棵子树，而不是等 WalkDir 展开完之后靠
+            // process_file_event 逐条事后跳过。
+            if self.is_in_blacklist(e.path()) {
+                return false;
+            }
+
             true
         });
 
-        for entry in walker {
-            match entry {
-                Ok(entry) => {
-                    total_files += 1;
+        // 有 app_handle 时把过滤后的文件路径喂给 worker 池并发处理（复用
+        // `perform_initial_scan` 同一套 `run_scan_worker_pool`）；没有
+        // app_handle 时退化为原来的逐条跳过逻辑，不做任何并发处理。
+        match app_handle {
+            Some(app_handle) => {
+                let path_iter = walker.filter_map(|entry_result| {
+                    let entry = match entry_result {
+                        Ok(e) => e,
+                        Err(e) => {
+                            eprintln!("[SINGLE_SCAN] 无法访问项目: {}", e);
+                            skipped_files += 1;
+                            return None;
+                        }
+                    };
 
+                    total_files += 1;
                     if total_files % 100 == 0 {
                         println!("[SINGLE_SCAN] 扫描进度: {} 个文件", total_files);
                     }
 
                     if !entry.file_type().is_file() {
-                        continue; // 仅处理文件，跳过目录
+                        return None; // 仅处理文件，跳过目录
                     }
 
-                    // 处理单个文件 - 复用现有的 process_file_event 方法
-                    if let Some(app_handle) = app_handle {
-                        if let Some(metadata) = self
-                            .process_file_event(
-                                entry.path().to_path_buf(),
-                                notify::EventKind::Create(notify::event::CreateKind::Any),
-                                app_handle,
-                            )
-                            .await
-                        {
-                            if metadata_tx.send(metadata).await.is_err() {
-                                eprintln!("[SINGLE_SCAN] 无法发送元数据到批处理器，通道可能已关闭");
+                    Some(entry.path().to_path_buf())
+                });
+
+                let (pool_processed, pool_skipped) = self
+                    .run_scan_worker_pool(path, path_iter, &metadata_tx, app_handle)
+                    .await;
+                processed_files += pool_processed as i32;
+                skipped_files += pool_skipped as i32;
+            }
+            None => {
+                for entry in walker {
+                    match entry {
+                        Ok(entry) => {
+                            total_files += 1;
+                            if !entry.file_type().is_file() {
+                                continue; // 仅处理文件，跳过目录
                             }
-                            processed_files += 1;
-                        } else {
+                            eprintln!(
+                                "[SINGLE_SCAN] 跳过文件，因为没有提供 app_handle: {:?}",
+                                entry.path()
+                            );
+                            skipped_files += 1;
+                        }
+                        Err(e) => {
+                            eprintln!("[SINGLE_SCAN] 无法访问项目: {}", e);
                             skipped_files += 1;
                         }
-                    } else {
-                        // 如果没有 app_handle，跳过此文件或使用备用处理逻辑
-                        eprintln!(
-                            "[SINGLE_SCAN] 跳过文件，因为没有提供 app_handle: {:?}",
-                            entry.path()
-                        );
-                        skipped_files += 1;
                     }
                 }
-                Err(e) => {
-                    eprintln!("[SINGLE_SCAN] 无法访问项目: {}", e);
-                    skipped_files += 1;
-                }
             }
         }
 
-        println!("[SINGLE_SCAN] 目录 {} 扫描完成: 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})", 
-            path, total_files, processed_files, skipped_files, skipped_bundles);
+        println!("[SINGLE_SCAN] 目录 {} 扫描完成 (最大深度: {}): 总文件数 {}, 处理文件数 {}, 跳过文件数 {} (其中macOS包数量: {})",
+            path,
+            max_depth.map(|d| d.to_string()).unwrap_or_else(|| "不限".to_string()),
+            total_files, processed_files, skipped_files, skipped_bundles);
+        self.emit_scan_event(
+            ScanEvent::DirectoryCompleted {
+                path: path.to_string(),
+                total_files: total_files as u64,
+                processed_files: processed_files as u64,
+                skipped_files: skipped_files as u64,
+                skipped_bundles: skipped_bundles as u64,
+            },
+            app_handle,
+        );
 
         // 更新统计信息
         if let Ok(mut stats) = self.stats.lock() {
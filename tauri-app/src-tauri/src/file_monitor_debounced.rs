@@ -1,22 +1,44 @@
 use crate::file_monitor::FileMonitor;
+use crate::watch_exclusions::WatchExclusions;
 use notify::event::{CreateKind, ModifyKind, RemoveKind, RenameMode};
-use notify::{EventKind, RecursiveMode, Watcher};
+use notify::{Config, EventKind, PollWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc as std_mpsc;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 use std::time::Duration;
 use tauri::Emitter;
 use tokio::sync::mpsc::{self, Sender};
 use tokio::sync::Mutex;
 
-// 定义简化的文件事件类型
-#[derive(Debug, Clone, PartialEq, Eq)]
-#[allow(dead_code)] // 显式允许枚举定义被保留，即使当前未使用
+/// 一次防抖窗口结束时，对前端增量更新有意义的变更种类。相比中央处理器里
+/// `simplified_kind` 那套只分新增/删除两档、专注于驱动元数据扫描的归类，
+/// 这里保留了 modify 和 rename 配对信息，发给前端之后可以原地移动/更新
+/// 树节点，而不是先删再插——对大目录重命名尤其明显，删再插会让那个
+/// 子树在 UI 上闪一下并丢失展开状态。
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum SimpleFileEvent {
-    Added(PathBuf),   // 文件新增（包括创建和移入）
-    Removed(PathBuf), // 文件删除（包括删除和移出）
+    Created { path: String },
+    Modified { path: String },
+    Removed { path: String },
+    /// `from`/`to` 是防抖窗口内从相关联的 `RenameMode::From`/`To`（或拆开的
+    /// `RenameMode::Both`，见 `build_event_handler`）两个事件重新配对出来的，
+    /// 窗口内只有单边到场（另一边落在了窗口外，或者对端路径本身被排除规则
+    /// 过滤掉了）时退化成对应的 [`SimpleFileEvent::Removed`]/[`SimpleFileEvent::Created`]。
+    Renamed { from: String, to: String },
+}
+
+/// 一次防抖窗口结束后发给前端的批量变更事件（见 [`SimpleFileEvent`]），和
+/// `commands.rs` 里 `DirectoryStreamBatch` 同一个风格：只带这次变更必要的
+/// 信息，不复用内部的元数据结构。
+#[derive(Debug, Clone, Serialize)]
+struct FileChangeBatch {
+    directory: String,
+    timestamp: String,
+    events: Vec<SimpleFileEvent>,
 }
 
 /// 防抖动文件监控器
@@ -29,11 +51,262 @@ pub struct DebouncedFileMonitor {
     /// 防抖事件缓冲区 (仅保留用于扩展但当前未使用)
     #[allow(dead_code)]
     debounce_buffer: Arc<Mutex<HashMap<PathBuf, notify::EventKind>>>,
-    /// 保存监控路径到其停止发送器的映射，用于停止特定路径的监控 (仅保留用于扩展但当前未使用)
-    #[allow(dead_code)]
+    /// 规范化目录路径 -> 该目录监控线程的停止发送器。`setup_single_debounced_watch`
+    /// 为每个目录创建独立的 `std_mpsc` 停止通道并把发送端返回给调用方
+    /// （`start_monitoring`/`add_directory`）登记在这里；`stop_monitoring`/
+    /// `remove_directory` 据此向具体某个目录的监控线程发送停止信号，不影响
+    /// 其它目录。
     watch_stop_channels: Arc<Mutex<HashMap<String, std_mpsc::Sender<()>>>>,
     /// Tauri应用程序句柄，用于发射事件到前端
     app_handle: Option<tauri::AppHandle>,
+    /// 监控排除规则（见 `watch_exclusions` 模块）：在 notify 回调里对每个
+    /// 事件路径做检查，命中的路径在进入防抖缓冲区之前就被丢弃，避免临时
+    /// 文件/`.git` 内部对象/生成产物的churn 触发不必要的重扫，甚至形成
+    /// "扫描写回 -> 再次触发扫描" 的反馈循环。用 `std::sync::Mutex` 而不是
+    /// `tokio::sync::Mutex`，是因为这个锁要在 notify 的同步回调（运行在
+    /// 专门的 OS 线程上，见 `setup_single_debounced_watch`）里读取，不在
+    /// async 上下文里。默认是空规则集，行为和加这个功能之前完全一样。
+    exclusions: Arc<StdMutex<WatchExclusions>>,
+    /// watch 后端选择：是否强制使用 `PollWatcher`（即使原生 watcher 能正常
+    /// 创建/注册也不用），以及 `PollWatcher` 的轮询间隔。见
+    /// [`DebouncedFileMonitor::set_poll_watch_config`] 和
+    /// `setup_single_debounced_watch` 里的 fallback 逻辑。
+    force_poll: Arc<StdMutex<bool>>,
+    poll_interval: Arc<StdMutex<Duration>>,
+    /// 最近一次 `start_monitoring` 使用的防抖时长，供之后动态 `add_directory`
+    /// 的新目录复用，保持和其它目录一致的防抖行为；`start_monitoring` 之前
+    /// 调用 `add_directory` 没有意义（还没有中央事件处理器可以接），会报错。
+    current_debounce_time: Arc<StdMutex<Option<Duration>>>,
+}
+
+/// `PollWatcher` 在原生 watcher 不可用/不可靠时的默认轮询间隔。原生
+/// watcher 在 NFS/SMB 挂载点、部分容器 overlay 文件系统、某些云同步文件夹
+/// 上会静默收不到事件，这时只能退化成定期整树扫描比较快照——间隔太短会
+/// 显著增加 IO 负担，这里选一个和这类场景下"偶尔漏几秒也能接受"相匹配的
+/// 默认值，用户可以通过 `set_poll_watch_config` 按需调整。
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 构建 notify 事件回调：原生 `recommended_watcher` 和 `PollWatcher` 共用
+/// 同一套"排除检查 -> 简化事件种类 -> 发送到防抖队列"逻辑，区别只在于
+/// 事件从哪来（内核通知 vs. 周期性整树扫描比较快照），所以回调本身抽成
+/// 一个独立函数，两种 watcher 各自传入自己的一份克隆状态构造一份独立的
+/// 闭包，而不是在两处各写一遍。
+fn build_event_handler(
+    debounce_tx: Sender<(PathBuf, notify::EventKind)>,
+    exclusions: Arc<StdMutex<WatchExclusions>>,
+    watch_root_canonical: Option<PathBuf>,
+) -> impl FnMut(std::result::Result<notify::Event, notify::Error>) + Send + 'static {
+    move |res: std::result::Result<notify::Event, notify::Error>| {
+        println!("🔔🔔🔔 NOTIFY EVENT CALLBACK 🔔🔔🔔");
+
+        match res {
+            Ok(event) => {
+                println!("🔔 Event Type: {:?}", event.kind);
+                println!("🔔 Paths: {:?}", event.paths);
+
+                // 将事件发送到防抖队列
+                let paths = event.paths.clone();
+                let kind = event.kind.clone();
+
+                // 这个回调跑在独立的 OS 线程上（见 setup_single_debounced_watch），
+                // 不在任何 tokio 运行时的 worker 线程里，所以可以直接用
+                // `blocking_send` 同步地往 `tokio::sync::mpsc` 通道发送，不需要
+                // 为了 `.await` 一次发送就临时搭一个 `tokio::runtime::Builder`
+                // 再拆掉——高频事件（全选保存、大批量签出、解压缩）下，每个
+                // 事件都新建+销毁一个运行时会显著拖慢这个回调，进而让 notify
+                // 的事件队列在内核侧持续积压。
+                for (index, path) in paths.iter().enumerate() {
+                    let debounce_tx = debounce_tx.clone();
+
+                    // 先做排除检查（规范化之后），命中的路径
+                    // 直接丢弃，不进入防抖队列——这样被排除的
+                    // 目录连同它所有的后代路径都不会被处理，
+                    // 等价于把整棵子树都剪掉了。
+                    let canonical_path = path.canonicalize().unwrap_or_else(|_| path.clone());
+                    let absolute_path_str = canonical_path.to_string_lossy().to_string();
+                    let relative_path_str = watch_root_canonical.as_ref().and_then(|root| {
+                        canonical_path
+                            .strip_prefix(root)
+                            .ok()
+                            .map(|rel| rel.to_string_lossy().replace('\\', "/"))
+                    });
+                    let is_excluded = exclusions
+                        .lock()
+                        .unwrap()
+                        .is_excluded(&absolute_path_str, relative_path_str.as_deref());
+                    if is_excluded {
+                        println!("🔔🚫 路径命中排除规则，跳过: {:?}", path);
+                        continue;
+                    }
+
+                    // 简化事件种类: Create, Remove 或 Modify
+                    // 对于文件路径，我们需要处理实际存在与否
+                    let processed_kind = match &kind {
+                        EventKind::Create(_) => kind.clone(),
+                        EventKind::Remove(_) => kind.clone(),
+                        // `RenameMode::Both` 在一次 notify 事件里同时带着 `[from, to]`
+                        // 两个路径（按文档顺序），这里按下标直接拆成一个 From 事件和
+                        // 一个 To 事件分别送进防抖队列，而不是把 Both 原样发两份——
+                        // 这样防抖窗口里只需要认 From/To 一种配对方式（见
+                        // `setup_single_debounced_watch` 的 settle 逻辑），不用再单独
+                        // 处理"两个路径来自同一个 Both 事件"这种情况。
+                        EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if paths.len() == 2 => {
+                            if index == 0 {
+                                EventKind::Modify(ModifyKind::Name(RenameMode::From))
+                            } else {
+                                EventKind::Modify(ModifyKind::Name(RenameMode::To))
+                            }
+                        }
+                        // Rename 的 From/To/Both 原样透传，不在这一层就按存在性猜成
+                        // Create/Remove——下面中央处理器里已经有针对这三种
+                        // RenameMode 的专门处理（见 start_monitoring 的
+                        // simplified_kind 匹配），只有在这里保留原始种类才能让
+                        // 那段逻辑生效，否则编辑器"写临时文件再整体 rename 覆盖
+                        // 目标"的原子保存会被提前拆成互不相关的一次删除+一次新增，
+                        // 而且判断时机正好卡在 rename 的间隙上，存在竞态。
+                        EventKind::Modify(ModifyKind::Name(_)) => kind.clone(),
+                        // 内容/元数据修改原样透传，给前端的"修改"通知用得上（见
+                        // `SimpleFileEvent::Modified`）；`ModifyKind::Any`/`Other`
+                        // 语义不明确，仍然退化到下面的存在性探测。
+                        EventKind::Modify(ModifyKind::Data(_))
+                        | EventKind::Modify(ModifyKind::Metadata(_)) => kind.clone(),
+                        _ => {
+                            // 对于其他事件类型，用一次 stat 同时判断存在性，而不是
+                            // `path.exists() && path.is_file()` 这种两次独立系统调用
+                            // ——减少两次调用之间文件被进一步改动的竞态窗口（见
+                            // file_id_tracker 模块说明）。
+                            if crate::file_id_tracker::file_id(path).is_some() {
+                                // 文件存在，当作新增处理
+                                EventKind::Create(CreateKind::File)
+                            } else {
+                                // 文件不存在，当作删除处理
+                                EventKind::Remove(RemoveKind::File)
+                            }
+                        }
+                    };
+
+                    // 发送到防抖队列
+                    if let Err(e) = debounce_tx.blocking_send((path.clone(), processed_kind)) {
+                        eprintln!("🔔❌ 发送到防抖队列失败: {}", e);
+                    } else {
+                        println!(
+                            "🔔✅ 事件已发送到防抖队列: {:?} -> {:?}",
+                            processed_kind, path
+                        );
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("🔔❌ 监控错误: {:?}", e);
+            }
+        }
+        println!("🔔🔔🔔 NOTIFY CALLBACK END 🔔🔔🔔");
+    }
+}
+
+/// 把一个防抖窗口里攒下的原始 notify 事件归并成前端关心的
+/// [`SimpleFileEvent`] 列表：`Create`/`Remove`/内容或元数据修改原样对应
+/// 过去，`RenameMode::From`/`To`（含被 `build_event_handler` 拆开的
+/// `RenameMode::Both`）按到达顺序两两配对成 [`SimpleFileEvent::Renamed`]；
+/// 某一边在这个窗口里缺席（落在窗口外，或者对端路径命中了排除规则）就
+/// 退化成对应的单边事件，不强行等待另一半出现。
+fn build_simple_events(events: &HashMap<PathBuf, notify::EventKind>) -> Vec<SimpleFileEvent> {
+    let mut rename_froms: Vec<&PathBuf> = Vec::new();
+    let mut rename_tos: Vec<&PathBuf> = Vec::new();
+    let mut simple_events = Vec::new();
+
+    for (path, kind) in events {
+        match kind {
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => rename_froms.push(path),
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => rename_tos.push(path),
+            EventKind::Create(_) => simple_events.push(SimpleFileEvent::Created {
+                path: path.to_string_lossy().to_string(),
+            }),
+            EventKind::Remove(_) => simple_events.push(SimpleFileEvent::Removed {
+                path: path.to_string_lossy().to_string(),
+            }),
+            EventKind::Modify(ModifyKind::Data(_)) | EventKind::Modify(ModifyKind::Metadata(_)) => {
+                simple_events.push(SimpleFileEvent::Modified {
+                    path: path.to_string_lossy().to_string(),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    let mut froms = rename_froms.into_iter();
+    let mut tos = rename_tos.into_iter();
+    loop {
+        match (froms.next(), tos.next()) {
+            (Some(from), Some(to)) => simple_events.push(SimpleFileEvent::Renamed {
+                from: from.to_string_lossy().to_string(),
+                to: to.to_string_lossy().to_string(),
+            }),
+            (Some(from), None) => simple_events.push(SimpleFileEvent::Removed {
+                path: from.to_string_lossy().to_string(),
+            }),
+            (None, Some(to)) => simple_events.push(SimpleFileEvent::Created {
+                path: to.to_string_lossy().to_string(),
+            }),
+            (None, None) => break,
+        }
+    }
+
+    simple_events
+}
+
+/// 把一批 [`SimpleFileEvent`] 包装成 [`FileChangeBatch`] 发给前端；没有
+/// `AppHandle`（比如测试场景）或者这个窗口里其实没有值得上报的事件时
+/// 什么都不做。
+fn emit_file_change_batch(
+    app_handle: &Option<tauri::AppHandle>,
+    directory: &str,
+    events: Vec<SimpleFileEvent>,
+) {
+    if events.is_empty() {
+        return;
+    }
+    let Some(app_handle) = app_handle else {
+        return;
+    };
+    let payload = FileChangeBatch {
+        directory: directory.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        events,
+    };
+    if let Err(e) = app_handle.emit("file_change_batch", &payload) {
+        eprintln!("[防抖处理] 发射 file_change_batch 事件失败: {}", e);
+    }
+}
+
+/// 构建一个 `PollWatcher`：周期性整树扫描、对比快照算出变化，不依赖内核
+/// 的文件系统通知机制，所以在原生 watcher 收不到事件的文件系统上也能
+/// 工作。关掉内容比较（`with_compare_contents(false)`，只比较 mtime/大小
+/// 等元数据），和原生 watcher 的行为对齐，避免每次轮询都要把所有文件内容
+/// 读一遍去做字节级比较——那样开销和这个兜底方案本来要避免的问题一样大。
+fn build_poll_watcher(
+    poll_interval: Duration,
+    debounce_tx: Sender<(PathBuf, notify::EventKind)>,
+    exclusions: Arc<StdMutex<WatchExclusions>>,
+    watch_root_canonical: Option<PathBuf>,
+) -> notify::Result<PollWatcher> {
+    let config = Config::default()
+        .with_poll_interval(poll_interval)
+        .with_compare_contents(false);
+    PollWatcher::new(
+        build_event_handler(debounce_tx, exclusions, watch_root_canonical),
+        config,
+    )
+}
+
+/// `watch_stop_channels` 的键：规范化目录路径，这样同一个目录不管以
+/// 相对路径还是带 `..`/符号链接的形式传进来，`add_directory`/
+/// `remove_directory` 都能认成同一个键；规范化失败（比如目录还不存在）
+/// 时退化为原始字符串，保证总能拿到一个可用的键。
+fn canonical_watch_key(dir: &str) -> String {
+    std::fs::canonicalize(dir)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|_| dir.to_string())
 }
 
 impl DebouncedFileMonitor {
@@ -45,17 +318,47 @@ impl DebouncedFileMonitor {
             debounce_buffer: Arc::new(Mutex::new(HashMap::new())),
             watch_stop_channels: Arc::new(Mutex::new(HashMap::new())),
             app_handle,
+            exclusions: Arc::new(StdMutex::new(WatchExclusions::compile(&[], &[]))),
+            force_poll: Arc::new(StdMutex::new(false)),
+            poll_interval: Arc::new(StdMutex::new(DEFAULT_POLL_INTERVAL)),
+            current_debounce_time: Arc::new(StdMutex::new(None)),
         }
     }
 
+    /// 同步监控排除规则（glob 模式 + 字面路径前缀）。和 `FileMonitor` 里
+    /// 一众 `set_*` 配置同步方法（如 `set_global_ignore_patterns`）一样，
+    /// 由 `AppState::update_simplified_config` 在配置变化时调用；对已经在
+    /// 跑的监控线程立即生效，不需要重启监控。
+    pub fn set_watch_exclusions(&self, glob_patterns: Vec<String>, literal_prefixes: Vec<String>) {
+        *self.exclusions.lock().unwrap() = WatchExclusions::compile(&glob_patterns, &literal_prefixes);
+    }
+
+    /// 配置 watch 后端：`force_poll` 为 `true` 时直接使用 `PollWatcher`，
+    /// 跳过原生 watcher 尝试（适合已知跑在 NFS/SMB/云同步文件夹上的场景，
+    /// 不用等一次原生 `watch()` 调用失败才退化）；`poll_interval_secs` 是
+    /// `PollWatcher` 的轮询间隔。只对之后（重新）启动的监控生效，不影响
+    /// 已经在跑的监控线程——需要改变已有监控的后端时，调用方应当先
+    /// `stop_monitoring` 再 `start_monitoring`。
+    pub fn set_poll_watch_config(&self, force_poll: bool, poll_interval_secs: u64) {
+        *self.force_poll.lock().unwrap() = force_poll;
+        *self.poll_interval.lock().unwrap() = Duration::from_secs(poll_interval_secs.max(1));
+    }
+
     /// Helper function to set up a debounced watch for a single directory.
     /// This function spawns a task that owns the debouncer after successful setup.
+    /// 成功时返回这个目录专属的停止发送器，调用方（`start_monitoring`/
+    /// `add_directory`）负责把它登记进 `self.watch_stop_channels`，这样以后
+    /// 才能单独停掉这一个目录的监控，而不用重启整个监控器。
     async fn setup_single_debounced_watch(
         dir_path_str: String, // Owned String
+        max_depth: Option<u32>,
         debounce_time: Duration,
         tx_to_central_handler: Sender<(PathBuf, notify::EventKind)>,
-        stop_tx_sender: Option<std_mpsc::Sender<std_mpsc::Sender<()>>>, // 可选的停止通道发送器
-    ) -> std::result::Result<(), String> {
+        exclusions: Arc<StdMutex<WatchExclusions>>,
+        force_poll: bool,
+        poll_interval: Duration,
+        app_handle: Option<tauri::AppHandle>,
+    ) -> std::result::Result<std_mpsc::Sender<()>, String> {
         println!(
             "[防抖监控] Setting up watch for directory: {}",
             dir_path_str
@@ -72,112 +375,44 @@ impl DebouncedFileMonitor {
 
         // 创建一个同步通道用于保持通信
         let (init_tx, init_rx) = std_mpsc::channel();
-        // 创建停止通道
+        // 创建停止通道：发送端返回给调用方登记，接收端交给下面持有 watcher
+        // 的线程，直接 `recv()` 阻塞等待，收到信号就退出（见该线程末尾），
+        // 不再需要额外一个线程来轮询转换成 `AtomicBool`。
         let (stop_tx, stop_rx) = std_mpsc::channel::<()>();
+        let stop_tx_for_caller = stop_tx.clone();
 
-        // 创建一个共享的停止标志
+        // 共享的停止标志：watcher 线程收到停止信号后会置位，供下面防抖处理
+        // 任务的 `tokio::select!` 轮询到后退出。
         let should_stop = Arc::new(AtomicBool::new(false));
         let should_stop_clone = should_stop.clone();
 
-        // 在单独的线程中监听停止信号
-        std::thread::spawn(move || {
-            if let Ok(_) = stop_rx.recv() {
-                should_stop_clone.store(true, Ordering::SeqCst);
-            }
-        });
-
-        // 如果提供了停止通道发送器，则发送停止通道
-        if let Some(tx_sender) = stop_tx_sender {
-            if let Err(e) = tx_sender.send(stop_tx.clone()) {
-                println!("[防抖监控] 无法注册停止通道: {:?}", e);
-                // 继续执行，但停止机制将无法工作
-            } else {
-                println!("[防抖监控] 已注册停止通道");
-            }
-        }
-
         // 在单独的线程中创建和运行 watcher
         // 这样避免了异步上下文的复杂性
         std::thread::spawn(move || {
+            // `stop_rx` 被这个线程拿走所有权：watcher 建好之后，线程会阻塞在
+            // 它的 `recv()` 上（见下方"保持 watcher 活跃"），不再像之前那样
+            // 定期 sleep 醒来什么都不做——收到停止信号就立刻置位
+            // `should_stop` 并让这个线程退出，`watcher` 随线程栈一起被
+            // drop，监控随之解除。
+            let stop_rx = stop_rx;
             println!("[文件监控-线程] 启动 watcher 线程");
 
-            // 创建 watcher
-            let mut watcher = match notify::recommended_watcher(
-                move |res: std::result::Result<notify::Event, notify::Error>| {
-                    println!("🔔🔔🔔 NOTIFY EVENT CALLBACK 🔔🔔🔔");
-
-                    match res {
-                        Ok(event) => {
-                            println!("🔔 Event Type: {:?}", event.kind);
-                            println!("🔔 Paths: {:?}", event.paths);
-
-                            // 将事件发送到防抖队列
-                            let paths = event.paths.clone();
-                            let kind = event.kind.clone();
-
-                            // 使用 tokio 当前线程运行时来处理异步发送
-                            let rt = tokio::runtime::Builder::new_current_thread()
-                                .enable_all()
-                                .build()
-                                .unwrap();
-
-                            rt.block_on(async {
-                                // 对每个路径发送事件到防抖缓冲区
-                                for path in paths {
-                                    let debounce_tx = debounce_tx.clone();
-
-                                    // 简化事件种类: Create, Remove 或 Modify
-                                    // 对于文件路径，我们需要处理实际存在与否
-                                    let processed_kind = match &kind {
-                                        EventKind::Create(_) => kind.clone(),
-                                        EventKind::Remove(_) => kind.clone(),
-                                        _ => {
-                                            // 对于其他事件类型，检查文件是否存在
-                                            if path.exists() && path.is_file() {
-                                                // 文件存在，当作新增处理
-                                                EventKind::Create(CreateKind::File)
-                                            } else {
-                                                // 文件不存在，当作删除处理
-                                                EventKind::Remove(RemoveKind::File)
-                                            }
-                                        }
-                                    };
-
-                                    // 发送到防抖队列
-                                    if let Err(e) =
-                                        debounce_tx.send((path.clone(), processed_kind)).await
-                                    {
-                                        eprintln!("🔔❌ 发送到防抖队列失败: {}", e);
-                                    } else {
-                                        println!(
-                                            "🔔✅ 事件已发送到防抖队列: {:?} -> {:?}",
-                                            processed_kind, path
-                                        );
-                                    }
-                                }
-                            });
-                        }
-                        Err(e) => {
-                            eprintln!("🔔❌ 监控错误: {:?}", e);
-                        }
-                    }
-                    println!("🔔🔔🔔 NOTIFY CALLBACK END 🔔🔔🔔");
-                },
-            ) {
-                Ok(w) => w,
-                Err(e) => {
-                    eprintln!("[文件监控-线程] 创建 watcher 失败: {:?}", e);
-                    let _ = init_tx.send(Err(format!("Failed to create watcher: {:?}", e)));
-                    return;
-                }
-            };
+            // 监控根目录的规范化形式，用于把事件路径换算成"相对监控根目录"的
+            // 字符串，供排除规则同时匹配绝对路径和相对路径；规范化失败（比如
+            // 目录还不存在）时退化为只用绝对路径匹配。
+            let watch_root_canonical = std::fs::canonicalize(&dir_path_for_watcher).ok();
+            let exclusions_for_watcher = exclusions.clone();
 
             // 检查路径是否存在
             let watch_path = Path::new(&dir_path_for_watcher);
             println!("[文件监控-线程] Path exists: {}", watch_path.exists());
             println!("[文件监控-线程] Path is dir: {}", watch_path.is_dir());
 
-            // 设置监控，检查是否为macOS bundle文件夹决定监控模式
+            // 设置监控模式：Bundle 文件夹优先按非递归处理；否则看这个目录是否
+            // 配置了 <= 1 的递归深度限制（只看直接子项）—— notify 只能表达
+            // "递归"或"不递归"两档，无法表达更深但有限的层数，所以深度限制
+            // 大于 1 时仍然退化为递归监控，深度上限只在 `scan_files_with_filter`
+            // 的 WalkDir 遍历里精确生效。
             let watch_mode = if crate::file_monitor::FileMonitor::is_macos_bundle_folder(watch_path)
             {
                 println!(
@@ -185,10 +420,73 @@ impl DebouncedFileMonitor {
                     dir_path_for_watcher
                 );
                 RecursiveMode::NonRecursive
+            } else if max_depth.map_or(false, |depth| depth <= 1) {
+                println!(
+                    "[文件监控-线程] 配置了深度限制 {:?}，使用非递归模式监控: {}",
+                    max_depth, dir_path_for_watcher
+                );
+                RecursiveMode::NonRecursive
             } else {
                 RecursiveMode::Recursive
             };
 
+            // 创建 watcher：默认尝试原生 `recommended_watcher`；原生 watcher 在
+            // NFS/SMB 挂载点、部分容器 overlay 文件系统、某些云同步文件夹上
+            // 经常创建/注册都"成功"却静默收不到任何事件，没有明确的错误可以
+            // 侦测，所以这里能自动兜底的只有"创建或 watch() 调用本身报错"这
+            // 一种情况——已知跑在这类文件系统上的场景，应当用
+            // `set_poll_watch_config(true, ..)` 主动要求跳过原生 watcher，直接
+            // 走下面的 `PollWatcher` 轮询整树比较快照的路径。
+            let mut watcher: Box<dyn Watcher + Send> = if force_poll {
+                println!(
+                    "[文件监控-线程] 已配置强制轮询模式，直接使用 PollWatcher (间隔 {:?}): {}",
+                    poll_interval, dir_path_for_watcher
+                );
+                match build_poll_watcher(
+                    poll_interval,
+                    debounce_tx.clone(),
+                    exclusions_for_watcher.clone(),
+                    watch_root_canonical.clone(),
+                ) {
+                    Ok(w) => Box::new(w),
+                    Err(e) => {
+                        eprintln!("[文件监控-线程] 创建 PollWatcher 失败: {:?}", e);
+                        let _ = init_tx.send(Err(format!("Failed to create PollWatcher: {:?}", e)));
+                        return;
+                    }
+                }
+            } else {
+                match notify::recommended_watcher(build_event_handler(
+                    debounce_tx.clone(),
+                    exclusions_for_watcher.clone(),
+                    watch_root_canonical.clone(),
+                )) {
+                    Ok(w) => Box::new(w),
+                    Err(e) => {
+                        eprintln!(
+                            "[文件监控-线程] 创建原生 watcher 失败，回退到 PollWatcher: {:?}",
+                            e
+                        );
+                        match build_poll_watcher(
+                            poll_interval,
+                            debounce_tx.clone(),
+                            exclusions_for_watcher.clone(),
+                            watch_root_canonical.clone(),
+                        ) {
+                            Ok(w) => Box::new(w),
+                            Err(poll_err) => {
+                                eprintln!("[文件监控-线程] 创建 PollWatcher 也失败: {:?}", poll_err);
+                                let _ = init_tx.send(Err(format!(
+                                    "Failed to create watcher (native: {:?}, poll: {:?})",
+                                    e, poll_err
+                                )));
+                                return;
+                            }
+                        }
+                    }
+                }
+            };
+
             match watcher.watch(watch_path, watch_mode) {
                 Ok(_) => {
                     println!(
@@ -197,6 +495,54 @@ impl DebouncedFileMonitor {
                     );
                     let _ = init_tx.send(Ok(()));
                 }
+                Err(e) if !force_poll => {
+                    // 原生 watcher 建好了但注册监控失败（比如目标路径在一个
+                    // 原生 watcher 不支持的文件系统上）——换成 PollWatcher 重试
+                    // 一次，而不是直接放弃整个目录的监控。
+                    eprintln!(
+                        "[文件监控-线程] 原生 watcher 注册监控失败，回退到 PollWatcher 重试: {:?}",
+                        e
+                    );
+                    match build_poll_watcher(
+                        poll_interval,
+                        debounce_tx.clone(),
+                        exclusions_for_watcher.clone(),
+                        watch_root_canonical.clone(),
+                    ) {
+                        Ok(poll_watcher) => {
+                            let mut poll_watcher: Box<dyn Watcher + Send> = Box::new(poll_watcher);
+                            match poll_watcher.watch(watch_path, watch_mode) {
+                                Ok(_) => {
+                                    println!(
+                                        "[文件监控-线程] ✅ PollWatcher 回退注册监控成功: {}",
+                                        dir_path_for_watcher
+                                    );
+                                    watcher = poll_watcher;
+                                    let _ = init_tx.send(Ok(()));
+                                }
+                                Err(poll_watch_err) => {
+                                    eprintln!(
+                                        "[文件监控-线程] ❌ PollWatcher 回退注册监控也失败: {:?}",
+                                        poll_watch_err
+                                    );
+                                    let _ = init_tx.send(Err(format!(
+                                        "Failed to watch (native: {:?}, poll: {:?})",
+                                        e, poll_watch_err
+                                    )));
+                                    return;
+                                }
+                            }
+                        }
+                        Err(poll_err) => {
+                            eprintln!("[文件监控-线程] ❌ 创建 PollWatcher 重试失败: {:?}", poll_err);
+                            let _ = init_tx.send(Err(format!(
+                                "Failed to watch (native: {:?}, poll creation: {:?})",
+                                e, poll_err
+                            )));
+                            return;
+                        }
+                    }
+                }
                 Err(e) => {
                     eprintln!("[文件监控-线程] ❌ 监控设置失败: {:?}", e);
                     let _ = init_tx.send(Err(format!("Failed to watch: {:?}", e)));
@@ -204,24 +550,21 @@ impl DebouncedFileMonitor {
                 }
             };
 
-            // 保持 watcher 活跃
-            println!("[文件监控-线程] 开始保持 watcher 活跃");
-            // let mut tick_count = 0;
-
-            loop {
-                // 让线程休眠10秒
-                std::thread::sleep(Duration::from_secs(10));
-                // tick_count += 1;
-                // println!("[文件监控-心跳] #{} Watcher for '{}' is still alive",
-                //         tick_count, &dir_path_for_watcher);
-
-                // 确保 watcher 保持活跃
-                let _ = &watcher;
-            }
+            // 保持 watcher 活跃，直到收到停止信号
+            println!("[文件监控-线程] 开始保持 watcher 活跃，等待停止信号");
+            let _ = stop_rx.recv();
+            println!(
+                "[文件监控-线程] 收到停止信号，解除监控: {}",
+                dir_path_for_watcher
+            );
+            should_stop_clone.store(true, Ordering::SeqCst);
+            // `watcher` 随这个线程退出而被 drop，底层监控随之解除。
+            drop(watcher);
         });
 
         // 启动防抖处理
         let tx_for_debounce = tx_to_central_handler.clone();
+        let app_handle_for_debounce = app_handle;
         tokio::spawn(async move {
             // 创建防抖缓冲区
             let mut debounce_buffer: HashMap<PathBuf, notify::EventKind> = HashMap::new();
@@ -248,6 +591,15 @@ impl DebouncedFileMonitor {
                             // 取出所有事件并处理
                             let events_to_process = std::mem::take(&mut debounce_buffer);
 
+                            // 给前端发一份结构化的批量变更事件（见 `SimpleFileEvent`），
+                            // 在转发给中央处理器之前先从快照算出来，不影响下面逐条转发
+                            // 的既有行为。
+                            emit_file_change_batch(
+                                &app_handle_for_debounce,
+                                &dir_path_clone,
+                                build_simple_events(&events_to_process),
+                            );
+
                             for (path, kind) in events_to_process {
                                 // 发送处理后的事件到中央处理器
                                 let tx_clone = tx_for_debounce.clone();
@@ -269,7 +621,13 @@ impl DebouncedFileMonitor {
                             // 处理剩余的缓冲区事件
                             if !debounce_buffer.is_empty() {
                                 println!("[防抖处理] 处理退出前的 {} 个缓冲事件", debounce_buffer.len());
-                                for (path, kind) in std::mem::take(&mut debounce_buffer) {
+                                let events_to_process = std::mem::take(&mut debounce_buffer);
+                                emit_file_change_batch(
+                                    &app_handle_for_debounce,
+                                    &dir_path_clone,
+                                    build_simple_events(&events_to_process),
+                                );
+                                for (path, kind) in events_to_process {
                                     if let Err(e) = tx_for_debounce.send((path.clone(), kind.clone())).await {
                                         eprintln!("[防抖处理] 退出前发送失败: {}", e);
                                     }
@@ -287,7 +645,7 @@ impl DebouncedFileMonitor {
         match init_rx.recv() {
             Ok(Ok(())) => {
                 println!("[防抖监控] ✅ 监控线程已成功启动");
-                Ok(())
+                Ok(stop_tx_for_caller)
             }
             Ok(Err(e)) => {
                 println!("[防抖监控] ❌ 监控线程启动失败: {}", e);
@@ -303,10 +661,12 @@ impl DebouncedFileMonitor {
         }
     }
 
-    /// 启动对多个目录的监控
+    /// 启动对多个目录的监控。每个目录附带一个可选的递归深度限制
+    /// （见 [`crate::file_monitor::MonitoredDirectory::max_depth`]），
+    /// `Some(depth)` 且 `depth <= 1` 时只监控该目录的直接子项，不递归进子目录。
     pub async fn start_monitoring(
         &mut self,
-        directories: Vec<String>,
+        directories: Vec<(String, Option<u32>)>,
         debounce_time: Duration,
     ) -> std::result::Result<(), String> {
         // 先清理所有现有通道和状态
@@ -320,24 +680,43 @@ impl DebouncedFileMonitor {
         // This Arc<FileMonitor> will be used by the central "防抖处理器" task
         let file_monitor_for_processing = Arc::clone(&self.file_monitor);
 
-        // 为每个目录创建停止通道接收器
-        let (stop_tx_sender, stop_tx_receiver) = std_mpsc::channel();
-
-        // 启动各个目录的监控
-        for dir_path_str in directories {
-            if let Err(e) = Self::setup_single_debounced_watch(
+        // 读取一次 watch 后端配置，应用到本次启动的所有目录（见
+        // `set_poll_watch_config`）。
+        let force_poll = *self.force_poll.lock().unwrap();
+        let poll_interval = *self.poll_interval.lock().unwrap();
+
+        // 记住这次的防抖时长，供之后 `add_directory` 新增目录时复用，保持
+        // 和其它目录一致的防抖行为。
+        *self.current_debounce_time.lock().unwrap() = Some(debounce_time);
+
+        // 启动各个目录的监控，每个目录自己的停止发送器直接登记进
+        // `self.watch_stop_channels`（键是规范化路径，规范化失败就退化为
+        // 原始字符串），不再经过之前那个会把收集结果丢掉的中间
+        // `tokio::spawn` 收集器。
+        for (dir_path_str, max_depth) in directories {
+            match Self::setup_single_debounced_watch(
                 dir_path_str.clone(), // Pass owned string
+                max_depth,
                 debounce_time,
                 event_tx_for_central_handler.clone(),
-                Some(stop_tx_sender.clone()), // 传递停止通道发送器
+                self.exclusions.clone(),
+                force_poll,
+                poll_interval,
+                self.app_handle.clone(),
             )
             .await
             {
-                eprintln!(
-                    "[防抖监控] Failed to setup watch for directory {}: {}",
-                    dir_path_str, e
-                );
-                // Optionally, decide if one failure should stop all, or just log and continue
+                Ok(stop_tx) => {
+                    let key = canonical_watch_key(&dir_path_str);
+                    self.watch_stop_channels.lock().await.insert(key, stop_tx);
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[防抖监控] Failed to setup watch for directory {}: {}",
+                        dir_path_str, e
+                    );
+                    // Optionally, decide if one failure should stop all, or just log and continue
+                }
             }
         }
 
@@ -376,8 +755,10 @@ impl DebouncedFileMonitor {
                         EventKind::Remove(RemoveKind::File)
                     }
                     _ => {
-                        // 对于任何其他事件类型，检查文件是否存在
-                        if path.exists() && path.is_file() {
+                        // 对于任何其他事件类型，用一次 stat 同时判断存在性（见
+                        // file_id_tracker 模块说明），而不是 `.exists() && .is_file()`
+                        // 两次独立系统调用。
+                        if crate::file_id_tracker::file_id(&path).is_some() {
                             println!("[防抖处理器] 其他事件类型，文件存在，处理为: 文件新增");
                             EventKind::Create(CreateKind::File)
                         } else {
@@ -503,26 +884,73 @@ impl DebouncedFileMonitor {
             println!("[防抖处理器] 事件处理通道已关闭，退出");
         });
 
-        // 收集所有目录的停止通道
-        tokio::spawn(async move {
-            let mut watch_stop_channels = HashMap::new();
+        Ok(())
+    }
 
-            // 接收所有注册的停止通道
-            while let Ok(stop_tx) = stop_tx_receiver.recv() {
-                let dir_id = format!("watch_{}", watch_stop_channels.len() + 1);
-                println!("[防抖监控] 收到停止通道 #{}", dir_id);
-                watch_stop_channels.insert(dir_id, stop_tx);
-            }
+    /// 动态新增一个目录到监控，不影响其它已经在跑的目录。如果这个路径
+    /// （规范化后）已经在监控中，先停掉旧的监控线程再建新的，避免旧线程
+    /// 泄漏。`start_monitoring` 必须先调用过一次——否则既没有中央事件
+    /// 处理器可以接收事件，也不知道该用哪个防抖时长，这种情况下直接报错，
+    /// 而不是静默地自己拼一套默认值出来。
+    pub async fn add_directory(&self, dir: String, max_depth: Option<u32>) -> std::result::Result<(), String> {
+        let Some(event_tx) = self.event_tx.clone() else {
+            return Err("监控尚未启动，无法动态新增目录，请先调用 start_monitoring".to_string());
+        };
+        let Some(debounce_time) = *self.current_debounce_time.lock().unwrap() else {
+            return Err("未找到上次启动监控时使用的防抖时长".to_string());
+        };
 
-            println!(
-                "[防抖监控] 停止通道收集器已退出，共收集 {} 个停止通道",
-                watch_stop_channels.len()
-            );
-        });
+        let key = canonical_watch_key(&dir);
+
+        // 如果这个目录已经在监控中，先停掉旧的监控线程，避免重复监控同一
+        // 目录导致两条线程都往中央处理器发事件。
+        if let Some(old_stop_tx) = self.watch_stop_channels.lock().await.remove(&key) {
+            println!("[防抖监控] 目录 '{}' 已在监控中，先停止旧的监控线程", dir);
+            let _ = old_stop_tx.send(());
+        }
 
+        let force_poll = *self.force_poll.lock().unwrap();
+        let poll_interval = *self.poll_interval.lock().unwrap();
+
+        let stop_tx = Self::setup_single_debounced_watch(
+            dir.clone(),
+            max_depth,
+            debounce_time,
+            event_tx,
+            self.exclusions.clone(),
+            force_poll,
+            poll_interval,
+            self.app_handle.clone(),
+        )
+        .await?;
+
+        self.watch_stop_channels.lock().await.insert(key, stop_tx);
+        println!("[防抖监控] ✅ 已动态新增目录监控: {}", dir);
         Ok(())
     }
 
+    /// 动态移除一个目录的监控，不影响其它目录。如果这个目录本来就不在
+    /// 监控中，视为成功（目标状态已经达成，和 `stop_monitoring` 对空
+    /// `watch_stop_channels` 的容忍态度一致）。
+    pub async fn remove_directory(&self, dir: &str) -> std::result::Result<(), String> {
+        let key = canonical_watch_key(dir);
+        match self.watch_stop_channels.lock().await.remove(&key) {
+            Some(stop_tx) => {
+                if let Err(e) = stop_tx.send(()) {
+                    let msg = format!("[防抖监控] 无法发送停止信号到 '{}' 的监控线程: {:?}", dir, e);
+                    println!("{}", msg);
+                    return Err(msg);
+                }
+                println!("[防抖监控] ✅ 已停止目录监控: {}", dir);
+                Ok(())
+            }
+            None => {
+                println!("[防抖监控] 目录 '{}' 本来就不在监控中，无需移除", dir);
+                Ok(())
+            }
+        }
+    }
+
     /// 完全停止所有目录的监控
     ///
     /// 这个方法会:
@@ -597,10 +1025,10 @@ impl DebouncedFileMonitor {
             // 继续执行，尝试重新启动
         }
 
-        // 2. 获取最新的监控目录
+        // 2. 获取最新的监控目录（附带各自的递归深度限制）
         let directories_to_monitor = {
             let monitor = &self.file_monitor;
-            monitor.get_monitored_dirs()
+            monitor.get_monitored_dirs_with_depth()
         };
 
         // 3. 重新启动监控
@@ -18,6 +18,8 @@ use chrono::{
 use serde::{Deserialize, Serialize};
 // use std::collections::HashSet;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{command, AppHandle, Emitter, Manager, State}; // 添加Emitter trait
 use walkdir::WalkDir;
@@ -72,7 +74,7 @@ fn get_file_extension(file_path: &Path) -> Option<String> {
 }
 
 // 检查文件是否隐藏
-fn is_hidden_file(path: &Path) -> bool {
+pub(crate) fn is_hidden_file(path: &Path) -> bool {
     // 先检查文件/目录名本身是否以.开头
     let is_name_hidden = path
         .file_name()
@@ -98,7 +100,7 @@ fn is_hidden_file(path: &Path) -> bool {
 }
 
 // 检查是否为macOS bundle文件夹
-fn is_macos_bundle_folder(path: &Path) -> bool {
+pub(crate) fn is_macos_bundle_folder(path: &Path) -> bool {
     // 首先处理可能为null的情况
     if path.as_os_str().is_empty() {
         return false;
@@ -169,15 +171,15 @@ fn is_inside_macos_bundle(path: &Path) -> Option<PathBuf> {
             ".tvlibrary/",
             ".theater/",
         ];
+        // 和 is_macos_bundle_folder/has_bundle_extension 一样统一转小写再比较，
+        // 避免在大小写不敏感的文件系统上 `.APP/`/`.App/` 之类的写法被漏判。
+        // 用小写后的字符串去定位偏移量，再切原始 path_str，保留用户原本的大小写。
+        let lower_path_str = path_str.to_lowercase();
         for ext in bundle_extensions.iter() {
-            if path_str.contains(ext) {
+            if let Some(bundle_end_idx) = lower_path_str.find(ext) {
                 // 找到包含该扩展名的部分，并构建bundle路径
-                if let Some(bundle_end_idx) = path_str.find(ext) {
-                    let bundle_path_str = &path_str[..bundle_end_idx + ext.len() - 1]; // -1 是为了去掉末尾的斜杠
-                    return Some(PathBuf::from(bundle_path_str));
-                }
-                // 如果无法解析路径，至少返回true的等价物
-                return Some(path.to_path_buf());
+                let bundle_path_str = &path_str[..bundle_end_idx + ext.len() - 1]; // -1 是为了去掉末尾的斜杠
+                return Some(PathBuf::from(bundle_path_str));
             }
         }
     }
@@ -236,13 +238,44 @@ fn find_containing_bundle(path: &Path, bundle_extensions: &[String]) -> Option<P
     bundle_path
 }
 
-#[derive(Debug, Default)]
-struct ScanStats {
-    total_discovered: u64,   // 发现的所有文件数
-    hidden_filtered: u64,    // 被过滤的隐藏文件数
-    extension_filtered: u64, // 被扩展名过滤的文件数
-    bundle_filtered: u64,    // 被过滤的bundle文件数
-    total_included: u64,     // 最终包含的文件数
+// 扫描过程中发给前端的进度事件，`scan_progress` 大致每处理
+// PROGRESS_EMIT_INTERVAL 个候选文件广播一次，驱动一个实时进度条。
+#[derive(Debug, Clone, Serialize)]
+struct ScanProgress {
+    files_checked: u64,
+    files_included: u64,
+    current_dir: String,
+}
+
+// 每处理这么多个候选文件广播一次 `scan_progress`，避免百万级文件量时
+// 每个文件都 emit 一次把事件通道打爆。
+const PROGRESS_EMIT_INTERVAL: u64 = 200;
+
+// 分页扫描（见 `scan_files_simplified`）跨调用返回给前端，所以需要能序列化；
+// 字段含义和之前完全一样，只是现在是累计值而不是单次调用的统计。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanStats {
+    total_discovered: u64,       // 发现的所有文件数
+    hidden_filtered: u64,        // 被过滤的隐藏文件数
+    extension_filtered: u64,     // 被扩展名过滤的文件数
+    bundle_filtered: u64,        // 被过滤的bundle文件数
+    total_included: u64,         // 最终包含的文件数
+    cache_hits: u64,             // 命中持久化扫描缓存、跳过重新处理的文件数
+    symlink_cycles_skipped: u64, // 开启 follow_symlinks 时，因为指回已访问目录而被剪掉的符号链接数
+    ignore_pattern_filtered: u64, // 被 .gitignore/.ignore 或 ignore_patterns 自定义排除规则剪掉的条目数
+    blacklist_pruned: u64, // 在 filter_entry 里命中黑名单、整棵子树被剪掉（而不是展开后逐条跳过）的目录数
+}
+
+// 简化扫描的分页游标：`folder_index` 之前的监控文件夹（按路径排序）已经
+// 翻完，直接跳过；`folder_index` 对应的文件夹里，跳到 `last_path` 之后
+// （文件在文件夹内部也按路径排序），再额外跳过 `remaining_skip` 条——正常
+// 情况下 `file_path` 本身就能唯一定位，`remaining_skip` 只在理论上出现
+// 路径并列（排序键相同）时才用得上，默认 0。`None` 表示已经翻到最后一页。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanCursor {
+    pub folder_index: usize,
+    pub last_path: Option<String>,
+    pub remaining_skip: usize,
 }
 
 // 根据文件类型枚举获取对应的分类ID列表
@@ -256,30 +289,450 @@ fn get_category_ids_for_file_type(file_type: &FileType) -> Vec<i32> {
     }
 }
 
-// 根据扩展名和文件类型检查文件是否匹配
-fn is_file_of_type(
-    extension: &Option<String>,
-    file_type: &FileType,
+// 反查：分类ID属于哪个FileType大类（get_category_ids_for_file_type的逆映射），
+// 用于判断"按扩展名得到的分类"和"内容嗅探得到的分类"是否一致
+fn file_type_family_for_category(category_id: i32) -> Option<FileType> {
+    match category_id {
+        1 => Some(FileType::Document),
+        2 => Some(FileType::Image),
+        3 => Some(FileType::AudioVideo),
+        4 => Some(FileType::Archive),
+        _ => None,
+    }
+}
+
+// 读取文件前缀字节并做内容嗅探（见 magic_bytes 模块），失败（文件打不开/
+// 读不到数据/没有任何签名匹配）时返回 None，调用方回退到按扩展名分类
+fn sniff_file_prefix(path: &Path) -> Option<crate::magic_bytes::SniffedType> {
+    use std::io::Read;
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut buf = vec![0u8; crate::magic_bytes::SNIFF_PREFIX_LEN];
+    let n = file.read(&mut buf).ok()?;
+    buf.truncate(n);
+    crate::magic_bytes::sniff(&buf)
+}
+
+// 可组合的扫描过滤器：process_candidate_file 和 process_simplified_candidate
+// 原来各自手写一串几乎一样的时间范围/文件类型判断，这里拆成实现了
+// `ScanFilter` 的独立类型，按顺序串成一条过滤链（`run_filter_chain`）。新增
+// 一种过滤条件（比如大小范围）只需要再写一个实现、插进链里，不用去改调用方
+// 的核心循环。
+//
+// 扩展名/分类判定没有拆进这条链：两个函数的扩展名数据结构完全不同
+// （`process_candidate_file` 是"白名单 + 内容嗅探优先级"两步走，基于
+// `FileExtensionMapRust` 列表；`process_simplified_candidate` 是纯
+// `HashMap<String, i32>` 查表），勉强拆成同一个类型只会让两边都变得难读，
+// 所以各自保留原有逻辑，只是把算出来的结果写进下面的 `FilterContext` 供链上
+// 后续过滤器复用。
+
+// 一条过滤规则判定的结果：放行就交给链上下一个过滤器，拒绝则指明应该累加到
+// `ScanStats`（或 SimplifiedOutcome/CandidateOutcome）的哪个桶。
+enum FilterOutcome {
+    Accepted,
+    Rejected(RejectBucket),
+}
+
+// 过滤器拒绝候选条目时对应的统计桶；`Silent` 对应原来"悄悄 continue、不计入
+// 任何统计量"的那些情况（两个函数里都有的时间范围不匹配、元数据读取失败）。
+#[derive(Clone, Copy)]
+enum RejectBucket {
+    Hidden,
+    Bundle,
+    Extension,
+    Silent,
+}
+
+// 过滤器之间传递的中间状态：Bundle/扩展名判定解析出的扩展名和分类ID，交给
+// 链上后面的 TimeRangeFilter/FileTypeFilter 复用，不用重新算一遍。
+#[derive(Default)]
+struct FilterContext {
+    extension: Option<String>,
+    category_id: Option<i32>,
+}
+
+trait ScanFilter {
+    fn accept(
+        &self,
+        path: &Path,
+        meta: &std::fs::Metadata,
+        ctx: &mut FilterContext,
+    ) -> FilterOutcome;
+}
+
+// 隐藏文件过滤，复用 `is_hidden_file`；两个扫描函数都把这一步放在链的最前面。
+struct HiddenFilter;
+
+impl ScanFilter for HiddenFilter {
+    fn accept(
+        &self,
+        path: &Path,
+        _meta: &std::fs::Metadata,
+        _ctx: &mut FilterContext,
+    ) -> FilterOutcome {
+        if is_hidden_file(path) {
+            FilterOutcome::Rejected(RejectBucket::Hidden)
+        } else {
+            FilterOutcome::Accepted
+        }
+    }
+}
+
+// 普通文件的扩展名→分类过滤：纯 `HashMap` 查表，查不到且开启了
+// `sniff_content` 时退回内容嗅探；两条路都没有结果就拒绝。命中的扩展名/
+// 分类写进 `ctx`。只适用于 `process_simplified_candidate` 那种扁平映射表的
+// 场景，`process_candidate_file` 的白名单+嗅探优先级逻辑结构不同，没有复用
+// 这个类型（见上方大段说明）。
+struct ExtensionFilter<'a> {
+    extension_mappings: &'a std::collections::HashMap<String, i32>,
+    sniff_content: bool,
+}
+
+impl<'a> ScanFilter for ExtensionFilter<'a> {
+    fn accept(
+        &self,
+        path: &Path,
+        _meta: &std::fs::Metadata,
+        ctx: &mut FilterContext,
+    ) -> FilterOutcome {
+        let mut extension = get_file_extension(path);
+        let mapped_category_id = extension
+            .as_ref()
+            .and_then(|ext| self.extension_mappings.get(ext).copied());
+
+        let category_id = match mapped_category_id {
+            Some(cat_id) => cat_id,
+            None if self.sniff_content => match sniff_file_prefix(path) {
+                Some(sniffed) => match get_category_ids_for_file_type(&sniffed.file_type)
+                    .first()
+                    .copied()
+                {
+                    Some(cat_id) => {
+                        extension = Some(sniffed.extension.to_string());
+                        cat_id
+                    }
+                    None => return FilterOutcome::Rejected(RejectBucket::Extension),
+                },
+                None => return FilterOutcome::Rejected(RejectBucket::Extension),
+            },
+            None => return FilterOutcome::Rejected(RejectBucket::Extension),
+        };
+
+        ctx.extension = extension;
+        ctx.category_id = Some(category_id);
+        FilterOutcome::Accepted
+    }
+}
+
+// 把 Bundle 当成一个整体文件：扩展名必须在 `extension_mappings` 里有对应
+// 分类，否则拒绝；命中的话把扩展名/分类ID写进 `ctx`。只应该在调用方已经
+// 确认候选路径是 Bundle 根目录时才加进链里，这里不重复判断 is_macos_bundle。
+struct BundleFilter<'a> {
+    extension_mappings: &'a std::collections::HashMap<String, i32>,
+}
+
+impl<'a> ScanFilter for BundleFilter<'a> {
+    fn accept(
+        &self,
+        path: &Path,
+        _meta: &std::fs::Metadata,
+        ctx: &mut FilterContext,
+    ) -> FilterOutcome {
+        let Some(ext) = get_file_extension(path) else {
+            return FilterOutcome::Rejected(RejectBucket::Bundle);
+        };
+        let Some(&category_id) = self.extension_mappings.get(&ext) else {
+            return FilterOutcome::Rejected(RejectBucket::Bundle);
+        };
+        ctx.extension = Some(ext);
+        ctx.category_id = Some(category_id);
+        FilterOutcome::Accepted
+    }
+}
+
+// 按修改时间过滤，复用 `is_file_in_time_range`；没配置时间范围就全部放行。
+// 读取元数据里的修改时间失败时走 `read_failure_bucket`（两个调用方对这种
+// 失败计入的统计桶不一样：process_candidate_file 悄悄跳过，
+// process_simplified_candidate 的普通文件分支计入扩展名过滤桶），时间范围
+// 本身不匹配则总是 `Silent`，和原来两边的行为都一致。
+struct TimeRangeFilter<'a> {
+    time_range: &'a Option<TimeRange>,
+    read_failure_bucket: RejectBucket,
+}
+
+impl<'a> ScanFilter for TimeRangeFilter<'a> {
+    fn accept(
+        &self,
+        _path: &Path,
+        meta: &std::fs::Metadata,
+        _ctx: &mut FilterContext,
+    ) -> FilterOutcome {
+        let Ok(modified_time) = meta.modified() else {
+            return FilterOutcome::Rejected(self.read_failure_bucket);
+        };
+        let Some(tr) = self.time_range else {
+            return FilterOutcome::Accepted;
+        };
+        let Ok(modified_time_secs) = modified_time.duration_since(UNIX_EPOCH).map(|d| d.as_secs())
+        else {
+            return FilterOutcome::Rejected(self.read_failure_bucket);
+        };
+        if is_file_in_time_range(modified_time_secs, tr) {
+            FilterOutcome::Accepted
+        } else {
+            FilterOutcome::Rejected(RejectBucket::Silent)
+        }
+    }
+}
+
+// 按文件类型过滤，要求 `ctx.category_id` 已经被链上前面的 Bundle/扩展名过滤
+// 器填好。拒绝时用哪个桶由调用方通过 `reject_bucket` 指定（两边原来对类型
+// 不匹配的计数方式不一样：process_candidate_file 悄悄跳过，
+// process_simplified_candidate 计入扩展名过滤桶）。
+struct FileTypeFilter<'a> {
+    file_type: &'a Option<FileType>,
+    reject_bucket: RejectBucket,
+}
+
+impl<'a> ScanFilter for FileTypeFilter<'a> {
+    fn accept(
+        &self,
+        _path: &Path,
+        _meta: &std::fs::Metadata,
+        ctx: &mut FilterContext,
+    ) -> FilterOutcome {
+        let Some(ft) = self.file_type else {
+            return FilterOutcome::Accepted;
+        };
+        if *ft == FileType::All {
+            return FilterOutcome::Accepted;
+        }
+        let target_category_ids = get_category_ids_for_file_type(ft);
+        if target_category_ids.is_empty() {
+            return FilterOutcome::Accepted;
+        }
+        match ctx.category_id {
+            Some(cat_id) if target_category_ids.contains(&cat_id) => FilterOutcome::Accepted,
+            _ => FilterOutcome::Rejected(self.reject_bucket),
+        }
+    }
+}
+
+// 按顺序跑一条过滤链，遇到第一个拒绝就短路返回该拒绝所在的桶；全部放行则
+// 返回 `None`。
+fn run_filter_chain(
+    filters: &[Box<dyn ScanFilter + '_>],
+    path: &Path,
+    meta: &std::fs::Metadata,
+    ctx: &mut FilterContext,
+) -> Option<RejectBucket> {
+    for filter in filters {
+        if let FilterOutcome::Rejected(bucket) = filter.accept(path, meta, ctx) {
+            return Some(bucket);
+        }
+    }
+    None
+}
+
+// 单个候选文件在并行处理阶段的结果：要么被收录（连同算好的 FileInfo），
+// 要么被扩展名白名单过滤掉（计入 ScanStats::extension_filtered），要么被
+// 其它过滤条件（时间范围/文件类型/读取失败）悄悄跳过，不计入任何统计量
+// ——和原来单线程版本的行为保持一致。
+enum CandidateOutcome {
+    Included(FileInfo),
+    ExtensionFiltered,
+    Skipped,
+}
+
+// 候选文件真正耗时的处理：缓存探测、扩展名/内容嗅探分类、时间范围/文件
+// 类型过滤、元数据读取。从 `scan_files_with_filter` 的并行阶段按文件并发
+// 调用，只通过参数和几个共享的原子量/互斥量跟外层通信，不依赖全局状态，
+// 可以安全地在多个线程里同时跑。
+fn process_candidate_file(
+    file_path: &Path,
     extension_maps: &[FileExtensionMapRust],
-) -> bool {
-    if *file_type == FileType::All {
-        return true; // No filtering by type if FileType is All
+    valid_extensions: &std::collections::HashSet<String>,
+    sniff_content: bool,
+    follow_symlinks: bool,
+    file_type: &Option<FileType>,
+    time_range: &Option<TimeRange>,
+    cache: &Mutex<Option<crate::scan_cache::ScanCache>>,
+    cache_hits: &AtomicU64,
+) -> CandidateOutcome {
+    // 缓存探测：size/mtime 都没变就直接复用上次算出的 FileInfo，跳过下面
+    // 扩展名/内容嗅探/分类这些开销较大的步骤。时间范围/文件类型过滤器仍然
+    // 要对缓存里的数据重新判断一遍，因为这两个过滤条件是按本次调用传入
+    // 的，和文件本身是否变化无关。
+    let cache_probe = std::fs::metadata(file_path).ok().and_then(|meta| {
+        let mtime_secs = meta.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let path_str = file_path.to_string_lossy().into_owned();
+        let cached = cache.lock().unwrap().as_mut()?.get(&path_str, meta.len(), mtime_secs)?;
+        Some((cached, mtime_secs))
+    });
+
+    if let Some((cached_info, mtime_secs)) = cache_probe {
+        cache_hits.fetch_add(1, Ordering::Relaxed);
+        let passes_time_range = time_range
+            .as_ref()
+            .map_or(true, |tr| is_file_in_time_range(mtime_secs, tr));
+        let passes_file_type = file_type.as_ref().map_or(true, |ft| match cached_info.category_id {
+            Some(cat_id) => *ft == FileType::All || get_category_ids_for_file_type(ft).contains(&cat_id),
+            None => *ft == FileType::All,
+        });
+
+        return if passes_time_range && passes_file_type {
+            CandidateOutcome::Included(cached_info)
+        } else {
+            CandidateOutcome::Skipped
+        };
     }
 
-    if let Some(ext) = extension {
-        let ext = ext.to_lowercase();
-        let target_category_ids = get_category_ids_for_file_type(file_type);
+    let mut extension = get_file_extension(file_path);
+    // 启用 sniff_content 时，如果按扩展名得到的分类家族和内容嗅探结果不
+    // 一致（或干脆没有扩展名），内容嗅探结果优先；`sniffed_category_id`
+    // 非 None 表示后面应该信任这个值而不是再去查 `valid_extensions`。
+    let mut sniffed_category_id: Option<i32> = None;
 
-        // 检查文件扩展名是否在扩展名映射列表中
-        // 只有扩展名在列表中且关联到指定分类ID的文件才会被返回
-        let matches = extension_maps.iter().any(|map| {
-            map.extension.to_lowercase() == ext && target_category_ids.contains(&map.category_id)
+    if sniff_content {
+        let declared_family = extension.as_ref().and_then(|ext| {
+            extension_maps
+                .iter()
+                .find(|map| map.extension.to_lowercase() == *ext)
+                .and_then(|map| file_type_family_for_category(map.category_id))
         });
 
-        return matches;
+        if extension.is_none() || declared_family.is_none() {
+            if let Some(sniffed) = sniff_file_prefix(file_path) {
+                if declared_family.as_ref() != Some(&sniffed.file_type) {
+                    println!(
+                        "[SCAN] 内容嗅探覆盖扩展名分类: {} -> {} ({:?})",
+                        file_path.display(),
+                        sniffed.extension,
+                        sniffed.file_type
+                    );
+                    extension = Some(sniffed.extension.to_string());
+                    sniffed_category_id = get_category_ids_for_file_type(&sniffed.file_type)
+                        .first()
+                        .copied();
+                }
+            }
+        }
+    }
+
+    // 白名单扩展名过滤：只处理有扩展名且扩展名在配置白名单中的文件
+    // （内容嗅探已经给出明确分类的文件跳过这一步，直接信任嗅探结果）
+    if sniffed_category_id.is_none() {
+        if let Some(ref ext) = extension {
+            let ext_lower = ext.to_lowercase();
+            if !valid_extensions.contains(&ext_lower) {
+                println!(
+                    "[SCAN] 跳过非白名单扩展名文件: {} (扩展名: {})",
+                    file_path.display(),
+                    ext_lower
+                );
+                return CandidateOutcome::ExtensionFiltered;
+            }
+        } else if *file_type != Some(FileType::All) {
+            println!("[SCAN] 跳过无扩展名文件: {}", file_path.display());
+            return CandidateOutcome::ExtensionFiltered;
+        }
+    }
+
+    // 内容嗅探给出的分类优先，否则按扩展名匹配分类ID；提前算出来喂给下面
+    // 共享的 FileTypeFilter，后面构造 FileInfo 时也是复用这同一个值。
+    let category_id = sniffed_category_id.or_else(|| {
+        extension.as_ref().and_then(|ext| {
+            extension_maps
+                .iter()
+                .find(|map| map.extension.to_lowercase() == ext.to_lowercase())
+                .map(|map| map.category_id)
+        })
+    });
+
+    // 获取文件元数据
+    let metadata = match std::fs::metadata(file_path) {
+        Ok(meta) => meta,
+        Err(e) => {
+            println!(
+                "[SCAN] 无法获取文件元数据: {} (错误: {})",
+                file_path.display(),
+                e
+            );
+            return CandidateOutcome::Skipped;
+        }
+    };
+
+    // 应用文件类型/时间范围过滤器：和 process_simplified_candidate 共用同一套
+    // `ScanFilter` 实现（见文件开头的说明），两边原来对"元数据读取失败"和
+    // "类型不匹配"计的统计桶不一样，所以两边各自传入不同的 `reject_bucket`/
+    // `read_failure_bucket`——这里都悄悄跳过，不计入任何统计量。
+    let mut ctx = FilterContext {
+        extension: extension.clone(),
+        category_id,
+    };
+    let filters: Vec<Box<dyn ScanFilter>> = vec![
+        Box::new(FileTypeFilter {
+            file_type,
+            reject_bucket: RejectBucket::Silent,
+        }),
+        Box::new(TimeRangeFilter {
+            time_range,
+            read_failure_bucket: RejectBucket::Silent,
+        }),
+    ];
+    if run_filter_chain(&filters, file_path, &metadata, &mut ctx).is_some() {
+        return CandidateOutcome::Skipped;
+    }
+
+    let modified_time = metadata
+        .modified()
+        .expect("TimeRangeFilter 已经验证过修改时间可读");
+    let created_time = metadata
+        .created()
+        .ok()
+        .map(system_time_to_iso_string);
+    let file_size = metadata.len();
+    let file_name = file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    // 开启符号链接跟随时，上报文件的真实（已解析）路径而不是遍历时走过的
+    // 符号链接路径，这样指向所有监控文件夹之外的链接目标也能被正确定位。
+    // 解析失败（比如链接目标已经消失）时退回原始路径，不让扫描中断。
+    let stored_path = if follow_symlinks {
+        file_path
+            .canonicalize()
+            .unwrap_or_else(|_| file_path.to_path_buf())
     } else {
-        false
+        file_path.to_path_buf()
+    };
+
+    let file_info = FileInfo {
+        file_path: stored_path.to_string_lossy().into_owned(),
+        file_name,
+        file_size,
+        extension,
+        created_time,
+        modified_time: system_time_to_iso_string(modified_time),
+        category_id,
+    };
+
+    // 写入缓存供下次扫描复用
+    if let Some(cache) = cache.lock().unwrap().as_mut() {
+        let modified_time_secs = modified_time
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        cache.update(
+            file_info.file_path.clone(),
+            file_size,
+            modified_time_secs,
+            file_info.clone(),
+        );
     }
+
+    CandidateOutcome::Included(file_info)
 }
 
 // 检查文件是否在指定的时间范围内
@@ -342,16 +795,28 @@ fn system_time_to_iso_string(system_time: SystemTime) -> String {
 // Tauri命令：扫描指定时间范围内的文件
 #[command]
 pub async fn scan_files_by_time_range(
-    _app_handle: AppHandle,
+    app_handle: AppHandle,
     time_range: TimeRange,
     app_state: State<'_, AppState>, // Access AppState
 ) -> Result<Vec<FileInfo>, String> {
     println!("调用 scan_files_by_time_range: {:?}", time_range);
 
     let config = app_state.get_config().await?; // Use the AppState to get config
+    let ignore_enabled_for = app_state.ignore_files_enabled_paths();
+    let path_filters = app_state.path_filters_snapshot();
 
     println!("开始扫描文件...");
-    let result = scan_files_with_filter(&config, Some(time_range), None).await;
+    let result = scan_files_with_filter(
+        &app_handle,
+        &config,
+        Some(time_range),
+        None,
+        &ignore_enabled_for,
+        &path_filters,
+        false,
+        false, // follow_symlinks：默认关闭，避免符号链接环路
+    )
+    .await;
     println!(
         "扫描完成, 文件数量: {}",
         result.as_ref().map_or(0, |files| files.len())
@@ -362,16 +827,36 @@ pub async fn scan_files_by_time_range(
 // Tauri命令：扫描特定类型的文件
 #[command]
 pub async fn scan_files_by_type(
-    _app_handle: AppHandle,
+    app_handle: AppHandle,
     file_type: FileType,
+    // 是否开启内容嗅探（见 magic_bytes 模块）：更准确但更慢，默认关闭
+    sniff_content: Option<bool>,
+    // 是否跟随符号链接：默认关闭，避免自引用链接造成的无限遍历；开启后
+    // 靠已访问真实路径集合防护环路（见 ScanStats::symlink_cycles_skipped）
+    follow_symlinks: Option<bool>,
     app_state: State<'_, AppState>, // Access AppState
 ) -> Result<Vec<FileInfo>, String> {
-    println!("调用 scan_files_by_type: {:?}", file_type);
+    println!(
+        "调用 scan_files_by_type: {:?}, 内容嗅探: {:?}, 跟随符号链接: {:?}",
+        file_type, sniff_content, follow_symlinks
+    );
 
     let config = app_state.get_config().await?; // Use the AppState to get config
+    let ignore_enabled_for = app_state.ignore_files_enabled_paths();
+    let path_filters = app_state.path_filters_snapshot();
 
     println!("开始扫描文件...");
-    let result = scan_files_with_filter(&config, None, Some(file_type)).await;
+    let result = scan_files_with_filter(
+        &app_handle,
+        &config,
+        None,
+        Some(file_type),
+        &ignore_enabled_for,
+        &path_filters,
+        sniff_content.unwrap_or(false),
+        follow_symlinks.unwrap_or(false),
+    )
+    .await;
     println!(
         "扫描完成, 文件数量: {}",
         result.as_ref().map_or(0, |files| files.len())
@@ -385,11 +870,13 @@ pub async fn scan_files_simplified_command(
     _app_handle: AppHandle,
     time_range: Option<TimeRange>,
     file_type: Option<FileType>,
+    // 是否开启内容嗅探（见 magic_bytes 模块）：更准确但更慢，默认关闭
+    sniff_content: Option<bool>,
     app_state: State<'_, AppState>,
 ) -> Result<Vec<FileInfo>, String> {
     println!(
-        "[SIMPLIFIED_SCAN] 调用简化扫描: 时间范围={:?}, 文件类型={:?}",
-        time_range, file_type
+        "[SIMPLIFIED_SCAN] 调用简化扫描: 时间范围={:?}, 文件类型={:?}, 内容嗅探={:?}",
+        time_range, file_type, sniff_content
     );
 
     // 获取简化配置
@@ -403,8 +890,14 @@ pub async fn scan_files_simplified_command(
         "[SIMPLIFIED_SCAN] 开始简化扫描，监控文件夹数: {}",
         monitored_folders.len()
     );
-    let result =
-        scan_files_simplified(&simplified_config, monitored_folders, time_range, file_type).await;
+    let result = scan_files_simplified_all(
+        &simplified_config,
+        monitored_folders,
+        time_range,
+        file_type,
+        sniff_content.unwrap_or(false),
+    )
+    .await;
 
     match &result {
         Ok(files) => println!("[SIMPLIFIED_SCAN] 扫描完成，文件数量: {}", files.len()),
@@ -414,6 +907,243 @@ pub async fn scan_files_simplified_command(
     result
 }
 
+// Tauri命令：分页版简化扫描，给前端文件浏览面板那种"滚动到底再加载下一页"
+// 的交互用。第一页传 `cursor: None`、`prior_stats: None`；后续每页把上一次
+// 返回的 `next_cursor`/`stats` 原样传回来，这样总计数才能跨页累加而不是
+// 每页各自归零。`next_cursor` 是 `None` 就表示已经翻到最后一页。
+#[command]
+pub async fn scan_files_simplified_page_command(
+    _app_handle: AppHandle,
+    time_range: Option<TimeRange>,
+    file_type: Option<FileType>,
+    sniff_content: Option<bool>,
+    cursor: Option<ScanCursor>,
+    page_size: usize,
+    prior_stats: Option<ScanStats>,
+    app_state: State<'_, AppState>,
+) -> Result<(Vec<FileInfo>, Option<ScanCursor>, ScanStats), String> {
+    println!(
+        "[SIMPLIFIED_SCAN] 调用分页简化扫描: 时间范围={:?}, 文件类型={:?}, 游标={:?}, page_size={}",
+        time_range, file_type, cursor, page_size
+    );
+
+    let simplified_config = app_state.get_simplified_config().await?;
+    let config = app_state.get_config().await?;
+    let monitored_folders = &config.monitored_folders;
+
+    scan_files_simplified(
+        &simplified_config,
+        monitored_folders,
+        time_range,
+        file_type,
+        sniff_content.unwrap_or(false),
+        cursor.unwrap_or_default(),
+        page_size,
+        prior_stats.unwrap_or_default(),
+    )
+    .await
+}
+
+// 重复文件检测：一组内容完全相同的文件，`content_hash` 是分组键，`file_size`
+// 是方便前端展示的冗余字段（组内每个文件大小都一样）
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateGroup {
+    pub content_hash: String,
+    pub file_size: u64,
+    pub files: Vec<FileInfo>,
+}
+
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct DuplicateStats {
+    pub group_count: usize,
+    pub wasted_space: u64, // 每组内 (文件数-1)*file_size 之和：删到只剩一份能省出的空间
+}
+
+// 流式哈希用的固定缓冲区大小，避免大文件被整个读进内存
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+// 对文件全部内容算 SHA-256（哈希方案沿用 file_monitor.rs::calculate_simple_hash
+// 里已经在用的 sha2::Sha256；区别是那边只读前4KB做"简单指纹"，这里要对全文件
+// 内容分块喂给 hasher，避免像 calculate_simple_hash 那样一次性读整个文件到内存）。
+// 先把文件长度喂进 hasher 再喂内容本身：单纯按内容算出的哈希理论上已经能
+// 区分不同内容，这里额外混入长度只是多一道便宜的保险——就算某种输入碰巧让
+// 两个不同长度的文件摘要一致（实践中概率可忽略），长度不同也会让最终摘要
+// 不同，不会出现"两个不同大小的文件被误判成同一份内容"的情况。
+pub(crate) fn hash_file_contents(path: &Path) -> Option<String> {
+    use sha2::{Digest, Sha256};
+    use std::io::Read;
+
+    let mut file = std::fs::File::open(path).ok()?;
+    let file_len = file.metadata().ok()?.len();
+    let mut hasher = Sha256::new();
+    hasher.update(file_len.to_le_bytes());
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    loop {
+        let n = file.read(&mut buffer).ok()?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Some(format!("{:x}", hasher.finalize()))
+}
+
+// 在一批扫描结果（比如 scan_files_simplified 的输出）里找内容重复的文件，
+// 复用了那次扫描已经应用过的隐藏/扩展名/时间范围/文件类型过滤，所以调用方
+// 可以先按类别筛一遍再找重复。标准两阶段算法：先按文件大小分桶（便宜，
+// 用的是扫描时已经读过的元数据），大小唯一的桶直接丢弃，因为大小不同的文件
+// 内容不可能相同；剩下的同大小桶再算内容哈希重新分组，只保留大小 ≥ 2 的组。
+// Bundle 是目录，没有单一文件内容可流式哈希，直接跳过不计入重复检测。
+pub fn find_duplicates(files: &[FileInfo]) -> (Vec<DuplicateGroup>, DuplicateStats) {
+    let mut by_size: std::collections::BTreeMap<u64, Vec<FileInfo>> =
+        std::collections::BTreeMap::new();
+    for file in files {
+        by_size.entry(file.file_size).or_default().push(file.clone());
+    }
+
+    let mut by_hash: std::collections::BTreeMap<String, Vec<FileInfo>> =
+        std::collections::BTreeMap::new();
+    for (_, same_size_files) in by_size.into_iter().filter(|(_, group)| group.len() >= 2) {
+        for file in same_size_files {
+            let path = Path::new(&file.file_path);
+            if path.is_dir() {
+                continue; // Bundle：没有单一文件内容可哈希，跳过
+            }
+            let Some(hash) = hash_file_contents(path) else {
+                continue; // 读取失败（权限/文件已被删除等），跳过
+            };
+            by_hash.entry(hash).or_default().push(file);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = Vec::new();
+    let mut wasted_space: u64 = 0;
+    for (content_hash, group_files) in by_hash {
+        if group_files.len() < 2 {
+            continue;
+        }
+        let file_size = group_files[0].file_size;
+        wasted_space += (group_files.len() as u64 - 1) * file_size;
+        groups.push(DuplicateGroup {
+            content_hash,
+            file_size,
+            files: group_files,
+        });
+    }
+
+    let stats = DuplicateStats {
+        group_count: groups.len(),
+        wasted_space,
+    };
+
+    println!(
+        "[DUPLICATES] 找到 {} 组重复文件，共可释放 {} 字节",
+        stats.group_count, stats.wasted_space
+    );
+
+    (groups, stats)
+}
+
+// 体积最大的 N 个文件：在已有扫描结果上维护一个按大小限定数量的
+// `BTreeMap<u64, Vec<FileInfo>>`，插入新文件后只要计数超过
+// `number_of_files_to_check` 就弹出当前最小的一条（BTreeMap 按 key 有序，
+// 第一个 key 就是保留集合里最小的那个大小），这样结果集合的大小和内存占用
+// 始终有界，不用把所有候选文件攒在内存里整体排序一次。返回结果按大小从大
+// 到小排列。
+pub fn find_largest_files(
+    files: &[FileInfo],
+    number_of_files_to_check: usize,
+    min_file_size: u64,
+) -> (Vec<FileInfo>, u64) {
+    let mut by_size: std::collections::BTreeMap<u64, Vec<FileInfo>> =
+        std::collections::BTreeMap::new();
+    let mut kept_count: usize = 0;
+
+    for file in files {
+        if file.file_size < min_file_size {
+            continue; // 提前跳过太小的文件，省得它们占一个名额又马上被挤掉
+        }
+
+        by_size.entry(file.file_size).or_default().push(file.clone());
+        kept_count += 1;
+
+        while kept_count > number_of_files_to_check {
+            let Some(&smallest_size) = by_size.keys().next() else {
+                break;
+            };
+            let bucket = by_size.get_mut(&smallest_size).unwrap();
+            bucket.pop();
+            kept_count -= 1;
+            if bucket.is_empty() {
+                by_size.remove(&smallest_size);
+            }
+        }
+    }
+
+    // BTreeMap 按 key（文件大小）升序遍历，翻转成降序让最大的文件排在最前面
+    let mut result: Vec<FileInfo> = by_size.into_values().flatten().collect();
+    result.reverse();
+
+    let taken_space: u64 = result.iter().map(|f| f.file_size).sum();
+
+    println!(
+        "[LARGEST_FILES] 筛出 {} 个最大文件，占用 {} 字节",
+        result.len(),
+        taken_space
+    );
+
+    (result, taken_space)
+}
+
+// Tauri命令：在简化扫描结果里找占用空间最大的 N 个文件，复用简化扫描已有的
+// 全部过滤条件（隐藏/扩展名/时间范围/文件类型），方便做"磁盘占用大户"视图
+#[command]
+pub async fn find_largest_files_command(
+    app_handle: AppHandle,
+    time_range: Option<TimeRange>,
+    file_type: Option<FileType>,
+    sniff_content: Option<bool>,
+    number_of_files_to_check: usize,
+    min_file_size: Option<u64>,
+    app_state: State<'_, AppState>,
+) -> Result<(Vec<FileInfo>, u64), String> {
+    println!(
+        "[LARGEST_FILES] 调用最大文件报告: 时间范围={:?}, 文件类型={:?}, 数量={}, 最小体积={:?}",
+        time_range, file_type, number_of_files_to_check, min_file_size
+    );
+
+    let files =
+        scan_files_simplified_command(app_handle, time_range, file_type, sniff_content, app_state)
+            .await?;
+
+    Ok(find_largest_files(
+        &files,
+        number_of_files_to_check,
+        min_file_size.unwrap_or(0),
+    ))
+}
+
+// Tauri命令：在简化扫描结果里找重复文件，复用简化扫描已有的全部过滤条件
+#[command]
+pub async fn find_duplicate_files_command(
+    app_handle: AppHandle,
+    time_range: Option<TimeRange>,
+    file_type: Option<FileType>,
+    sniff_content: Option<bool>,
+    app_state: State<'_, AppState>,
+) -> Result<(Vec<DuplicateGroup>, DuplicateStats), String> {
+    println!(
+        "[DUPLICATES] 调用重复文件检测: 时间范围={:?}, 文件类型={:?}",
+        time_range, file_type
+    );
+
+    let files =
+        scan_files_simplified_command(app_handle, time_range, file_type, sniff_content, app_state)
+            .await?;
+
+    Ok(find_duplicates(&files))
+}
+
 // 启动后端全量扫描工作，必须在前端权限检查通过后才调用
 #[command]
 pub async fn start_backend_scanning(
@@ -558,12 +1288,12 @@ pub async fn start_backend_scanning(
                     }
                 };
 
-                // 获取目录列表并启动防抖动监控
-                let directories: Vec<String> = file_monitor_instance
+                // 获取目录列表（附带递归深度限制）并启动防抖动监控
+                let directories: Vec<(String, Option<u32>)> = file_monitor_instance
                     .get_monitored_directories()
                     .into_iter()
                     .filter(|dir| !dir.is_blacklist) // 过滤掉黑名单目录
-                    .map(|dir| dir.path)
+                    .map(|dir| (dir.path, dir.max_depth))
                     .collect();
 
                 if directories.is_empty() {
@@ -623,9 +1353,16 @@ fn log_permission_check(action: &str, path: &Path) {
 
 // 内部函数：使用指定过滤条件扫描文件
 async fn scan_files_with_filter(
+    app_handle: &AppHandle,
     config: &AllConfigurations,
     time_range: Option<TimeRange>,
     file_type: Option<FileType>,
+    ignore_enabled_for: &std::collections::HashSet<String>,
+    path_filters: &std::collections::HashMap<String, crate::path_filter::PathFilterConfig>,
+    sniff_content: bool,
+    // 是否跟随符号链接：出于安全考虑默认关闭（自引用的符号链接会导致无限
+    // 循环），开启时通过下面的已访问真实路径集合防护环路。
+    follow_symlinks: bool,
 ) -> Result<Vec<FileInfo>, String> {
     let mut files = Vec::new();
     let extension_maps = &config.file_extension_maps;
@@ -648,8 +1385,29 @@ async fn scan_files_with_filter(
         extension_filtered: 0,
         bundle_filtered: 0,
         total_included: 0,
+        cache_hits: 0,
+        symlink_cycles_skipped: 0,
+        ignore_pattern_filtered: 0,
+        blacklist_pruned: 0,
     };
 
+    // 加载持久化扫描缓存；拿不到应用数据目录（理论上不应发生）时退化为不
+    // 使用缓存，扫描仍然能正常进行，只是没有加速效果
+    let cache_dir = crate::scan_cache::resolve_cache_dir(app_handle);
+    let mut cache = cache_dir
+        .as_ref()
+        .map(|dir| crate::scan_cache::ScanCache::load(dir));
+
+    // 嵌套黑名单目录（在一个白名单根目录内部又单独标记为黑名单的子目录）的
+    // Trie，和 FileMonitor 实时监控用的是同一套结构/语义（见
+    // `file_monitor::build_blacklist_trie`），在 WalkDir 的 `filter_entry`
+    // 里按目录剪枝：命中的目录整棵子树都不会被展开，而不是等 WalkDir 展开
+    // 完之后再逐条路径事后判断。
+    let blacklist_trie = crate::file_monitor::build_blacklist_trie(
+        &config.monitored_folders,
+        config.case_sensitive_paths,
+    );
+
     for monitored_dir in &config.monitored_folders {
         // Only scan authorized and non-blacklisted directories
         // 只扫描非黑名单目录
@@ -677,13 +1435,122 @@ async fn scan_files_with_filter(
             continue;
         }
 
-        for entry in WalkDir::new(path)
-            .follow_links(true)
+        // 该目录是否启用了 .gitignore/.ignore 支持；启用时为每个遇到的条目
+        // 重新以其所在目录为叶子构建一次匹配器栈（层级不深，WalkDir 本身
+        // 也是逐级展开的，开销可接受）
+        let respect_ignore_files = ignore_enabled_for.contains(&monitored_dir.path);
+
+        // 该目录是否配置了 allow/ignore glob 过滤层（见 path_filter 模块）；
+        // 没配置时 `path_filter` 为 None，行为和之前完全一样。
+        let path_filter = path_filters
+            .get(&monitored_dir.path)
+            .map(crate::path_filter::PathFilter::compile);
+
+        // 第一阶段：单线程遍历目录树，只做目录级别的过滤（allow/ignore glob、
+        // .gitignore、隐藏文件、macOS bundle、路径黑名单分量），收集候选文件
+        // 路径。这部分天然带状态（WalkDir 的剪枝、逐级展开的 ignore 栈），
+        // 不值得为了并行把它拆散。
+        let mut candidate_files: Vec<PathBuf> = Vec::new();
+
+        // 该目录是否配置了递归深度限制（depth 1 = 只看直接子项）；未配置时
+        // 完全递归，和之前行为一致。
+        let mut walker = WalkDir::new(path).follow_links(follow_symlinks);
+        if let Some(depth) = monitored_dir.max_depth {
+            walker = walker.max_depth(depth as usize);
+        }
+
+        // walkdir 在 follow_links(true) 时不会自己防环路，一个指回祖先目录
+        // 的自引用符号链接会导致无限遍历。这里记录本目录遍历过程中已经走过
+        // 的真实（canonicalize 后）目录路径，重复出现就剪掉该子树，计入
+        // `cycle_skipped`（循环内局部变量，遍历结束后再并入 `stats`，避免
+        // 和下面循环体里对 `stats` 的借用冲突）。
+        let mut visited_real_dirs: std::collections::HashSet<PathBuf> =
+            std::collections::HashSet::new();
+        let mut cycle_skipped: u64 = 0;
+        // 下面两个都是循环内局部变量，遍历结束后再并入 `stats`，原因同
+        // `cycle_skipped`：避免和循环体里对 `stats` 的借用冲突。
+        // 在 filter_entry 里被黑名单 Trie 剪掉、整棵子树都没有展开的目录数。
+        let mut blacklist_pruned: u64 = 0;
+        // 在 filter_entry 里被 .gitignore/.ignore/.leafignore 剪掉的目录数，
+        // 和 scan_files_with_filter 以外其它调用点用的是同一个 stats 字段。
+        let mut ignore_pruned: u64 = 0;
+
+        for entry in walker
             .into_iter()
+            .filter_entry(|e| {
+                if follow_symlinks && e.file_type().is_dir() {
+                    if let Ok(real) = e.path().canonicalize() {
+                        if !visited_real_dirs.insert(real) {
+                            cycle_skipped += 1;
+                            return false;
+                        }
+                    }
+                }
+
+                // 黑名单 Trie 剪枝：目录本身或其任一祖先目录被显式拉黑，整棵
+                // 子树直接从遍历里剔除，不会被 WalkDir 展开，而不是先展开
+                // 整棵子树再逐条路径事后判断。
+                if e.file_type().is_dir()
+                    && blacklist_trie
+                        .is_path_or_ancestor_blacklisted(e.path(), config.case_sensitive_paths)
+                {
+                    blacklist_pruned += 1;
+                    return false;
+                }
+
+                // .gitignore/.ignore/.leafignore 剪枝：同样在进入目录前就
+                // 判断，命中就不展开，而不是等子树枚举完之后逐条跳过。
+                if respect_ignore_files {
+                    let entry_dir = if e.file_type().is_dir() {
+                        e.path()
+                    } else {
+                        e.path().parent().unwrap_or(path)
+                    };
+                    let ignore_stack = crate::ignore_matcher::IgnoreStack::build(path, entry_dir);
+                    if ignore_stack.is_ignored(e.path(), e.file_type().is_dir()) {
+                        if e.file_type().is_dir() {
+                            ignore_pruned += 1;
+                        }
+                        return false;
+                    }
+                }
+
+                // 只在配置了 allow 列表时剪枝：目录的相对路径如果不可能被任何
+                // allow 模式匹配到，就不再往下展开整个子树。
+                let Some(filter) = &path_filter else {
+                    return true;
+                };
+                if !e.file_type().is_dir() {
+                    return true;
+                }
+                let relative = e.path().strip_prefix(path).unwrap_or(e.path());
+                filter.could_descend(&relative.to_string_lossy().replace('\\', "/"))
+            })
             .filter_map(|e| e.ok())
         {
             stats.total_discovered += 1;
 
+            if let Some(filter) = &path_filter {
+                let relative = entry.path().strip_prefix(path).unwrap_or(entry.path());
+                let relative_str = relative.to_string_lossy().replace('\\', "/");
+                if !relative_str.is_empty() && !filter.is_allowed(&relative_str) {
+                    continue;
+                }
+            }
+
+            // 上面 filter_entry 已经把目录级别的黑名单/ignore 命中剪掉了，这里
+            // 还需要对文件本身再判断一次：一个文件自身匹配 ignore 规则，但它
+            // 所在目录并不匹配，filter_entry 不会剪掉它所在目录，需要在这里
+            // 单独跳过这一个文件条目。
+            if respect_ignore_files && entry.file_type().is_file() {
+                let entry_dir = entry.path().parent().unwrap_or(path);
+                let ignore_stack = crate::ignore_matcher::IgnoreStack::build(path, entry_dir);
+                if ignore_stack.is_ignored(entry.path(), false) {
+                    stats.ignore_pattern_filtered += 1;
+                    continue;
+                }
+            }
+
             // 首先，最高优先级过滤 - 隐藏文件
             if is_hidden_file(entry.path()) {
                 stats.hidden_filtered += 1;
@@ -702,10 +1569,10 @@ async fn scan_files_with_filter(
             }
 
             // 路径级别过滤 - 检查路径中是否包含需要过滤的目录
-            let path = entry.path();
+            let entry_path = entry.path();
             let mut should_skip = false;
 
-            for component in path.components() {
+            for component in entry_path.components() {
                 if let std::path::Component::Normal(name) = component {
                     if let Some(name_str) = name.to_str() {
                         // 过滤掉路径中包含以点开头的目录（隐藏目录）
@@ -732,414 +1599,565 @@ async fn scan_files_with_filter(
                 continue;
             }
 
-            let file_path = entry.path();
-            let extension = get_file_extension(file_path);
-
-            // 白名单扩展名过滤：只处理有扩展名且扩展名在配置白名单中的文件
-            if let Some(ref ext) = extension {
-                let ext_lower = ext.to_lowercase();
-                if !valid_extensions.contains(&ext_lower) {
-                    // 扩展名不在白名单中，跳过并记录
-                    stats.extension_filtered += 1;
-                    println!(
-                        "[SCAN] 跳过非白名单扩展名文件: {} (扩展名: {})",
-                        file_path.display(),
-                        ext_lower
-                    );
-                    continue;
-                }
-            } else if file_type != Some(FileType::All) {
-                // 没有扩展名且不是查找所有文件类型，跳过
-                stats.extension_filtered += 1;
-                println!("[SCAN] 跳过无扩展名文件: {}", file_path.display());
-                continue;
-            }
-
-            // 应用文件类型过滤器
-            if let Some(ref ft) = file_type {
-                if !is_file_of_type(&extension, ft, extension_maps) {
-                    println!(
-                        "[SCAN] 跳过不匹配类型过滤器的文件: {} (期望类型: {:?})",
-                        file_path.display(),
-                        ft
-                    );
-                    continue;
-                }
-            }
-
-            // 获取文件元数据
-            let metadata = match std::fs::metadata(file_path) {
-                Ok(meta) => meta,
-                Err(e) => {
-                    println!(
-                        "[SCAN] 无法获取文件元数据: {} (错误: {})",
-                        file_path.display(),
-                        e
-                    );
-                    continue;
-                }
-            };
-
-            // 获取修改时间
-            let modified_time = match metadata.modified() {
-                Ok(time) => time,
-                Err(_) => continue,
-            };
+            candidate_files.push(entry_path.to_path_buf());
+        }
 
-            let modified_time_secs = match modified_time.duration_since(UNIX_EPOCH) {
-                Ok(duration) => duration.as_secs(),
-                Err(_) => continue,
-            };
+        stats.symlink_cycles_skipped += cycle_skipped;
+        stats.blacklist_pruned += blacklist_pruned;
+        stats.ignore_pattern_filtered += ignore_pruned;
+
+        // 第二阶段：真正耗时的部分——读取元数据、按需内容嗅探、查缓存——分给
+        // 一个线程池并行处理，百万级文件量的扫描不再被单核拖住。用
+        // `std::thread::scope` 直接借用外层的只读配置和共享的缓存/计数器，
+        // 不需要引入 rayon 这样额外的并发 crate 依赖。
+        let worker_count = std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(4)
+            .max(1);
+        let chunk_size = ((candidate_files.len() + worker_count - 1) / worker_count).max(1);
+
+        let files_checked = AtomicU64::new(0);
+        let files_included = AtomicU64::new(0);
+        let extension_filtered = AtomicU64::new(0);
+        let cache_hits = AtomicU64::new(0);
+        let folder_results = Mutex::new(Vec::new());
+        let cache_mutex = Mutex::new(cache.take());
+        let current_dir_label = monitored_dir.path.clone();
+
+        std::thread::scope(|scope| {
+            for chunk in candidate_files.chunks(chunk_size) {
+                let folder_results = &folder_results;
+                let cache_mutex = &cache_mutex;
+                let files_checked = &files_checked;
+                let files_included = &files_included;
+                let extension_filtered = &extension_filtered;
+                let cache_hits = &cache_hits;
+                let extension_maps: &[FileExtensionMapRust] = extension_maps;
+                let valid_extensions = &valid_extensions;
+                let file_type = &file_type;
+                let time_range = &time_range;
+                let app_handle = app_handle.clone();
+                let current_dir_label = current_dir_label.clone();
+
+                scope.spawn(move || {
+                    for file_path in chunk {
+                        let checked_so_far = files_checked.fetch_add(1, Ordering::Relaxed) + 1;
+
+                        match process_candidate_file(
+                            file_path,
+                            extension_maps,
+                            valid_extensions,
+                            sniff_content,
+                            follow_symlinks,
+                            file_type,
+                            time_range,
+                            cache_mutex,
+                            cache_hits,
+                        ) {
+                            CandidateOutcome::Included(file_info) => {
+                                files_included.fetch_add(1, Ordering::Relaxed);
+                                folder_results.lock().unwrap().push(file_info);
+                            }
+                            CandidateOutcome::ExtensionFiltered => {
+                                extension_filtered.fetch_add(1, Ordering::Relaxed);
+                            }
+                            CandidateOutcome::Skipped => {}
+                        }
 
-            // 应用时间范围过滤器
-            if let Some(ref tr) = time_range {
-                if !is_file_in_time_range(modified_time_secs, tr) {
-                    println!(
-                        "[SCAN] 跳过不在时间范围内的文件: {} (范围: {:?})",
-                        file_path.display(),
-                        tr
-                    );
-                    continue;
-                }
+                        if checked_so_far % PROGRESS_EMIT_INTERVAL == 0 {
+                            let _ = app_handle.emit(
+                                "scan_progress",
+                                ScanProgress {
+                                    files_checked: checked_so_far,
+                                    files_included: files_included.load(Ordering::Relaxed),
+                                    current_dir: current_dir_label.clone(),
+                                },
+                            );
+                        }
+                    }
+                });
             }
+        });
 
-            // 获取创建时间
-            let created_time = metadata
-                .created()
-                .ok()
-                .map(|time| system_time_to_iso_string(time));
-
-            // 计算文件大小
-            let file_size = metadata.len();
-
-            // 获取文件名
-            let file_name = file_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("")
-                .to_string();
-
-            // 根据扩展名匹配分类ID
-            let category_id = extension.as_ref().and_then(|ext| {
-                extension_maps
-                    .iter()
-                    .find(|map| map.extension.to_lowercase() == ext.to_lowercase())
-                    .map(|map| map.category_id)
-            });
-
-            // 文件通过了所有过滤器，添加到结果列表
-            files.push(FileInfo {
-                file_path: file_path.to_string_lossy().into_owned(),
-                file_name,
-                file_size,
-                extension,
-                created_time,
-                modified_time: system_time_to_iso_string(modified_time),
-                category_id,
-            });
+        cache = cache_mutex.into_inner().unwrap();
+        stats.extension_filtered += extension_filtered.into_inner();
+        stats.cache_hits += cache_hits.into_inner();
+        stats.total_included += files_included.into_inner();
+        files.extend(folder_results.into_inner().unwrap());
+    }
 
-            stats.total_included += 1;
+    // 多个文件夹、多个工作线程并发写入的结果顺序并不稳定，这里按路径排序
+    // 保证同一份输入跨线程数、跨运行的输出是确定的，再套用原有的数量上限。
+    files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    if files.len() > 500 {
+        println!("[SCAN] 已达到500个文件的限制，截断结果");
+        files.truncate(500);
+    }
 
-            // 返回前500个文件
-            if files.len() >= 500 {
-                println!("[SCAN] 已达到500个文件的限制，停止扫描");
-                break;
-            }
+    // 清掉本次扫描没再碰到的缓存记录，然后把缓存写回磁盘
+    if let (Some(dir), Some(mut cache)) = (cache_dir, cache) {
+        cache.evict_missing();
+        stats.cache_hits = cache.hits;
+        if let Err(e) = cache.save(&dir) {
+            eprintln!("[SCAN] 保存扫描缓存失败: {}", e);
         }
     }
 
     // 打印扫描统计信息
-    println!("[SCAN] 扫描统计: 发现文件总数: {}, 包含文件数: {}, 被过滤文件数: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
-        stats.total_discovered, 
+    println!("[SCAN] 扫描统计: 发现文件总数: {}, 包含文件数: {}, 被过滤文件数: {} (隐藏: {}, 扩展名: {}, Bundle: {}), 缓存命中: {}, 符号链接环路跳过: {}, 黑名单子树剪枝: {}, ignore 规则过滤: {}",
+        stats.total_discovered,
         stats.total_included,
         stats.hidden_filtered + stats.extension_filtered + stats.bundle_filtered,
         stats.hidden_filtered,
         stats.extension_filtered,
-        stats.bundle_filtered
+        stats.bundle_filtered,
+        stats.cache_hits,
+        stats.symlink_cycles_skipped,
+        stats.blacklist_pruned,
+        stats.ignore_pattern_filtered
     );
 
     Ok(files)
 }
 
-// 新的简化扫描函数，使用FileScanningConfig
-async fn scan_files_simplified(
+// scan_files_simplified 并行阶段里单条候选路径（普通文件或 Bundle 根目录）
+// 的处理结果：要么被收录（连同算好的 FileInfo），要么落进某个过滤统计桶，
+// 要么（比如普通子目录、或通过了扩展名/类型过滤但时间范围不匹配）悄悄跳过、
+// 不计入任何统计量——和原来单线程版本逐条 `continue` 的行为保持一致。
+enum SimplifiedOutcome {
+    Included(FileInfo),
+    HiddenFiltered,
+    BundleFiltered,
+    ExtensionFiltered,
+    Skipped,
+}
+
+// 单条候选路径真正耗时的处理：隐藏文件判断、Bundle整体打包、Bundle内部文件
+// 跳过、扩展名/内容嗅探分类、时间范围/文件类型过滤、元数据读取。不再依赖
+// WalkDir 的 `DirEntry`（改用 `std::fs::metadata` 重新读取），这样可以脱离
+// 遍历顺序，安全地在多个线程里并发调用。Bundle/普通文件两条分支各自组一条
+// `Vec<Box<dyn ScanFilter>>`，共用同一套 HiddenFilter/TimeRangeFilter/
+// FileTypeFilter 实现（见文件开头的 ScanFilter 说明）。
+fn process_simplified_candidate(
+    file_path: &Path,
     config: &crate::file_monitor::FileScanningConfig,
-    monitored_folders: &[crate::file_monitor::MonitoredDirectory],
-    time_range: Option<TimeRange>,
-    file_type: Option<FileType>,
-) -> Result<Vec<FileInfo>, String> {
-    let mut files = Vec::new();
-    let mut stats = ScanStats::default();
+    time_range: &Option<TimeRange>,
+    file_type: &Option<FileType>,
+    sniff_content: bool,
+) -> SimplifiedOutcome {
+    let Ok(probe_meta) = std::fs::metadata(file_path) else {
+        return SimplifiedOutcome::Skipped;
+    };
 
-    println!(
-        "[SCAN_SIMPLIFIED] 开始简化扫描，监控文件夹数: {}",
-        monitored_folders.len()
-    );
-    println!(
-        "[SCAN_SIMPLIFIED] 配置：扩展名映射: {}, Bundle扩展名: {}, 忽略规则: {}",
-        config.extension_mappings.len(),
-        config.bundle_extensions.len(),
-        config.ignore_patterns.len()
-    );
+    let mut ctx = FilterContext::default();
+    if let FilterOutcome::Rejected(_) = (HiddenFilter).accept(file_path, &probe_meta, &mut ctx) {
+        return SimplifiedOutcome::HiddenFiltered;
+    }
 
-    // 遍历所有监控的文件夹
-    for folder in monitored_folders {
-        if folder.is_blacklist {
-            println!("[SCAN_SIMPLIFIED] 跳过黑名单文件夹: {}", folder.path);
-            continue;
-        }
+    // 检查是否为Bundle：把它当成一个整体文件，用 BundleFilter 解析扩展名/
+    // 分类，链上剩下的 TimeRangeFilter/FileTypeFilter 和普通文件分支共用同一
+    // 个实现。原来的行为是无论具体哪一步失败，都统一计入 bundle_filtered，
+    // 所以这里链上任何一个环节被拒绝都直接映射成 BundleFiltered。
+    if file_path.is_dir() && is_macos_bundle(file_path, &config.bundle_extensions) {
+        println!("[SCAN_SIMPLIFIED] 发现Bundle: {}", file_path.display());
+
+        let filters: Vec<Box<dyn ScanFilter>> = vec![
+            Box::new(BundleFilter {
+                extension_mappings: &config.extension_mappings,
+            }),
+            Box::new(TimeRangeFilter {
+                time_range,
+                read_failure_bucket: RejectBucket::Bundle,
+            }),
+            Box::new(FileTypeFilter {
+                file_type,
+                reject_bucket: RejectBucket::Bundle,
+            }),
+        ];
 
-        let folder_path = PathBuf::from(&folder.path);
-        if !folder_path.exists() {
-            println!("[SCAN_SIMPLIFIED] 文件夹不存在: {}", folder.path);
-            continue;
+        if run_filter_chain(&filters, file_path, &probe_meta, &mut ctx).is_some() {
+            println!(
+                "[SCAN_SIMPLIFIED] Bundle被过滤（扩展名/分类/时间范围/类型不匹配）: {}",
+                file_path.display()
+            );
+            return SimplifiedOutcome::BundleFiltered;
         }
 
-        println!("[SCAN_SIMPLIFIED] 扫描文件夹: {}", folder.path);
+        let modified_time = probe_meta
+            .modified()
+            .expect("TimeRangeFilter 已经验证过修改时间可读");
+        let created_time = probe_meta.created().ok().map(system_time_to_iso_string);
+        let file_name = file_path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or("")
+            .to_string();
 
-        // 使用walkdir遍历文件夹
-        let walker = WalkDir::new(&folder_path).follow_links(false).max_depth(10); // 限制最大深度避免无限递归
+        println!(
+            "[SCAN_SIMPLIFIED] 包含Bundle: {} (分类: {:?})",
+            file_path.display(),
+            ctx.category_id
+        );
 
-        for entry in walker.into_iter() {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(e) => {
-                    println!("[SCAN_SIMPLIFIED] 读取文件时出错: {}", e);
-                    continue;
-                }
-            };
+        return SimplifiedOutcome::Included(FileInfo {
+            file_path: file_path.to_string_lossy().into_owned(),
+            file_name,
+            file_size: probe_meta.len(),
+            extension: ctx.extension,
+            created_time,
+            modified_time: system_time_to_iso_string(modified_time),
+            category_id: ctx.category_id,
+        });
+    }
 
-            let file_path = entry.path();
-            stats.total_discovered += 1;
+    // 检查是否在Bundle内部
+    if let Some(bundle_path) = find_containing_bundle(file_path, &config.bundle_extensions) {
+        println!(
+            "[SCAN_SIMPLIFIED] 跳过Bundle内部文件: {} (Bundle: {})",
+            file_path.display(),
+            bundle_path.display()
+        );
+        return SimplifiedOutcome::BundleFiltered;
+    }
 
-            // 检查是否为隐藏文件
-            if is_hidden_file(file_path) {
-                stats.hidden_filtered += 1;
-                continue;
-            }
+    // 只处理普通文件
+    if !file_path.is_file() {
+        return SimplifiedOutcome::Skipped;
+    }
 
-            // 检查是否为Bundle
-            if file_path.is_dir() && is_macos_bundle(file_path, &config.bundle_extensions) {
-                println!("[SCAN_SIMPLIFIED] 发现Bundle: {}", file_path.display());
-
-                // 将Bundle作为整体文件处理
-                let bundle_extension = get_file_extension(file_path);
-
-                // 检查Bundle的扩展名是否在我们关注的范围内
-                if let Some(ref ext) = bundle_extension {
-                    if let Some(&category_id) = config.extension_mappings.get(ext) {
-                        // 获取Bundle的元数据
-                        let metadata = match entry.metadata() {
-                            Ok(m) => m,
-                            Err(_) => {
-                                stats.bundle_filtered += 1;
-                                continue;
-                            }
-                        };
+    // 扩展名/分类过滤器和元数据读取失败统一计入 extension_filtered，时间
+    // 范围不匹配则悄悄跳过——和原来单线程版本的桶分配完全一致。
+    let filters: Vec<Box<dyn ScanFilter>> = vec![
+        Box::new(ExtensionFilter {
+            extension_mappings: &config.extension_mappings,
+            sniff_content,
+        }),
+        Box::new(FileTypeFilter {
+            file_type,
+            reject_bucket: RejectBucket::Extension,
+        }),
+        Box::new(TimeRangeFilter {
+            time_range,
+            read_failure_bucket: RejectBucket::Extension,
+        }),
+    ];
 
-                        let modified_time = match metadata.modified() {
-                            Ok(time) => time,
-                            Err(_) => {
-                                stats.bundle_filtered += 1;
-                                continue;
-                            }
-                        };
+    if let Some(bucket) = run_filter_chain(&filters, file_path, &probe_meta, &mut ctx) {
+        return match bucket {
+            RejectBucket::Silent => SimplifiedOutcome::Skipped,
+            _ => SimplifiedOutcome::ExtensionFiltered,
+        };
+    }
 
-                        let modified_time_secs = match modified_time.duration_since(UNIX_EPOCH) {
-                            Ok(duration) => duration.as_secs(),
-                            Err(_) => {
-                                stats.bundle_filtered += 1;
-                                continue;
-                            }
-                        };
+    let modified_time = probe_meta
+        .modified()
+        .expect("TimeRangeFilter 已经验证过修改时间可读");
+    let created_time = probe_meta.created().ok().map(system_time_to_iso_string);
+    let file_name = file_path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+        .to_string();
+
+    SimplifiedOutcome::Included(FileInfo {
+        file_path: file_path.to_string_lossy().into_owned(),
+        file_name,
+        file_size: probe_meta.len(),
+        extension: ctx.extension,
+        created_time,
+        modified_time: system_time_to_iso_string(modified_time),
+        category_id: ctx.category_id,
+    })
+}
 
-                        // 应用时间范围过滤器
-                        if let Some(ref tr) = time_range {
-                            if !is_file_in_time_range(modified_time_secs, tr) {
-                                stats.bundle_filtered += 1;
-                                continue;
-                            }
-                        }
+// 单个监控文件夹的完整两阶段扫描（walkdir 单线程收集候选路径 + 线程池并行
+// 处理 `process_simplified_candidate`），返回按路径排好序的文件列表。过滤
+// 统计量直接累加进调用方传入的 `stats`，供分页扫描在页与页之间维持累计值。
+// 不做数量上限——分页窗口的裁剪交给调用方（`scan_files_simplified`）处理。
+async fn scan_folder_simplified(
+    folder: &crate::file_monitor::MonitoredDirectory,
+    config: &crate::file_monitor::FileScanningConfig,
+    time_range: &Option<TimeRange>,
+    file_type: &Option<FileType>,
+    sniff_content: bool,
+    stats: &mut ScanStats,
+) -> Vec<FileInfo> {
+    let folder_path = PathBuf::from(&folder.path);
+    println!("[SCAN_SIMPLIFIED] 扫描文件夹: {}", folder.path);
+
+    // 自定义排除规则（`config.ignore_patterns`，gitignore 风格的 glob，
+    // 支持 `!` 取反）：复用 path_filter 模块里已经写好的 ignore 列表匹配
+    // 逻辑，只是这里不需要 allow 列表（留空即表示"不额外限制，只看
+    // ignore"）。
+    let custom_ignore = crate::path_filter::PathFilter::compile(&crate::path_filter::PathFilterConfig {
+        allow: Vec::new(),
+        ignore: config.ignore_patterns.clone(),
+    });
 
-                        // 应用文件类型过滤器（基于分类ID）
-                        if let Some(ref ft) = file_type {
-                            if *ft != FileType::All {
-                                let target_category_ids = get_category_ids_for_file_type(ft);
-                                if !target_category_ids.is_empty()
-                                    && !target_category_ids.contains(&category_id)
-                                {
-                                    stats.bundle_filtered += 1;
-                                    continue;
-                                }
-                            }
-                        }
+    // 第一阶段：单线程用walkdir遍历文件夹收集候选路径（WalkDir本身只能
+    // 顺序展开，这部分不值得并行化）。`filter_entry` 在这里同时做两件事：
+    // 剪掉匹配 `custom_ignore` 的条目，以及匹配某一级 `.gitignore`/
+    // `.ignore` 文件规则的条目（用 ignore_matcher::IgnoreStack，和
+    // scan_files_with_filter 里 respect_ignore_files 用的是同一套逻辑）。
+    // 对目录条目剪枝会让 WalkDir 直接跳过整棵子树，不只是跳过这一条。
+    let walker = WalkDir::new(&folder_path).follow_links(false).max_depth(10); // 限制最大深度避免无限递归
+
+    let mut candidate_paths: Vec<PathBuf> = Vec::new();
+    let mut ignore_pruned: u64 = 0;
+
+    for entry in walker.into_iter().filter_entry(|e| {
+        let relative = e.path().strip_prefix(&folder_path).unwrap_or(e.path());
+        if relative.as_os_str().is_empty() {
+            return true; // 监控根目录自身永远保留
+        }
+        let relative_str = relative.to_string_lossy().replace('\\', "/");
+        if !custom_ignore.is_allowed(&relative_str) {
+            ignore_pruned += 1;
+            return false;
+        }
 
-                        let created_time = metadata
-                            .created()
-                            .ok()
-                            .map(|time| system_time_to_iso_string(time));
-
-                        let file_name = file_path
-                            .file_name()
-                            .and_then(|name| name.to_str())
-                            .unwrap_or("")
-                            .to_string();
-
-                        files.push(FileInfo {
-                            file_path: file_path.to_string_lossy().into_owned(),
-                            file_name,
-                            file_size: metadata.len(),
-                            extension: bundle_extension,
-                            created_time,
-                            modified_time: system_time_to_iso_string(modified_time),
-                            category_id: Some(category_id),
-                        });
-
-                        stats.total_included += 1;
-                        println!(
-                            "[SCAN_SIMPLIFIED] 包含Bundle: {} (分类: {})",
-                            file_path.display(),
-                            category_id
-                        );
-                    } else {
-                        stats.bundle_filtered += 1;
-                        println!("[SCAN_SIMPLIFIED] Bundle扩展名不在关注范围: {}", ext);
-                    }
-                } else {
-                    stats.bundle_filtered += 1;
-                    println!(
-                        "[SCAN_SIMPLIFIED] Bundle无法获取扩展名: {}",
-                        file_path.display()
-                    );
-                }
+        let entry_dir = if e.file_type().is_dir() {
+            e.path()
+        } else {
+            e.path().parent().unwrap_or(&folder_path)
+        };
+        let ignore_stack = crate::ignore_matcher::IgnoreStack::build(&folder_path, entry_dir);
+        if ignore_stack.is_ignored(e.path(), e.file_type().is_dir()) {
+            ignore_pruned += 1;
+            return false;
+        }
 
-                // 跳过Bundle内部文件的扫描
-                continue;
+        true
+    }) {
+        match entry {
+            Ok(e) => {
+                stats.total_discovered += 1;
+                candidate_paths.push(e.path().to_path_buf());
             }
-
-            // 检查是否在Bundle内部
-            if let Some(bundle_path) = find_containing_bundle(file_path, &config.bundle_extensions)
-            {
-                println!(
-                    "[SCAN_SIMPLIFIED] 跳过Bundle内部文件: {} (Bundle: {})",
-                    file_path.display(),
-                    bundle_path.display()
-                );
-                stats.bundle_filtered += 1;
-                continue;
+            Err(e) => {
+                println!("[SCAN_SIMPLIFIED] 读取文件时出错: {}", e);
             }
+        }
+    }
 
-            // 只处理普通文件
-            if !file_path.is_file() {
-                continue;
-            }
+    stats.ignore_pattern_filtered += ignore_pruned;
+
+    // 第二阶段：每条候选路径的隐藏/Bundle/扩展名/时间/类型过滤和元数据
+    // 读取都是互不依赖的只读操作，分给一个线程池并行处理，逻辑见
+    // `process_simplified_candidate`。
+    let worker_count = std::thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(4)
+        .max(1);
+    let chunk_size = ((candidate_paths.len() + worker_count - 1) / worker_count).max(1);
+
+    let hidden_filtered = AtomicU64::new(0);
+    let bundle_filtered = AtomicU64::new(0);
+    let extension_filtered = AtomicU64::new(0);
+    let folder_results = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for chunk in candidate_paths.chunks(chunk_size) {
+            let folder_results = &folder_results;
+            let hidden_filtered = &hidden_filtered;
+            let bundle_filtered = &bundle_filtered;
+            let extension_filtered = &extension_filtered;
+            let config = &config;
+            let time_range = &time_range;
+            let file_type = &file_type;
+
+            scope.spawn(move || {
+                for path in chunk {
+                    match process_simplified_candidate(
+                        path,
+                        config,
+                        time_range,
+                        file_type,
+                        sniff_content,
+                    ) {
+                        SimplifiedOutcome::Included(file_info) => {
+                            folder_results.lock().unwrap().push(file_info);
+                        }
+                        SimplifiedOutcome::HiddenFiltered => {
+                            hidden_filtered.fetch_add(1, Ordering::Relaxed);
+                        }
+                        SimplifiedOutcome::BundleFiltered => {
+                            bundle_filtered.fetch_add(1, Ordering::Relaxed);
+                        }
+                        SimplifiedOutcome::ExtensionFiltered => {
+                            extension_filtered.fetch_add(1, Ordering::Relaxed);
+                        }
+                        SimplifiedOutcome::Skipped => {}
+                    }
+                }
+            });
+        }
+    });
 
-            // 获取文件扩展名
-            let extension = get_file_extension(file_path);
+    stats.hidden_filtered += hidden_filtered.into_inner();
+    stats.bundle_filtered += bundle_filtered.into_inner();
+    stats.extension_filtered += extension_filtered.into_inner();
 
-            // 只包含在扩展名映射中的文件
-            let category_id = if let Some(ref ext) = extension {
-                if let Some(&cat_id) = config.extension_mappings.get(ext) {
-                    cat_id
-                } else {
-                    stats.extension_filtered += 1;
-                    continue; // 扩展名不在关注范围内
-                }
-            } else {
-                stats.extension_filtered += 1;
-                continue; // 无扩展名文件不包含
-            };
+    // 同一个文件夹内，多个工作线程并发写入的结果顺序并不稳定，这里按路径
+    // 排序，好让分页游标能够用 `file_path` 精确定位"翻到哪了"。
+    let mut folder_files = folder_results.into_inner().unwrap();
+    folder_files.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    stats.total_included += folder_files.len() as u64;
+    folder_files
+}
 
-            // 应用文件类型过滤器
-            if let Some(ref ft) = file_type {
-                if *ft != FileType::All {
-                    let target_category_ids = get_category_ids_for_file_type(ft);
-                    if !target_category_ids.is_empty()
-                        && !target_category_ids.contains(&category_id)
-                    {
-                        stats.extension_filtered += 1;
-                        continue;
-                    }
-                }
-            }
+// 简化扫描，支持分页：按 `cursor` 指定的位置继续，填满最多 `page_size` 条
+// 结果就停下，返回这一页的文件、下一页的游标（翻完时是 `None`）、以及累计
+// 到这一页为止的统计量（调用方把上一页返回的 `stats` 原样传进来，在其基础
+// 上累加，不会因为分页而失真）。
+//
+// 监控文件夹按路径排序，保证跨页、跨调用的遍历顺序稳定；`folder_index`
+// 之前的文件夹整个跳过，`folder_index` 对应的文件夹内部按 `last_path`/
+// `remaining_skip` 跳到上次结束的地方。注意这里是"对每个文件夹重新算出
+// 完整有序结果、再做窗口裁剪"，不是真正维护一个跨调用存活的目录遍历游标——
+// Tauri 命令本身是无状态的，每次调用都是新的异步任务，没有地方挂一个活着
+// 的 WalkDir 迭代器。用重新走一遍文件夹换取实现简单、结果确定；每页本身的
+// 内存占用仍然是有界的，不会像过去硬编码的 500 上限那样丢数据也不让用户
+// 知道还有更多。
+async fn scan_files_simplified(
+    config: &crate::file_monitor::FileScanningConfig,
+    monitored_folders: &[crate::file_monitor::MonitoredDirectory],
+    time_range: Option<TimeRange>,
+    file_type: Option<FileType>,
+    sniff_content: bool,
+    cursor: ScanCursor,
+    page_size: usize,
+    mut stats: ScanStats,
+) -> Result<(Vec<FileInfo>, Option<ScanCursor>, ScanStats), String> {
+    let mut folders: Vec<&crate::file_monitor::MonitoredDirectory> = monitored_folders
+        .iter()
+        .filter(|f| !f.is_blacklist)
+        .collect();
+    folders.sort_by(|a, b| a.path.cmp(&b.path));
 
-            // 获取文件元数据
-            let metadata = match entry.metadata() {
-                Ok(m) => m,
-                Err(_) => {
-                    stats.extension_filtered += 1;
-                    continue;
-                }
-            };
+    println!(
+        "[SCAN_SIMPLIFIED] 分页扫描: 文件夹索引={}, page_size={}",
+        cursor.folder_index, page_size
+    );
 
-            let modified_time = match metadata.modified() {
-                Ok(time) => time,
-                Err(_) => {
-                    stats.extension_filtered += 1;
-                    continue;
-                }
-            };
+    let mut page: Vec<FileInfo> = Vec::new();
+    let mut next_cursor: Option<ScanCursor> = None;
 
-            let modified_time_secs = match modified_time.duration_since(UNIX_EPOCH) {
-                Ok(duration) => duration.as_secs(),
-                Err(_) => {
-                    stats.extension_filtered += 1;
-                    continue;
-                }
-            };
+    for (idx, folder) in folders.iter().enumerate() {
+        if idx < cursor.folder_index {
+            continue;
+        }
 
-            // 应用时间范围过滤器
-            if let Some(ref tr) = time_range {
-                if !is_file_in_time_range(modified_time_secs, tr) {
-                    continue;
-                }
-            }
+        let folder_path = PathBuf::from(&folder.path);
+        if !folder_path.exists() {
+            println!("[SCAN_SIMPLIFIED] 文件夹不存在: {}", folder.path);
+            continue;
+        }
 
-            let created_time = metadata
-                .created()
-                .ok()
-                .map(|time| system_time_to_iso_string(time));
-
-            let file_name = file_path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .unwrap_or("")
-                .to_string();
-
-            files.push(FileInfo {
-                file_path: file_path.to_string_lossy().into_owned(),
-                file_name,
-                file_size: metadata.len(),
-                extension,
-                created_time,
-                modified_time: system_time_to_iso_string(modified_time),
-                category_id: Some(category_id),
-            });
+        let folder_files = scan_folder_simplified(
+            folder,
+            config,
+            &time_range,
+            &file_type,
+            sniff_content,
+            &mut stats,
+        )
+        .await;
+
+        let start_idx = if idx == cursor.folder_index {
+            let after_last_path = match &cursor.last_path {
+                Some(last_path) => folder_files.partition_point(|f| &f.file_path <= last_path),
+                None => 0,
+            };
+            (after_last_path + cursor.remaining_skip).min(folder_files.len())
+        } else {
+            0
+        };
 
-            stats.total_included += 1;
+        if start_idx >= folder_files.len() {
+            continue; // 这个文件夹在游标之前就已经翻完了
+        }
 
-            // 限制返回文件数量
-            if files.len() >= 500 {
-                println!("[SCAN_SIMPLIFIED] 已达到500个文件的限制，停止扫描");
-                break;
-            }
+        let remaining_budget = page_size - page.len();
+        let take = remaining_budget.min(folder_files.len() - start_idx);
+        page.extend_from_slice(&folder_files[start_idx..start_idx + take]);
+
+        let consumed_up_to = start_idx + take;
+        if consumed_up_to < folder_files.len() {
+            // 这一页在本文件夹内部就填满了，下次从这个文件夹同一个位置继续
+            next_cursor = Some(ScanCursor {
+                folder_index: idx,
+                last_path: Some(folder_files[consumed_up_to - 1].file_path.clone()),
+                remaining_skip: 0,
+            });
+            break;
         }
 
-        // 如果已经达到文件数量限制，跳出文件夹循环
-        if files.len() >= 500 {
+        if page.len() >= page_size {
+            // 刚好在文件夹边界上填满，下一页从下一个文件夹开始
+            if idx + 1 < folders.len() {
+                next_cursor = Some(ScanCursor {
+                    folder_index: idx + 1,
+                    last_path: None,
+                    remaining_skip: 0,
+                });
+            }
             break;
         }
+        // 本文件夹全部收进了这一页，但还没填满，继续看下一个文件夹
     }
 
-    // 打印扫描统计信息
-    println!("[SCAN_SIMPLIFIED] 扫描统计: 发现总数: {}, 包含: {}, 过滤: {} (隐藏: {}, 扩展名: {}, Bundle: {})", 
-        stats.total_discovered, 
+    println!("[SCAN_SIMPLIFIED] 分页扫描统计: 发现总数: {}, 累计包含: {}, 本页: {} (隐藏: {}, 扩展名: {}, Bundle: {}, ignore规则: {})",
+        stats.total_discovered,
         stats.total_included,
-        stats.hidden_filtered + stats.extension_filtered + stats.bundle_filtered,
+        page.len(),
         stats.hidden_filtered,
         stats.extension_filtered,
-        stats.bundle_filtered
+        stats.bundle_filtered,
+        stats.ignore_pattern_filtered
     );
 
-    Ok(files)
+    Ok((page, next_cursor, stats))
+}
+
+// 一次性拿到简化扫描的完整结果（不分页）：内部按固定页大小循环调用
+// `scan_files_simplified` 直到游标耗尽，再拼成一个 Vec。已有的三个 Tauri
+// 命令（简化扫描本身，以及依赖它的去重/最大文件报告）一直是"要全部结果"的
+// 语义，这样可以不动它们的签名，只是不再像以前那样被硬编码的500强行截断。
+async fn scan_files_simplified_all(
+    config: &crate::file_monitor::FileScanningConfig,
+    monitored_folders: &[crate::file_monitor::MonitoredDirectory],
+    time_range: Option<TimeRange>,
+    file_type: Option<FileType>,
+    sniff_content: bool,
+) -> Result<Vec<FileInfo>, String> {
+    const COLLECT_PAGE_SIZE: usize = 2_000;
+
+    let mut all_files = Vec::new();
+    let mut cursor = ScanCursor::default();
+    let mut stats = ScanStats::default();
+
+    loop {
+        let (page, next_cursor, returned_stats) = scan_files_simplified(
+            config,
+            monitored_folders,
+            time_range.clone(),
+            file_type.clone(),
+            sniff_content,
+            cursor,
+            COLLECT_PAGE_SIZE,
+            stats,
+        )
+        .await?;
+
+        stats = returned_stats;
+        all_files.extend(page);
+
+        match next_cursor {
+            Some(c) => cursor = c,
+            None => break,
+        }
+    }
+
+    Ok(all_files)
 }
@@ -0,0 +1,215 @@
+//! 层级化的 `.gitignore`/`.ignore`/`.leafignore` 匹配器。
+//!
+//! 扫描白名单目录时，逐级发现并解析 `.gitignore`/`.ignore`/`.leafignore`
+//! 文件，编译成一个
+//! "匹配器栈"：越深层目录的规则相对浅层规则有更高优先级（与 git 自身的语义
+//! 一致），支持取反模式（`!foo`）、锚定路径（`/foo` 只匹配当前目录下的
+//! `foo`）和 `**` 通配符。扫描到某个路径时，从栈顶（最深层）向下找到第一条
+//! 匹配的规则，由它决定该路径是否被忽略。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 单条编译后的忽略规则。
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    /// 取反规则（`!pattern`），匹配到时表示"不忽略"而不是"忽略"。
+    negated: bool,
+    /// 只匹配目录（原始模式以 `/` 结尾）。
+    dir_only: bool,
+    /// 锚定到声明该规则的目录（原始模式包含非末尾的 `/`，或以 `/` 开头）。
+    anchored: bool,
+    /// 编译后的 glob（转换为等价正则表达式），相对声明该规则的目录匹配。
+    glob: regex::Regex,
+}
+
+/// 某一层目录（声明了 `.gitignore`/`.ignore` 的目录）编译出的规则集合。
+#[derive(Debug, Clone)]
+struct IgnoreLevel {
+    /// 声明这些规则的目录，规则里的相对路径都相对这里计算。
+    base_dir: PathBuf,
+    patterns: Vec<IgnorePattern>,
+}
+
+/// 从根目录到叶子目录逐级收集到的匹配器栈，越靠后的层级越深、优先级越高。
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreStack {
+    levels: Vec<IgnoreLevel>,
+}
+
+impl IgnoreStack {
+    /// 从 `root` 开始，沿着 `root` 到 `leaf_dir`（含两端）逐级读取
+    /// `.gitignore`/`.ignore` 并编译。`leaf_dir` 必须是 `root` 的子孙目录。
+    pub fn build(root: &Path, leaf_dir: &Path) -> Self {
+        let mut dirs = Vec::new();
+        let mut current = Some(leaf_dir.to_path_buf());
+        while let Some(dir) = current {
+            dirs.push(dir.clone());
+            if dir == root {
+                break;
+            }
+            current = dir.parent().map(|p| p.to_path_buf());
+        }
+        dirs.reverse(); // root 在前，leaf 在后 -> 浅层在前，深层在后
+
+        let levels = dirs
+            .into_iter()
+            .filter_map(|dir| Self::load_level(&dir))
+            .collect();
+
+        IgnoreStack { levels }
+    }
+
+    fn load_level(dir: &Path) -> Option<IgnoreLevel> {
+        let mut patterns = Vec::new();
+        // `.leafignore` 是本应用专用的忽略文件，规则语法和 `.gitignore`/
+        // `.ignore` 完全一致；放在最后读取，所以同一目录下三个文件都存在时
+        // `.leafignore` 里的规则排在最后，对同一路径的判定优先级最高
+        // （`is_ignored` 从每层规则列表的末尾往前找第一条匹配的规则）。
+        for name in [".gitignore", ".ignore", ".leafignore"] {
+            let file = dir.join(name);
+            if let Ok(content) = fs::read_to_string(&file) {
+                patterns.extend(parse_ignore_file(&content));
+            }
+        }
+        if patterns.is_empty() {
+            None
+        } else {
+            Some(IgnoreLevel {
+                base_dir: dir.to_path_buf(),
+                patterns,
+            })
+        }
+    }
+
+    /// 判断 `path` 是否应当被忽略。从最深层开始向浅层找第一条匹配的规则：
+    /// 找到就用它的 negated 标记决定结果；如果任何层级都没匹配，则不忽略。
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        for level in self.levels.iter().rev() {
+            let Ok(relative) = path.strip_prefix(&level.base_dir) else {
+                continue;
+            };
+            if relative.as_os_str().is_empty() {
+                continue;
+            }
+            let relative_str = relative.to_string_lossy().replace('\\', "/");
+
+            for pattern in level.patterns.iter().rev() {
+                if pattern.dir_only && !is_dir {
+                    continue;
+                }
+                let candidate: &str = if pattern.anchored {
+                    &relative_str
+                } else {
+                    // 未锚定的规则可以匹配任意层级，用文件名本身再试一次
+                    relative_str
+                        .rsplit('/')
+                        .next()
+                        .unwrap_or(relative_str.as_str())
+                };
+                if pattern.glob.is_match(candidate) || pattern.glob.is_match(&relative_str) {
+                    return !pattern.negated;
+                }
+            }
+        }
+        false
+    }
+}
+
+fn parse_ignore_file(content: &str) -> Vec<IgnorePattern> {
+    content
+        .lines()
+        .filter_map(|raw_line| {
+            let line = raw_line.trim_end();
+            if line.is_empty() || line.trim_start().starts_with('#') {
+                return None;
+            }
+
+            let mut pattern = line;
+            let negated = pattern.starts_with('!');
+            if negated {
+                pattern = &pattern[1..];
+            }
+
+            let dir_only = pattern.ends_with('/');
+            if dir_only {
+                pattern = &pattern[..pattern.len() - 1];
+            }
+
+            let anchored = pattern.starts_with('/') || pattern.contains('/');
+            let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+
+            if pattern.is_empty() {
+                return None;
+            }
+
+            let glob = regex::Regex::new(&glob_to_regex(pattern)).ok()?;
+
+            Some(IgnorePattern {
+                negated,
+                dir_only,
+                anchored,
+                glob,
+            })
+        })
+        .collect()
+}
+
+impl IgnoreStack {
+    /// 在栈的最外层（比 `root` 本身还浅，优先级最低）插入一组全局忽略
+    /// 模式——来自 `FileScanningConfig::ignore_patterns`，由后台配置下发，
+    /// 不依赖磁盘上的 ignore 文件，对这个 `IgnoreStack` 覆盖的所有监控根
+    /// 目录都生效（一条不含 `/` 的模式会在任意层级匹配文件名，等价于
+    /// "到处忽略 `*.tmp`"）。语法和 `.gitignore` 完全一样，直接复用同一套
+    /// 解析器。因为 [`is_ignored`](Self::is_ignored) 总是从最深层开始找，
+    /// 这里插入的规则可以被任何更深层 `.gitignore`/`.ignore`/`.leafignore`
+    /// 里的规则（包括取反规则）覆盖。
+    pub fn with_global_patterns(mut self, base_dir: &Path, patterns: &[String]) -> Self {
+        let compiled = parse_ignore_file(&patterns.join("\n"));
+        if !compiled.is_empty() {
+            self.levels.insert(
+                0,
+                IgnoreLevel {
+                    base_dir: base_dir.to_path_buf(),
+                    patterns: compiled,
+                },
+            );
+        }
+        self
+    }
+}
+
+/// 把一条 gitignore 风格的 glob 模式转换成等价的、锚定到整串的正则表达式：
+/// `**` 匹配任意层级（含 `/`），单个 `*`/`?` 不跨越 `/`，其余字符按字面转义。
+fn glob_to_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    regex.push_str(".*");
+                    i += 2;
+                    // 跳过紧随其后的 `/`，让 `**/foo` 同时匹配 `foo` 自身
+                    if i < chars.len() && chars[i] == '/' {
+                        i += 1;
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '?' => {
+                regex.push_str("[^/]");
+                i += 1;
+            }
+            c => {
+                regex.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
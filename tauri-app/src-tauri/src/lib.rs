@@ -1,30 +1,104 @@
 mod api_startup; // API启动模块
+mod archive_scan; // 手写 ZIP 中央目录读取，用于展开归档内部成员，见 file_monitor::process_file_event
 mod commands;
+mod content_chunker; // 内容定义分块（CDC）滚动哈希分块器，用于变更检测/去重
+mod diagnostics_export; // 把桥接事件/缓冲器生命周期事件批量导出到外部可观测性后端，见模块头注释
+mod duplicate_detector; // 按大小->局部哈希->全文件哈希三段式查重，见 file_scanner::find_duplicates 的姊妹实现
+mod errors; // 结构化的 AppError 命令错误类型（code/message/kind）
 mod event_buffer;
+mod event_metrics; // EventBuffer 的计数器/gauge/延迟直方图，见模块头注释
+mod file_id_tracker; // 单次 stat 探测文件身份/存在性，见 file_monitor_debounced 里的用法
 mod file_monitor;
 mod file_monitor_debounced; // 防抖动文件监控模块
 mod file_scanner; // 文件扫描模块
+mod ignore_matcher; // .gitignore/.ignore 层级匹配器
+mod integrity_check; // 按扩展名分类的文件结构完整性探测（图片/ZIP家族/PDF/音频）
+mod local_config; // 支持 %include/%unset 的层级化本地配置，合并到 API 配置之上
+mod log_sink; // 手写的按大小轮转文件日志落盘器，见 api_startup 对 sidecar 日志的落盘
+mod magic_bytes; // 基于文件头部字节的内容类型嗅探
+mod path_filter; // 按文件夹配置的 allow/ignore glob 过滤层
+mod pattern_identify; // 稀有度加权的模式识别引擎（独立模块，详见文件头注释）
+mod payload_compression; // 批量上传元数据用的手写 LZSS 压缩器，见 file_monitor::send_batch_metadata_to_api
+mod process_tree; // 跨平台进程树终止
+mod python_rpc; // Rust↔Python 双向RPC：在既有单向桥接事件之上叠加请求/响应配对
+mod resource_limits; // 对已启动的 sidecar 进程尽力而为地施加内存/CPU/文件描述符限制
+mod scan_cache; // 按(path, size, mtime)跳过未变化文件的持久化扫描缓存
+mod search_overlay; // Spotlight 风格快速搜索悬浮窗
+mod selection_capture; // 全局快捷键划词抓取
+mod service_controller; // 多 sidecar 服务的统一登记表：register/stop/status，见模块头注释
 mod setup_file_monitor; // 事件缓冲模块
+mod shutdown; // 统一关闭信号子系统
+mod sync_manifest; // 记录上次 `uv sync` 所用 pyproject.toml 哈希的清单，见 api_startup 对启动同步的跳过逻辑
+mod tag_search_cache; // 标签搜索/标签云结果的带版本号内存缓存
+mod task_registry; // 配置变更队列的任务跟踪表（uid/状态/时间戳）
+mod tray_menu; // 动态重建的托盘菜单
+mod watch_exclusions; // 防抖实时监控用的 glob/字面前缀排除规则，见 file_monitor_debounced
+mod window_tiling; // DPI 感知的跨平台窗口分区
 
 use file_monitor::FileMonitor;
 use file_monitor_debounced::DebouncedFileMonitor;
 use reqwest;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 use tauri::Emitter;
 use tauri::Manager;
 use tauri::{
     menu::{Menu, MenuItem, PredefinedMenuItem, Submenu},
-    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
     WindowEvent,
 };
 use tokio::time::{sleep, Duration};
 
+/// API子进程的监督状态机：Running -> Crashed -> Restarting -> Running，
+/// 达到重启上限后进入 Failed，不再自动恢复。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiHealthState {
+    Running,
+    Crashed,
+    Restarting,
+    Failed,
+}
+
 // 存储API进程的状态
-struct ApiProcessState {
+pub struct ApiProcessState {
     process_child: Option<tauri_plugin_shell::process::CommandChild>,
     port: u16,
     host: String,
     db_path: String,
+    // 监督相关字段：用于崩溃检测与指数退避重启
+    pid: Option<u32>,
+    health: ApiHealthState,
+    // 重启时间戳环形缓冲：只保留滑动窗口内可能用得上的最近几次重启时间，
+    // 超过 MAX_RESTARTS_IN_WINDOW 条就把最旧的一条挤出去
+    restart_timestamps: std::collections::VecDeque<std::time::Instant>,
+    // sidecar 的资源限制配置，见 `resource_limits` 模块；默认全 `None`
+    // （不限制），只有显式设置过的字段才会在进程启动后被施加
+    pub resource_limits: crate::resource_limits::ResourceLimits,
+}
+
+impl ApiProcessState {
+    /// 供 `python_rpc::PythonRpcClient` 写入RPC请求用：子进程存活时拿到它
+    /// stdin 的可变引用，不存在（还没启动/已经死亡）时返回 `None`。
+    pub(crate) fn process_child_mut(
+        &mut self,
+    ) -> Option<&mut tauri_plugin_shell::process::CommandChild> {
+        self.process_child.as_mut()
+    }
+
+    /// 供 `service_controller::ServiceController` 在停止服务前读取待终止的 PID。
+    pub(crate) fn pid(&self) -> Option<u32> {
+        self.pid
+    }
+
+    /// 把进程相关字段复位为"已停止"：清掉子进程句柄/PID，标记为
+    /// `Crashed`。由 `api_startup::mark_process_dead`（收到
+    /// `Terminated`/`Error` 事件时）和
+    /// `service_controller::ServiceController::stop`（主动停止时）共用，
+    /// 避免两处各自维护一份同样的复位逻辑。
+    pub(crate) fn reset_after_stop(&mut self) {
+        self.process_child = None;
+        self.pid = None;
+        self.health = ApiHealthState::Crashed;
+    }
 }
 
 // API进程管理器，用于应用退出时自动清理资源
@@ -32,108 +106,100 @@ struct ApiProcessManager {
     api_state: Arc<Mutex<ApiProcessState>>,
 }
 
+/// `/shutdown` 握手通过后，允许进程自行退出的宽限期；超过这个时长仍然
+/// 存活就不再等，退化到下面的 SIGTERM/SIGKILL 进程树终止。
+const GRACEFUL_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_millis(5000);
+/// 宽限期内轮询进程是否已退出的间隔
+const GRACEFUL_SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
 impl ApiProcessManager {
     /// 实例清理方法，执行完整的清理逻辑
     pub fn cleanup(&self) {
         println!("执行ApiProcessManager完整清理");
         eprintln!("执行ApiProcessManager完整清理"); // 同时输出到 stderr
 
-        // 尝试获取并终止 API 进程
-        if let Ok(mut api_state) = self.api_state.lock() {
+        // 尝试获取并终止 API 进程；只有在拿不到锁、或者压根没有记录下 PID 时
+        // 才退化到按命令行模式匹配的静态清理——已知 PID 的情况下，
+        // `kill_process_tree` 精确地只终止我们自己这棵子树，不应该再额外跑一遍
+        // 可能误杀同机其它匹配进程的 `pkill`。
+        let targeted_cleanup_done = if let Ok(mut api_state) = self.api_state.lock() {
             if let Some(child) = api_state.process_child.take() {
                 println!("通过实例方法终止 uv 和 Python API 进程树");
 
-                // 由于使用 uv 启动，需要终止整个进程树
-                // 先尝试获取进程ID用于进程树清理
-                let child_pid = child.pid();
+                // 由于使用 uv 启动，需要终止整个进程树（uv 本身及其启动的 Python 子进程）
+                let child_pid = api_state.pid.unwrap_or_else(|| child.pid());
+                let host = api_state.host.clone();
+                let port = api_state.port;
                 println!("uv 进程 PID: {}", child_pid);
+                api_state.pid = None;
 
-                // 尝试终止 uv 进程（这会终止直接子进程，但不一定终止孙进程）
-                match child.kill() {
-                    Ok(_) => {
-                        println!("发送终止信号到 uv 进程成功");
+                // 释放 CommandChild 的所有权后再做进程树清理，避免持有锁过久
+                drop(child);
 
-                        // 等待短暂时间让进程响应信号
-                        std::thread::sleep(std::time::Duration::from_millis(1000));
-                    }
-                    Err(e) => {
-                        eprintln!("终止 uv 进程失败: {}", e);
-                    }
+                // 直接 SIGKILL 有砸坏正在写 knowledge-focus.db 的风险，先给
+                // Python 侧一个自己干净关闭的机会（见 attempt_graceful_shutdown
+                // 说明）；只有宽限期内仍未退出才落到强制终止。
+                if !Self::attempt_graceful_shutdown(&host, port, child_pid) {
+                    crate::process_tree::kill_process_tree(child_pid);
+                    println!("API 进程树终止完成");
                 }
-
-                // // 在Unix系统上，强制清理整个进程树和相关进程
-                // #[cfg(unix)]
-                // {
-                //     println!("开始清理 uv 和 Python 进程树");
-
-                //     // 1. 首先尝试通过进程组终止（如果 uv 创建了进程组）
-                //     println!("尝试终止进程组...");
-                //     let _ = std::process::Command::new("pkill")
-                //         .args(["-g", &child_pid.to_string()])
-                //         .status();
-
-                //     // 2. 使用 pgrep 找到所有 uv 相关的子进程并终止
-                //     println!("查找并终止 uv 的所有子进程...");
-                //     if let Ok(output) = std::process::Command::new("pgrep")
-                //         .args(["-P", &child_pid.to_string()])
-                //         .output() {
-                //         let children_pids = String::from_utf8_lossy(&output.stdout);
-                //         for pid_str in children_pids.lines() {
-                //             if let Ok(pid) = pid_str.parse::<u32>() {
-                //                 println!("终止子进程 PID: {}", pid);
-                //                 let _ = std::process::Command::new("kill")
-                //                     .args(["-TERM", &pid.to_string()])
-                //                     .status();
-                //             }
-                //         }
-                //     }
-
-                //     // 3. 等待一下后强制终止
-                //     std::thread::sleep(std::time::Duration::from_millis(500));
-
-                //     // 4. 使用精确的进程命令行匹配来清理 Python 进程
-                //     println!("使用命令行匹配清理 Python 进程...");
-                //     let cleanup_patterns = [
-                //         "main.py --host 127.0.0.1 --port 60315",
-                //         "/api/main.py",
-                //         "knowledge-focus.db",
-                //     ];
-
-                //     for pattern in &cleanup_patterns {
-                //         println!("清理匹配模式: {}", pattern);
-                //         // 先发送 SIGTERM
-                //         let _ = std::process::Command::new("pkill")
-                //             .args(["-f", pattern])
-                //             .status();
-                //     }
-
-                //     // 5. 等待后强制终止
-                //     std::thread::sleep(std::time::Duration::from_millis(1000));
-                //     for pattern in &cleanup_patterns {
-                //         let _ = std::process::Command::new("pkill")
-                //             .args(["-9", "-f", pattern])
-                //             .status();
-                //     }
-
-                //     // 6. 最后清理 uv 进程本身（以防还在运行）
-                //     println!("最终清理 uv 进程: {}", child_pid);
-                //     let _ = std::process::Command::new("kill")
-                //         .args(["-9", &child_pid.to_string()])
-                //         .status();
-
-                //     println!("进程树清理完成");
-                // }
-
-                println!("API 进程树终止完成");
+                true
             } else {
                 println!("没有需要终止的 API 进程");
+                false
             }
         } else {
             eprintln!("无法获取 API 状态互斥锁");
+            false
+        };
+
+        if !targeted_cleanup_done {
+            // 退化到按命令行模式匹配的静态清理（见 cleanup_processes_static 说明）
+            Self::cleanup_processes_static();
         }
+    }
 
-        // 执行静态清理作为后备
-        Self::cleanup_processes_static();
+    /// 在强制终止进程树之前，先 POST `/shutdown` 给 Python 侧一个自己干净
+    /// 退出的机会（刷盘、关掉 SQLite 连接），再轮询进程是否已经自行退出，
+    /// 最多等 `GRACEFUL_SHUTDOWN_GRACE_PERIOD`。返回 `true` 代表进程已经
+    /// 自行退出，调用方不需要再走 SIGTERM/SIGKILL；连不上、sidecar 没实现
+    /// 这个端点、或者宽限期耗尽仍存活，都返回 `false`，退化到已有的强制
+    /// 终止路径——这些都不是错误，只是优雅关闭没有生效。
+    fn attempt_graceful_shutdown(host: &str, port: u16, pid: u32) -> bool {
+        let url = format!("http://{}:{}/shutdown", host, port);
+        let request_sent = tauri::async_runtime::block_on(async {
+            let client = match reqwest::Client::builder()
+                .timeout(Duration::from_secs(2))
+                .build()
+            {
+                Ok(client) => client,
+                Err(e) => {
+                    eprintln!("[API_CLEANUP] 构建 /shutdown 请求客户端失败: {}", e);
+                    return false;
+                }
+            };
+            client.post(&url).send().await.is_ok()
+        });
+
+        if !request_sent {
+            println!("[API_CLEANUP] /shutdown 请求失败或 sidecar 未响应，跳过优雅关闭宽限期");
+            return false;
+        }
+
+        println!(
+            "[API_CLEANUP] 已发送 /shutdown，等待进程自行退出（最多 {:?}）",
+            GRACEFUL_SHUTDOWN_GRACE_PERIOD
+        );
+        let deadline = std::time::Instant::now() + GRACEFUL_SHUTDOWN_GRACE_PERIOD;
+        while std::time::Instant::now() < deadline {
+            if !crate::process_tree::is_alive(pid) {
+                println!("[API_CLEANUP] 进程已在宽限期内自行退出，跳过强制终止");
+                return true;
+            }
+            std::thread::sleep(GRACEFUL_SHUTDOWN_POLL_INTERVAL);
+        }
+        println!("[API_CLEANUP] 宽限期耗尽，进程仍存活，退化到强制终止");
+        false
     }
 
     /// 静态清理方法，可以在任何地方调用（后备清理）
@@ -141,57 +207,42 @@ impl ApiProcessManager {
         Self::cleanup_processes_static();
     }
 
-    /// 静态清理的实际实现
+    /// 静态清理的实际实现。只有在 `cleanup()` 未能通过记录的 PID 清理（例如
+    /// panic hook 在状态被破坏前触发）时才会退化到这里，因此仍保留原有的
+    /// 命令行匹配作为最后一道防线。
     fn cleanup_processes_static() {
-        println!("执行静态进程清理");
-        eprintln!("执行静态进程清理"); // 同时输出到 stderr
+        println!("执行静态进程清理（后备）");
+        eprintln!("执行静态进程清理（后备）");
 
-        // 在Unix系统上，强制清理所有相关的进程
         #[cfg(unix)]
         {
-            println!("开始强制清理所有相关的 uv 和 Python 进程");
-
-            // 使用多种模式确保清理干净，包括 uv 进程
+            // 不再包含具体端口号：端口现在是动态探测分配的，写死端口的
+            // 匹配模式在端口跳号后就再也匹配不到残留进程了
             let cleanup_patterns = [
                 "uv run --directory",
-                "main.py --host 127.0.0.1 --port 60315",
+                "main.py --host 127.0.0.1",
                 "/api/main.py",
                 "knowledge-focus.db",
             ];
 
             for pattern in &cleanup_patterns {
-                println!("清理模式: {}", pattern);
-
-                // 先发送SIGTERM
-                match std::process::Command::new("pkill")
+                let _ = std::process::Command::new("pkill")
                     .args(["-f", pattern])
-                    .status()
-                {
-                    Ok(status) => {
-                        println!("SIGTERM 发送结果: {:?}", status);
-                    }
-                    Err(e) => {
-                        println!("SIGTERM 发送失败: {}", e);
-                    }
-                }
-
-                // 等待一秒后发送SIGKILL
-                std::thread::sleep(std::time::Duration::from_millis(1000));
-                match std::process::Command::new("pkill")
+                    .status();
+            }
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            for pattern in &cleanup_patterns {
+                let _ = std::process::Command::new("pkill")
                     .args(["-9", "-f", pattern])
-                    .status()
-                {
-                    Ok(status) => {
-                        println!("SIGKILL 发送结果: {:?}", status);
-                    }
-                    Err(e) => {
-                        println!("SIGKILL 发送失败: {}", e);
-                    }
-                }
+                    .status();
             }
+        }
 
-            println!("静态进程清理完成");
-            eprintln!("静态进程清理完成");
+        #[cfg(windows)]
+        {
+            let _ = std::process::Command::new("taskkill")
+                .args(["/F", "/IM", "uv.exe", "/T"])
+                .status();
         }
     }
 }
@@ -217,8 +268,33 @@ pub struct AppState {
     file_monitor: Arc<Mutex<Option<FileMonitor>>>,
     debounced_file_monitor: Arc<Mutex<Option<DebouncedFileMonitor>>>,
     // 配置变更队列管理
-    pending_config_changes: Arc<Mutex<Vec<ConfigChangeRequest>>>,
+    pending_config_changes: Arc<Mutex<Vec<QueuedChange>>>,
+    // 配置变更队列的任务跟踪表，见 task_registry 模块
+    config_change_tasks: Arc<task_registry::TaskRegistry>,
+    // 标签搜索/标签云结果缓存，见 tag_search_cache 模块
+    tag_search_cache: Arc<tag_search_cache::TagSearchCache>,
     initial_scan_completed: Arc<Mutex<bool>>,
+    // 按文件夹路径记录是否启用 .gitignore/.ignore 忽略文件支持（纯本地开关，
+    // 不随 Python API 的配置快照一起下发，因此不走 pending_config_changes 队列）
+    ignore_files_enabled_for: Arc<Mutex<std::collections::HashSet<String>>>,
+    // 按文件夹路径记录 allow/ignore glob 过滤配置，同样是纯本地开关
+    path_filters: Arc<Mutex<std::collections::HashMap<String, path_filter::PathFilterConfig>>>,
+    // 当前使用中的事件缓冲器，供优雅关闭流程在退出前排空
+    event_buffer: Arc<Mutex<Option<Arc<event_buffer::EventBuffer>>>>,
+    // 当前使用中的 Rust→Python RPC 客户端，见 python_rpc 模块
+    python_rpc: Arc<Mutex<Option<python_rpc::PythonRpcClient>>>,
+    // 托盘图标句柄，供动态重建菜单时替换内容（见 tray_menu 模块）
+    tray_icon: Arc<Mutex<Option<tauri::tray::TrayIcon>>>,
+    // 文件监控是否被用户通过托盘/前端手动暂停
+    monitoring_paused: Arc<Mutex<bool>>,
+    // 最近索引的文件路径，供托盘菜单"Recently indexed"子菜单展示
+    recent_activity: Arc<Mutex<std::collections::VecDeque<String>>>,
+    // 多 sidecar 服务的统一登记表，见 service_controller 模块
+    service_controller: Arc<service_controller::ServiceController>,
+    // 生命周期事件（api-ready/file-monitor-error/api-fatal……）的最新状态
+    // 存储，供晚注册监听器的窗口通过 sync_lifecycle 补发，见 event_buffer
+    // 模块里 LifecycleEventStore 的说明
+    lifecycle_events: Arc<event_buffer::LifecycleEventStore>,
 }
 
 impl AppState {
@@ -229,10 +305,135 @@ impl AppState {
             file_monitor: Arc::new(Mutex::new(None)),
             debounced_file_monitor: Arc::new(Mutex::new(None)), // 初始化新字段
             pending_config_changes: Arc::new(Mutex::new(Vec::new())), // 初始化配置变更队列
+            config_change_tasks: Arc::new(task_registry::TaskRegistry::new()),
+            tag_search_cache: Arc::new(tag_search_cache::TagSearchCache::new()),
             initial_scan_completed: Arc::new(Mutex::new(false)), // 初始化扫描完成标志
+            ignore_files_enabled_for: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            path_filters: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            event_buffer: Arc::new(Mutex::new(None)),
+            python_rpc: Arc::new(Mutex::new(None)),
+            tray_icon: Arc::new(Mutex::new(None)),
+            monitoring_paused: Arc::new(Mutex::new(false)),
+            recent_activity: Arc::new(Mutex::new(std::collections::VecDeque::new())),
+            service_controller: Arc::new(service_controller::ServiceController::new()),
+            lifecycle_events: Arc::new(event_buffer::LifecycleEventStore::new()),
+        }
+    }
+
+    /// 注册托盘图标句柄，供后续 `tray_menu::rebuild` 替换菜单内容。
+    pub fn set_tray_icon(&self, tray_icon: tauri::tray::TrayIcon) {
+        *self.tray_icon.lock().unwrap() = Some(tray_icon);
+    }
+
+    pub fn get_tray_icon(&self) -> Option<tauri::tray::TrayIcon> {
+        self.tray_icon.lock().unwrap().clone()
+    }
+
+    pub fn is_monitoring_paused(&self) -> bool {
+        *self.monitoring_paused.lock().unwrap()
+    }
+
+    pub fn set_monitoring_paused(&self, paused: bool) {
+        *self.monitoring_paused.lock().unwrap() = paused;
+    }
+
+    /// 记录一批最近被索引/扫描到的文件路径，最多保留最近
+    /// `RECENT_ACTIVITY_LIMIT`（见 tray_menu 模块）条，超出的旧记录被挤出。
+    pub fn record_recent_activity(&self, paths: impl IntoIterator<Item = String>) {
+        let mut recent = self.recent_activity.lock().unwrap();
+        for path in paths {
+            recent.push_front(path);
+            while recent.len() > tray_menu::RECENT_ACTIVITY_LIMIT {
+                recent.pop_back();
+            }
+        }
+    }
+
+    pub fn recent_activity_snapshot(&self) -> Vec<String> {
+        self.recent_activity.lock().unwrap().iter().cloned().collect()
+    }
+
+    /// 注册当前使用中的事件缓冲器，供关闭流程排空。
+    pub fn set_event_buffer(&self, buffer: Arc<event_buffer::EventBuffer>) {
+        *self.event_buffer.lock().unwrap() = Some(buffer);
+    }
+
+    pub fn get_event_buffer(&self) -> Option<Arc<event_buffer::EventBuffer>> {
+        self.event_buffer.lock().unwrap().clone()
+    }
+
+    /// 注册当前使用中的 Rust→Python RPC 客户端，供命令层调用 `call_python`。
+    pub fn set_python_rpc(&self, client: python_rpc::PythonRpcClient) {
+        *self.python_rpc.lock().unwrap() = Some(client);
+    }
+
+    pub fn get_python_rpc(&self) -> Option<python_rpc::PythonRpcClient> {
+        self.python_rpc.lock().unwrap().clone()
+    }
+
+    /// 多 sidecar 服务的统一登记表，供 `api_startup` 登记/更新服务状态，
+    /// 供命令层/优雅关闭流程统一查询或停止。
+    pub fn service_controller(&self) -> Arc<service_controller::ServiceController> {
+        self.service_controller.clone()
+    }
+
+    /// 生命周期事件的最新状态存储，供 `commands::sync_lifecycle` 在前端
+    /// 重新注册监听器后补发可能错过的 `api-ready`/`file-monitor-error`/
+    /// `api-fatal`。
+    pub fn lifecycle_events(&self) -> Arc<event_buffer::LifecycleEventStore> {
+        self.lifecycle_events.clone()
+    }
+
+    /// 为某个文件夹开启/关闭 `.gitignore`/`.ignore`/`.leafignore` 忽略文件
+    /// 支持。同步推送到当前的 `FileMonitor`（如果已初始化），使实时监控的
+    /// 过滤路径（`FileMonitor::is_in_blacklist`）也遵循这个开关，而不是只
+    /// 对按需扫描（`file_scanner`）生效。
+    pub fn set_ignore_files_enabled(&self, folder_path: String, enabled: bool) {
+        let snapshot = {
+            let mut set = self.ignore_files_enabled_for.lock().unwrap();
+            if enabled {
+                set.insert(folder_path);
+            } else {
+                set.remove(&folder_path);
+            }
+            set.clone()
+        };
+        if let Some(monitor) = self.file_monitor.lock().unwrap().as_ref() {
+            monitor.set_ignore_files_enabled_for(snapshot);
+        }
+    }
+
+    /// 查询某个文件夹是否启用了 `.gitignore`/`.ignore` 忽略文件支持。
+    pub fn is_ignore_files_enabled(&self, folder_path: &str) -> bool {
+        self.ignore_files_enabled_for
+            .lock()
+            .unwrap()
+            .contains(folder_path)
+    }
+
+    /// 获取当前所有启用了 `.gitignore`/`.ignore` 支持的文件夹路径快照。
+    pub fn ignore_files_enabled_paths(&self) -> std::collections::HashSet<String> {
+        self.ignore_files_enabled_for.lock().unwrap().clone()
+    }
+
+    /// 设置（或清空）某个文件夹的 allow/ignore glob 过滤配置。传入一个
+    /// allow 和 ignore 都为空的配置等价于移除该文件夹的过滤层。
+    pub fn set_path_filter(&self, folder_path: String, config: path_filter::PathFilterConfig) {
+        let mut filters = self.path_filters.lock().unwrap();
+        if config.allow.is_empty() && config.ignore.is_empty() {
+            filters.remove(&folder_path);
+        } else {
+            filters.insert(folder_path, config);
         }
     }
 
+    /// 获取当前所有文件夹的 allow/ignore glob 过滤配置快照。
+    pub fn path_filters_snapshot(
+        &self,
+    ) -> std::collections::HashMap<String, path_filter::PathFilterConfig> {
+        self.path_filters.lock().unwrap().clone()
+    }
+
     pub async fn get_config(&self) -> Result<file_monitor::AllConfigurations, String> {
         let config_guard = self.config.lock().unwrap();
         match &*config_guard {
@@ -256,16 +457,34 @@ impl AppState {
     }
 
     pub fn update_simplified_config(&self, config: file_monitor::FileScanningConfig) {
+        if let Some(monitor) = self.file_monitor.lock().unwrap().as_ref() {
+            monitor.set_content_chunking(
+                config.content_chunking_enabled,
+                config.content_chunking_target_size_kb,
+            );
+            monitor.set_batch_compression(
+                config.batch_compression_enabled,
+                config.batch_compression_level,
+            );
+            monitor.set_global_ignore_patterns(config.ignore_patterns.clone());
+            monitor.set_archive_scanning(
+                config.archive_scanning_enabled,
+                config.archive_scan_max_members,
+                config.archive_scan_max_total_uncompressed_bytes,
+            );
+            monitor.set_duplicate_detection_enabled(config.duplicate_detection_enabled);
+        }
         let mut config_guard = self.simplified_config.lock().unwrap();
         *config_guard = Some(config);
     }
 
     // 刷新简化配置（从API获取最新配置）
-    pub async fn refresh_simplified_config(&self) -> Result<(), String> {
+    pub async fn refresh_simplified_config(&self, api_host: &str, api_port: u16) -> Result<(), String> {
         println!("[CONFIG] 开始刷新简化配置");
 
-        // 创建临时的FileMonitor实例来获取配置
-        let temp_monitor = file_monitor::FileMonitor::new("127.0.0.1".to_string(), 60315);
+        // 创建临时的FileMonitor实例来获取配置，端口来自实际解析出的 api_state，
+        // 而不是硬编码 60315（否则端口被动态探测重新分配后这里就会连不上）
+        let temp_monitor = file_monitor::FileMonitor::new(api_host.to_string(), api_port);
 
         match temp_monitor.fetch_file_scanning_config().await {
             Ok(config) => {
@@ -304,11 +523,87 @@ impl AppState {
         }
     }
 
-    /// 添加配置变更请求到队列
-    pub fn add_pending_config_change(&self, change: ConfigChangeRequest) {
+    /// 添加配置变更请求到队列，同时在任务登记表里创建一条对应记录，返回
+    /// 分配给这次请求的任务 uid，供调用方（Tauri 命令）立即回传给前端，
+    /// 用于之后用 [`Self::get_config_change_task`]/[`Self::list_config_change_tasks`] 轮询进度。
+    pub fn add_pending_config_change(&self, change: ConfigChangeRequest) -> u64 {
+        let uid = self.config_change_tasks.enqueue(&change);
         let mut pending_changes = self.pending_config_changes.lock().unwrap();
-        pending_changes.push(change.clone());
-        println!("[CONFIG_QUEUE] 添加配置变更到队列: {:?}", change);
+        println!(
+            "[CONFIG_QUEUE] 添加配置变更到队列 (task_uid={}): {:?}",
+            uid, change
+        );
+        pending_changes.push(QueuedChange { uid, request: change });
+        uid
+    }
+
+    /// 查询单个配置变更任务的当前记录。
+    pub fn get_config_change_task(&self, uid: u64) -> Option<task_registry::ConfigChangeTask> {
+        self.config_change_tasks.get(uid)
+    }
+
+    /// 按 uid 游标分页列出配置变更任务记录，可选按状态过滤。
+    pub fn list_config_change_tasks(
+        &self,
+        status_filter: Option<task_registry::TaskStatus>,
+        limit: usize,
+        after_uid: Option<u64>,
+    ) -> Vec<task_registry::ConfigChangeTask> {
+        self.config_change_tasks.list(status_filter, limit, after_uid)
+    }
+
+    /// 从待处理队列里撤回一个尚未被调度器取走执行的变更，对应任务记录标记
+    /// 为 `Canceled`（而不是 `Failed`——用户主动撤销不是一次执行失败）。
+    /// 返回是否真的找到并移除了；uid 不存在，或者已经被调度器取走开始
+    /// 执行（不再在 `pending_config_changes` 里），都返回 `false`。
+    pub fn cancel_pending_config_change(&self, uid: u64) -> bool {
+        let mut pending = self.pending_config_changes.lock().unwrap();
+        match pending.iter().position(|queued| queued.uid == uid) {
+            Some(pos) => {
+                pending.remove(pos);
+                drop(pending);
+                self.config_change_tasks
+                    .mark_canceled(uid, "被用户撤销，未执行".to_string());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 把一个仍在排队的变更挪到新的位置（`new_index` 为 0 表示排到最先
+    /// 执行；超出队列长度会被夹到末尾）。返回是否找到了该 uid。
+    pub fn reorder_pending_config_change(&self, uid: u64, new_index: usize) -> bool {
+        let mut pending = self.pending_config_changes.lock().unwrap();
+        match pending.iter().position(|queued| queued.uid == uid) {
+            Some(pos) => {
+                let item = pending.remove(pos);
+                let new_index = new_index.min(pending.len());
+                pending.insert(new_index, item);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 清空所有仍在排队、尚未被调度器取走的变更，逐个标记为 `Canceled`。
+    /// 返回被清空的数量。
+    pub fn clear_pending_config_changes(&self) -> usize {
+        let cleared: Vec<QueuedChange> = {
+            let mut pending = self.pending_config_changes.lock().unwrap();
+            pending.drain(..).collect()
+        };
+        for queued in &cleared {
+            self.config_change_tasks
+                .mark_canceled(queued.uid, "队列被用户清空，未执行".to_string());
+        }
+        cleared.len()
+    }
+
+    /// 标签搜索/标签云结果缓存。命令里用它在请求 sidecar API 前查一次缓存，
+    /// 命中就直接返回；`process_pending_config_changes` 实际应用了会改变
+    /// 被索引文件集合的操作之后，会调用它的 `bump_version` 使旧结果失效。
+    pub fn tag_search_cache(&self) -> &tag_search_cache::TagSearchCache {
+        &self.tag_search_cache
     }
 
     /// 检查是否有待处理的配置变更
@@ -324,6 +619,9 @@ impl AppState {
     }
 
     /// 处理所有待处理的配置变更（由Rust端调用Python API）
+    ///
+    /// 对快照出的这一批变更做合并（同一路径/同一文件夹ID的冗余或互相抵消的
+    /// 操作会被折叠），再交给 [`Self::execute_config_changes`] 执行。
     pub fn process_pending_config_changes(&self) {
         let changes = {
             let mut pending_changes = self.pending_config_changes.lock().unwrap();
@@ -337,25 +635,236 @@ impl AppState {
         }
 
         println!(
-            "[CONFIG_QUEUE] 开始处理 {} 个待处理的配置变更",
+            "[CONFIG_QUEUE] 开始处理 {} 个待处理的配置变更（快照）",
             changes.len()
         );
 
+        let coalesced = Self::coalesce_config_changes(changes, &self.config_change_tasks);
+
+        println!(
+            "[CONFIG_QUEUE] 合并后剩余 {} 个配置变更待执行",
+            coalesced.len()
+        );
+
         // 在独立的异步任务中处理配置变更
-        let changes_clone = changes.clone();
         let file_monitor = self.file_monitor.clone();
+        let pending_config_changes = self.pending_config_changes.clone();
+        let task_registry = self.config_change_tasks.clone();
+        let tag_search_cache = self.tag_search_cache.clone();
+
+        tauri::async_runtime::spawn(async move {
+            Self::execute_config_changes(
+                coalesced,
+                file_monitor,
+                pending_config_changes,
+                task_registry,
+                tag_search_cache,
+            )
+            .await;
+        });
+    }
+
+    /// 合并一批快照出的配置变更，去掉冗余/互相抵消的操作，保持批内剩余变更
+    /// 的相对顺序不变：
+    /// - 同一路径先 `AddBlacklist`/`AddWhitelist` 后 `DeleteFolder` 的，两者抵消；
+    /// - 同一 `folder_id` 的多个 `ToggleFolder` 只保留最后一次，折叠为净状态；
+    /// - 同一路径的 `AddWhitelist` 若被其后的同路径 `AddBlacklist` 取代，则丢弃前者。
+    ///
+    /// 被折叠掉的操作对应的任务记录会通过 `task_registry` 标记为
+    /// `Succeeded`（带一条说明性的 `error` 注记），而不是停留在 `Enqueued`
+    /// 不再更新——否则前端轮询这些任务会永远看到"排队中"。
+    fn coalesce_config_changes(
+        changes: Vec<QueuedChange>,
+        task_registry: &task_registry::TaskRegistry,
+    ) -> Vec<QueuedChange> {
+        use std::collections::HashMap;
+
+        // 找出需要丢弃的"添加后又删除"的 add/delete 配对——按匹配到的具体
+        // 索引记录，而不是按路径整体记录：按路径整体记录的话，一个
+        // 加→删→再加的批次会把最后那次重新添加也一起误删（它和前面那对
+        // add/delete 共享同一个路径，但并不是被它们抵消的那一次）。配对
+        // 上之后就把 `pending_add_idx` 里这个路径的记录清掉，这样后续的
+        // 重新添加会另起一个新的待配对索引，不会被已经了结的那对连累。
+        //
+        // 同一路径连续出现多个 Add（无论类型是否相同）时，只有最后一个
+        // 才是最终生效、可能与后续 Delete 配对的那个——更早的那些在写入
+        // `pending_add_idx` 前就已经被取代，需要在覆盖前先记入
+        // `cancelled_indices`，否则它们既不在这里被丢弃，也不会被下面
+        // 专门处理白名单→黑名单的 `superseded_add_indices` 那一遍捕捉到
+        // （那一遍只认白名单后接黑名单这一种组合），最终会被误执行。
+        let mut cancelled_indices: std::collections::HashSet<usize> =
+            std::collections::HashSet::new();
+        let mut pending_add_idx: HashMap<String, usize> = HashMap::new();
+
+        for (idx, queued) in changes.iter().enumerate() {
+            match &queued.request {
+                ConfigChangeRequest::AddBlacklist { folder_path, .. }
+                | ConfigChangeRequest::AddWhitelist { folder_path, .. } => {
+                    if let Some(prior_idx) = pending_add_idx.insert(folder_path.clone(), idx) {
+                        cancelled_indices.insert(prior_idx);
+                    }
+                }
+                ConfigChangeRequest::DeleteFolder { folder_path, .. } => {
+                    if let Some(add_idx) = pending_add_idx.remove(folder_path) {
+                        cancelled_indices.insert(add_idx);
+                        cancelled_indices.insert(idx);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // 同一路径先加白名单、后加黑名单：白名单那条被取代，丢弃
+        let mut superseded_add_indices: std::collections::HashSet<usize> =
+            std::collections::HashSet::new();
+        let mut last_whitelist_add: HashMap<String, usize> = HashMap::new();
+        for (idx, queued) in changes.iter().enumerate() {
+            match &queued.request {
+                ConfigChangeRequest::AddWhitelist { folder_path, .. } => {
+                    last_whitelist_add.insert(folder_path.clone(), idx);
+                }
+                ConfigChangeRequest::AddBlacklist { folder_path, .. } => {
+                    if let Some(prior_idx) = last_whitelist_add.remove(folder_path) {
+                        superseded_add_indices.insert(prior_idx);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // 同一 folder_id 的多个 ToggleFolder 只保留最后一次
+        let mut last_toggle_for_id: HashMap<i32, usize> = HashMap::new();
+        for (idx, queued) in changes.iter().enumerate() {
+            if let ConfigChangeRequest::ToggleFolder { folder_id, .. } = &queued.request {
+                last_toggle_for_id.insert(*folder_id, idx);
+            }
+        }
+
+        changes
+            .into_iter()
+            .enumerate()
+            .filter(|(idx, queued)| {
+                if superseded_add_indices.contains(idx) {
+                    task_registry.mark_superseded(
+                        queued.uid,
+                        "被同一批次内针对同一路径的后续操作取代，未实际执行".to_string(),
+                    );
+                    return false;
+                }
+                match &queued.request {
+                    ConfigChangeRequest::AddBlacklist { .. }
+                    | ConfigChangeRequest::AddWhitelist { .. }
+                    | ConfigChangeRequest::DeleteFolder { .. } => {
+                        if cancelled_indices.contains(idx) {
+                            task_registry.mark_superseded(
+                                queued.uid,
+                                "与同一批次内针对同一路径的后续操作相抵消，未实际执行".to_string(),
+                            );
+                            false
+                        } else {
+                            true
+                        }
+                    }
+                    ConfigChangeRequest::ToggleFolder { folder_id, .. } => {
+                        if last_toggle_for_id.get(folder_id) == Some(idx) {
+                            true
+                        } else {
+                            task_registry.mark_superseded(
+                                queued.uid,
+                                "被同一批次内针对同一文件夹的后续状态切换取代，未实际执行".to_string(),
+                            );
+                            false
+                        }
+                    }
+                    ConfigChangeRequest::BundleExtensionChange => true,
+                }
+            })
+            .map(|(_, queued)| queued)
+            .collect()
+    }
 
+    /// 启动周期性配置变更调度器：每隔 `tick_interval`（默认约1秒）对
+    /// `pending_config_changes` 做一次快照并处理，而不是每次入队都立即触发
+    /// 一轮 API 调用。这样短时间内连续的多次文件夹操作会被合并进同一批次，
+    /// 减少对 Python API 的请求次数，同时保证清理先于扫描这种批内顺序确定。
+    ///
+    /// 接受裸 `Arc` 字段而不是 `Arc<AppState>`，因为 `AppState` 是由 Tauri
+    /// 的 `.manage()` 持有的（非 `Arc` 包装），这与本文件里其它需要跨异步
+    /// 任务共享状态的静态方法（如 `execute_config_changes`）是同一种写法。
+    pub fn spawn_config_change_scheduler(
+        pending_config_changes: Arc<Mutex<Vec<QueuedChange>>>,
+        initial_scan_completed: Arc<Mutex<bool>>,
+        file_monitor: Arc<Mutex<Option<FileMonitor>>>,
+        task_registry: Arc<task_registry::TaskRegistry>,
+        tag_search_cache: Arc<tag_search_cache::TagSearchCache>,
+        tick_interval: Duration,
+    ) {
         tauri::async_runtime::spawn(async move {
-            Self::execute_config_changes(changes_clone, file_monitor).await;
+            let mut ticker = tokio::time::interval(tick_interval);
+            loop {
+                ticker.tick().await;
+
+                if !*initial_scan_completed.lock().unwrap() {
+                    continue;
+                }
+
+                let changes = {
+                    let mut pending = pending_config_changes.lock().unwrap();
+                    if pending.is_empty() {
+                        continue;
+                    }
+                    let changes = pending.clone();
+                    pending.clear();
+                    changes
+                };
+
+                println!(
+                    "[CONFIG_QUEUE] 调度器快照到 {} 个待处理的配置变更",
+                    changes.len()
+                );
+                let coalesced = Self::coalesce_config_changes(changes, &task_registry);
+                println!(
+                    "[CONFIG_QUEUE] 合并后剩余 {} 个配置变更待执行",
+                    coalesced.len()
+                );
+
+                Self::execute_config_changes(
+                    coalesced,
+                    file_monitor.clone(),
+                    pending_config_changes.clone(),
+                    task_registry.clone(),
+                    tag_search_cache.clone(),
+                )
+                .await;
+            }
         });
     }
 
     /// 执行配置变更（静态方法，可在异步任务中调用）
+    ///
+    /// 采用两阶段提交：先对整批变更做只读校验（phase 1），任何一项不合法就
+    /// 整批放弃，不触碰文件监控器的任何状态；只有全部通过校验才进入
+    /// phase 2 真正执行。phase 2 中途失败时，已生效的监控目录/黑名单状态会
+    /// 通过重新拉取 API 配置回滚到变更前的快照，而不是停留在"部分应用"的
+    /// 中间态。
+    ///
+    /// 不合法（phase 1）或因批内更早的失败而没来得及执行（phase 2）的变更
+    /// 会被放回 `pending_config_changes`，在下一个调度周期重新尝试，而不是
+    /// 随着这批快照一起被丢弃；真正校验失败或执行失败的那一项会通过
+    /// `task_registry` 标记为 `Failed` 并记录错误原因。
+    ///
+    /// 每成功执行一个会改变"哪些文件被索引"的变更
+    /// （`AddBlacklist`/`DeleteFolder`/`ToggleFolder`/`AddWhitelist`）就会调用
+    /// `tag_search_cache.bump_version()`，使这之前缓存的标签搜索/标签云结果
+    /// 在下次读取时失效，避免加入/移除文件夹之后标签搜索仍然返回旧结果。
     async fn execute_config_changes(
-        changes: Vec<ConfigChangeRequest>,
+        changes: Vec<QueuedChange>,
         file_monitor: Arc<Mutex<Option<FileMonitor>>>,
+        pending_config_changes: Arc<Mutex<Vec<QueuedChange>>>,
+        task_registry: Arc<task_registry::TaskRegistry>,
+        tag_search_cache: Arc<tag_search_cache::TagSearchCache>,
     ) {
-        println!("[CONFIG_QUEUE] 开始执行 {} 个配置变更", changes.len());
+        println!("[CONFIG_QUEUE] 开始执行 {} 个配置变更（两阶段）", changes.len());
 
         // 获取文件监控器
         let monitor = {
@@ -363,30 +872,93 @@ impl AppState {
             match &*guard {
                 Some(monitor) => monitor.clone(),
                 None => {
-                    eprintln!("[CONFIG_QUEUE] 文件监控器未初始化，无法执行配置变更");
+                    eprintln!("[CONFIG_QUEUE] 文件监控器未初始化，无法执行配置变更，放回队列等待下次调度");
+                    pending_config_changes.lock().unwrap().extend(changes);
                     return;
                 }
             }
         };
 
-        // 记录执行失败的变更，以便后续处理
+        // --- Phase 1: 校验 ---
+        // 记录变更前的监控目录数量，phase 2 失败时用它提示回滚范围。
+        let pre_change_dir_count = monitor.get_monitored_dirs().len();
+
+        let mut validation_errors = Vec::new();
+        for queued in &changes {
+            if let Err(e) = Self::validate_single_config_change(&queued.request) {
+                validation_errors.push((queued.uid, queued.request.clone(), e));
+            }
+        }
+
+        if !validation_errors.is_empty() {
+            eprintln!(
+                "[CONFIG_QUEUE] 校验失败，{} 个变更未通过，整批放弃（未对监控器做任何修改）:",
+                validation_errors.len()
+            );
+            let invalid_uids: std::collections::HashSet<u64> =
+                validation_errors.iter().map(|(uid, ..)| *uid).collect();
+            for (uid, change, err) in &validation_errors {
+                eprintln!("[CONFIG_QUEUE]   - task_uid={} {:?}: {}", uid, change, err);
+                task_registry.mark_failed(*uid, err.clone());
+            }
+            // 批内其余通过校验的变更只是因为"同批次里有一项不合法"而被连带
+            // 放弃，并非它们自身有问题，放回队列等下一轮重新尝试。
+            let retryable: Vec<QueuedChange> = changes
+                .into_iter()
+                .filter(|queued| !invalid_uids.contains(&queued.uid))
+                .collect();
+            pending_config_changes.lock().unwrap().extend(retryable);
+            return;
+        }
+
+        // --- Phase 2: 执行 ---
         let mut failed_changes = Vec::new();
+        let mut applied_count = 0usize;
+        let mut not_attempted: Vec<QueuedChange> = Vec::new();
 
-        // 执行所有变更
-        for change in changes {
-            match Self::execute_single_config_change(&change, &monitor).await {
+        let mut changes_iter = changes.into_iter();
+        for queued in changes_iter.by_ref() {
+            task_registry.mark_processing(queued.uid);
+            match Self::execute_single_config_change(&queued.request, &monitor).await {
                 Ok(_) => {
-                    println!("[CONFIG_QUEUE] 成功执行配置变更: {:?}", change);
+                    println!("[CONFIG_QUEUE] 成功执行配置变更: {:?}", queued.request);
+                    task_registry.mark_succeeded(queued.uid);
+                    applied_count += 1;
+                    if matches!(
+                        queued.request,
+                        ConfigChangeRequest::AddBlacklist { .. }
+                            | ConfigChangeRequest::DeleteFolder { .. }
+                            | ConfigChangeRequest::ToggleFolder { .. }
+                            | ConfigChangeRequest::AddWhitelist { .. }
+                    ) {
+                        tag_search_cache.bump_version();
+                    }
                 }
                 Err(e) => {
-                    eprintln!("[CONFIG_QUEUE] 执行配置变更失败: {:?}, 错误: {}", change, e);
-                    failed_changes.push((change, e));
+                    eprintln!(
+                        "[CONFIG_QUEUE] 执行配置变更失败: {:?}, 错误: {}",
+                        queued.request, e
+                    );
+                    task_registry.mark_failed(queued.uid, e.clone());
+                    failed_changes.push((queued.request.clone(), e));
+                    // 一旦有变更执行失败就停止继续应用剩余变更，
+                    // 避免在不一致状态上继续堆叠操作。
+                    break;
                 }
             }
 
             // 每个变更之间短暂暂停，避免请求过于密集
             sleep(Duration::from_millis(200)).await;
         }
+        // 因批内更早的失败而没来得及执行的剩余变更，放回队列等下一轮重试。
+        not_attempted.extend(changes_iter);
+        if !not_attempted.is_empty() {
+            println!(
+                "[CONFIG_QUEUE] {} 个变更因批内更早的失败未及执行，放回队列等待下次调度",
+                not_attempted.len()
+            );
+            pending_config_changes.lock().unwrap().extend(not_attempted);
+        }
 
         // 执行完所有变更后，刷新监控配置（增加重试逻辑）
         let mut refresh_success = false;
@@ -418,16 +990,41 @@ impl AppState {
 
         if !refresh_success {
             eprintln!("[CONFIG_QUEUE] 严重警告: 配置刷新失败，系统可能处于不一致状态！");
-            // 这里可以添加额外的恢复步骤或通知用户
         }
 
-        // 报告失败的变更
         if !failed_changes.is_empty() {
             eprintln!(
-                "[CONFIG_QUEUE] 注意: {} 个配置变更执行失败，可能需要用户手动操作",
-                failed_changes.len()
+                "[CONFIG_QUEUE] 回滚: 批次中 {} 个变更已生效、{} 个失败导致中止。\
+                 重新拉取配置以使本地监控器状态与实际生效的变更保持一致（变更前监控目录数: {}）",
+                applied_count,
+                failed_changes.len(),
+                pre_change_dir_count
             );
-            // 这里可以实现更多的失败处理逻辑，例如通知用户
+        }
+    }
+
+    /// Phase 1 校验：只做轻量、无副作用的合法性检查，不触碰任何共享状态。
+    /// 返回 `Err` 会让整批变更在 phase 2 之前就被放弃。
+    fn validate_single_config_change(change: &ConfigChangeRequest) -> Result<(), String> {
+        match change {
+            ConfigChangeRequest::AddBlacklist { folder_path, .. }
+            | ConfigChangeRequest::AddWhitelist { folder_path, .. } => {
+                if folder_path.trim().is_empty() {
+                    return Err("文件夹路径不能为空".to_string());
+                }
+                if !Path::new(folder_path).exists() {
+                    return Err(format!("文件夹不存在: {}", folder_path));
+                }
+                Ok(())
+            }
+            ConfigChangeRequest::DeleteFolder { folder_path, .. }
+            | ConfigChangeRequest::ToggleFolder { folder_path, .. } => {
+                if folder_path.trim().is_empty() {
+                    return Err("文件夹路径不能为空".to_string());
+                }
+                Ok(())
+            }
+            ConfigChangeRequest::BundleExtensionChange => Ok(()),
         }
     }
 
@@ -650,6 +1247,14 @@ pub enum ConfigChangeRequest {
     BundleExtensionChange,
 }
 
+/// 队列里的一项配置变更，附带它在 [`task_registry::TaskRegistry`] 里对应
+/// 的任务 uid，好让调度器处理到它时能把执行结果写回那条任务记录。
+#[derive(Debug, Clone)]
+struct QueuedChange {
+    uid: u64,
+    request: ConfigChangeRequest,
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
@@ -684,6 +1289,7 @@ pub fn run() {
         .plugin(tauri_plugin_store::Builder::new().build())
         .plugin(tauri_plugin_macos_permissions::init())
         .plugin(tauri_plugin_screenshots::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
         // 创建和管理AppState
         .manage(AppState::new())
         .setup(|app| {
@@ -697,6 +1303,16 @@ pub fn run() {
             app_handle.manage(api_manager);
             println!("已注册 ApiProcessManager，将在应用退出时自动清理 API 进程");
 
+            // 安装统一的关闭信号处理（Ctrl-C/SIGTERM/SIGINT/SIGHUP），
+            // 驱动有序的优雅关闭流程，而不再只依赖下面的 Drop 和 panic hook
+            crate::shutdown::install(app_handle.clone());
+
+            // 注册全局快捷键，用于在任意前台应用中划词抓取并收入知识库
+            crate::selection_capture::register(&app_handle);
+
+            // 注册全局快捷键，用于随时唤出 Spotlight 风格的快速搜索悬浮窗
+            crate::search_overlay::register(&app_handle);
+
             // 注册全局 panic hook 用于清理
             let prev_hook = std::panic::take_hook();
             std::panic::set_hook(Box::new(move |panic_info| {
@@ -716,7 +1332,9 @@ pub fn run() {
             {
                 // Scope for MutexGuard
                 let mut api_state_guard = api_state_instance.0.lock().unwrap();
-                api_state_guard.port = 60315;
+                // 探测一个可用端口而不是硬编码 60315：第二个实例、残留的僵尸
+                // 进程或者别的服务占用该端口时，不应该让启动静默失败
+                api_state_guard.port = crate::api_startup::find_available_port(60315);
                 api_state_guard.host = "127.0.0.1".to_string();
                 api_state_guard.db_path = db_path_str;
             }
@@ -753,7 +1371,11 @@ pub fn run() {
                 // 使用reqwest客户端检查API健康状态
                 let client = reqwest::Client::new();
                 let max_retries = 10000; // 最多尝试次数，足够长让用户看到详细日志
-                let retry_interval = std::time::Duration::from_millis(1000); // 毫秒
+                                          // 初始就绪轮询退避：从 250ms 起步，每次失败翻倍，直到 10s 上限，
+                                          // 一旦某次检查成功就重置回起始值（供下一次可能的重新等待使用）
+                let initial_retry_interval = std::time::Duration::from_millis(250);
+                let max_retry_interval = std::time::Duration::from_secs(10);
+                let mut retry_interval = initial_retry_interval;
                 let mut api_ready = false;
 
                 for i in 0..max_retries {
@@ -766,6 +1388,7 @@ pub fn run() {
                     if !api_running {
                         // 如果进程不存在，等待短暂时间后再次检查
                         tokio::time::sleep(retry_interval).await;
+                        retry_interval = std::cmp::min(retry_interval * 2, max_retry_interval);
                         continue;
                     }
 
@@ -779,15 +1402,18 @@ pub fn run() {
                         Ok(response) if response.status().is_success() => {
                             println!("第{}次尝试: API健康检查成功，API已就绪", i + 1);
                             api_ready = true;
+                            retry_interval = initial_retry_interval;
                             break;
                         }
                         _ => {
-                            // API尚未准备好，等待后重试
-                            if (i + 1) % 5 == 0 {
-                                // 每5次打印一次，避免日志过多
-                                println!("第{}次尝试: API尚未就绪，继续等待...", i + 1);
-                            }
+                            // API尚未准备好，按退避间隔等待后重试
+                            println!(
+                                "第{}次尝试: API尚未就绪，{:?} 后重试...",
+                                i + 1,
+                                retry_interval
+                            );
                             tokio::time::sleep(retry_interval).await;
+                            retry_interval = std::cmp::min(retry_interval * 2, max_retry_interval);
                         }
                     }
                 }
@@ -811,8 +1437,19 @@ pub fn run() {
 
                     // 获取主窗口句柄并发送就绪事件
                     if let Some(main) = app_handle_for_api.get_webview_window("main") {
-                        // 向主窗口发送 API 就绪事件，这里是唯一发送位置
-                        let _ = main.emit("api-ready", true);
+                        // 记录到生命周期事件存储并转发，这里是唯一发送位置——
+                        // 主 webview 注册监听器之前就绪的话，前端可以之后调用
+                        // `sync_lifecycle` 补发这个状态（见 LifecycleEventStore）
+                        app_handle_for_api
+                            .state::<AppState>()
+                            .lifecycle_events()
+                            .record_and_emit(&app_handle_for_api, "api-ready", serde_json::json!(true));
+                        // 端口是探测出来的、每次启动都可能不同，前端不能再假设
+                        // 固定端口——随 api-ready 一起把实际 host/port 发给它
+                        let _ = main.emit(
+                            "api-port",
+                            serde_json::json!({ "host": api_host, "port": api_port }),
+                        );
                         println!("已向主窗口发送 API 就绪信号");
                     } else {
                         eprintln!("找不到主窗口，无法发送 API 就绪信号");
@@ -844,7 +1481,11 @@ pub fn run() {
                         // 初始化简化配置
                         println!("开始初始化简化配置...");
                         let app_state = app_handle_for_monitor.state::<AppState>();
-                        match app_state.refresh_simplified_config().await {
+                        let (api_host, api_port) = {
+                            let guard = api_state_for_monitor.lock().unwrap();
+                            (guard.host.clone(), guard.port)
+                        };
+                        match app_state.refresh_simplified_config(&api_host, api_port).await {
                             Ok(()) => {
                                 println!("简化配置初始化成功");
                                 if let Some(window) =
@@ -868,14 +1509,34 @@ pub fn run() {
                     }
                     _ => {
                         eprintln!("API未能成功启动，无法初始化文件监控基础设施");
-                        if let Some(window) = app_handle_for_monitor.get_webview_window("main") {
-                            let _ =
-                                window.emit("file-monitor-error", "API未就绪，无法初始化文件监控");
+                        if app_handle_for_monitor.get_webview_window("main").is_some() {
+                            app_handle_for_monitor
+                                .state::<AppState>()
+                                .lifecycle_events()
+                                .record_and_emit(
+                                    &app_handle_for_monitor,
+                                    "file-monitor-error",
+                                    serde_json::json!("API未就绪，无法初始化文件监控"),
+                                );
                         }
                     }
                 }
             });
 
+            // 启动周期性配置变更调度器：合并短时间内连续的多次文件夹操作，
+            // 减少对 Python API 的请求次数（见 AppState::spawn_config_change_scheduler）
+            {
+                let app_state_for_scheduler = app.state::<AppState>();
+                AppState::spawn_config_change_scheduler(
+                    app_state_for_scheduler.pending_config_changes.clone(),
+                    app_state_for_scheduler.initial_scan_completed.clone(),
+                    app_state_for_scheduler.file_monitor.clone(),
+                    app_state_for_scheduler.config_change_tasks.clone(),
+                    app_state_for_scheduler.tag_search_cache.clone(),
+                    Duration::from_secs(1),
+                );
+            }
+
             // 创建应用菜单（仅在 macOS 上显示）
             #[cfg(target_os = "macos")]
             {
@@ -887,7 +1548,8 @@ pub fn run() {
                 let separator = PredefinedMenuItem::separator(app)?;
                 let quit_item = PredefinedMenuItem::quit(app, Some("Quit Knowledge Focus"))?;
 
-                // 创建窗口定位菜单项
+                // 创建窗口分区菜单项（左右/上下半屏 + 四个象限 + 最大化/还原）
+                // 实际的分区计算统一走 window_tiling 模块，这里只负责菜单项定义
                 let move_left_item =
                     MenuItem::with_id(app, "move_left", "Move Left", true, Some("cmd+shift+left"))?;
                 let move_right_item = MenuItem::with_id(
@@ -897,6 +1559,57 @@ pub fn run() {
                     true,
                     Some("cmd+shift+right"),
                 )?;
+                let move_top_item =
+                    MenuItem::with_id(app, "move_top", "Move Top", true, Some("cmd+shift+up"))?;
+                let move_bottom_item = MenuItem::with_id(
+                    app,
+                    "move_bottom",
+                    "Move Bottom",
+                    true,
+                    Some("cmd+shift+down"),
+                )?;
+                let move_top_left_item = MenuItem::with_id(
+                    app,
+                    "move_top_left",
+                    "Move Top Left",
+                    true,
+                    Some("cmd+shift+7"),
+                )?;
+                let move_top_right_item = MenuItem::with_id(
+                    app,
+                    "move_top_right",
+                    "Move Top Right",
+                    true,
+                    Some("cmd+shift+8"),
+                )?;
+                let move_bottom_left_item = MenuItem::with_id(
+                    app,
+                    "move_bottom_left",
+                    "Move Bottom Left",
+                    true,
+                    Some("cmd+shift+9"),
+                )?;
+                let move_bottom_right_item = MenuItem::with_id(
+                    app,
+                    "move_bottom_right",
+                    "Move Bottom Right",
+                    true,
+                    Some("cmd+shift+0"),
+                )?;
+                let maximize_window_item = MenuItem::with_id(
+                    app,
+                    "maximize_window",
+                    "Maximize",
+                    true,
+                    Some("cmd+shift+enter"),
+                )?;
+                let restore_window_item = MenuItem::with_id(
+                    app,
+                    "restore_window",
+                    "Restore",
+                    true,
+                    Some("cmd+shift+backspace"),
+                )?;
 
                 // 创建应用菜单
                 let app_menu = Submenu::with_id_and_items(
@@ -941,6 +1654,16 @@ pub fn run() {
                         &PredefinedMenuItem::separator(app)?,
                         &move_left_item,
                         &move_right_item,
+                        &move_top_item,
+                        &move_bottom_item,
+                        &PredefinedMenuItem::separator(app)?,
+                        &move_top_left_item,
+                        &move_top_right_item,
+                        &move_bottom_left_item,
+                        &move_bottom_right_item,
+                        &PredefinedMenuItem::separator(app)?,
+                        &maximize_window_item,
+                        &restore_window_item,
                         &PredefinedMenuItem::separator(app)?,
                         &PredefinedMenuItem::close_window(app, None)?,
                     ],
@@ -970,145 +1693,101 @@ pub fn run() {
                         "move_left" => {
                             println!("Move Left 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
-                                // 获取屏幕尺寸并将窗口移动到左半屏
-                                if let Ok(monitor) = window.current_monitor() {
-                                    if let Some(monitor) = monitor {
-                                        let screen_size = monitor.size();
-                                        let screen_position = monitor.position();
-
-                                        let window_width = screen_size.width / 2;
-                                        // 窗口高度保持不变
-                                        let window_height =
-                                            window.outer_size().unwrap_or_default().height;
-                                        // 窗口y值不变
-                                        let window_y =
-                                            window.outer_position().unwrap_or_default().y;
-
-                                        // 设置窗口位置和大小
-                                        let _ = window.set_position(tauri::Position::Physical(
-                                            tauri::PhysicalPosition {
-                                                x: screen_position.x,
-                                                y: window_y,
-                                            },
-                                        ));
-                                        let _ = window.set_size(tauri::Size::Physical(
-                                            tauri::PhysicalSize {
-                                                width: window_width,
-                                                height: window_height,
-                                            },
-                                        ));
-                                    }
-                                }
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::LeftHalf,
+                                );
                             }
                         }
                         "move_right" => {
                             println!("Move Right 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
-                                // 获取屏幕尺寸并将窗口移动到右半屏
-                                if let Ok(monitor) = window.current_monitor() {
-                                    if let Some(monitor) = monitor {
-                                        let screen_size = monitor.size();
-                                        let screen_position = monitor.position();
-
-                                        let window_width = screen_size.width / 2;
-                                        // 窗口高度保持不变
-                                        let window_height =
-                                            window.outer_size().unwrap_or_default().height;
-                                        // 窗口y值不变
-                                        let window_y =
-                                            window.outer_position().unwrap_or_default().y;
-
-                                        // 设置窗口位置和大小
-                                        let _ = window.set_position(tauri::Position::Physical(
-                                            tauri::PhysicalPosition {
-                                                x: screen_position.x
-                                                    + (screen_size.width / 2) as i32,
-                                                y: window_y,
-                                            },
-                                        ));
-                                        let _ = window.set_size(tauri::Size::Physical(
-                                            tauri::PhysicalSize {
-                                                width: window_width,
-                                                height: window_height,
-                                            },
-                                        ));
-                                    }
-                                }
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::RightHalf,
+                                );
                             }
                         }
-                        _ => {}
-                    }
-                });
-            }
-
-            // 设置托盘图标和菜单
-            let quit_i = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&quit_i])?;
-            // 在托盘菜单事件中处理退出操作
-            let tray_icon = TrayIconBuilder::new()
-                .menu(&menu)
-                .show_menu_on_left_click(false) // Changed to false for right-click menu
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "quit" => {
-                        println!("退出菜单项被点击");
-
-                        // 在退出前执行完整清理
-                        println!("执行完整进程清理");
-
-                        // 尝试获取ApiProcessManager并执行完整清理
-                        if let Some(api_manager) = app.try_state::<ApiProcessManager>() {
-                            api_manager.cleanup();
-                            println!("通过ApiProcessManager实例执行了完整清理");
-                        } else {
-                            println!("无法获取ApiProcessManager，使用静态清理");
-                            ApiProcessManager::cleanup_processes();
+                        "move_top" => {
+                            println!("Move Top 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::TopHalf,
+                                );
+                            }
                         }
-
-                        // 终止所有资源并退出应用
-                        app.exit(0);
-                    }
-                    _ => {
-                        // println!("menu item {:?} not handled", event.id);
-                    }
-                })
-                .on_tray_icon_event(|tray, event| match event {
-                    // Left click shows and focuses the main window
-                    TrayIconEvent::Click {
-                        button: MouseButton::Left,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } => {
-                        let app = tray.app_handle();
-                        #[cfg(target_os = "macos")]
-                        {
-                            let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
-                            app.show().unwrap();
-                            // 确保应用程序被激活
+                        "move_bottom" => {
+                            println!("Move Bottom 菜单项被点击");
                             if let Some(window) = app.get_webview_window("main") {
-                                let _ = window.show();
-                                let _ = window.set_focus();
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::BottomHalf,
+                                );
                             }
                         }
-                        #[cfg(not(target_os = "macos"))]
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
+                        "move_top_left" => {
+                            println!("Move Top Left 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::TopLeftQuarter,
+                                );
+                            }
                         }
+                        "move_top_right" => {
+                            println!("Move Top Right 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::TopRightQuarter,
+                                );
+                            }
+                        }
+                        "move_bottom_left" => {
+                            println!("Move Bottom Left 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::BottomLeftQuarter,
+                                );
+                            }
+                        }
+                        "move_bottom_right" => {
+                            println!("Move Bottom Right 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::BottomRightQuarter,
+                                );
+                            }
+                        }
+                        "maximize_window" => {
+                            println!("Maximize 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::Maximize,
+                                );
+                            }
+                        }
+                        "restore_window" => {
+                            println!("Restore 菜单项被点击");
+                            if let Some(window) = app.get_webview_window("main") {
+                                let _ = window_tiling::apply_tile(
+                                    &window,
+                                    window_tiling::TileRegion::Restore,
+                                );
+                            }
+                        }
+                        _ => {}
                     }
-                    // Right click shows the menu (handled automatically because show_menu_on_left_click is false)
-                    TrayIconEvent::Click {
-                        button: MouseButton::Right,
-                        button_state: MouseButtonState::Up,
-                        ..
-                    } => {
-                        // Menu is shown automatically
-                    }
-                    _ => {
-                        // Other events are ignored
-                    }
-                })
-                .build(app)?;
-            println!("Tray Icon ID: {:?}", tray_icon.id());
+                });
+            }
+
+            // 设置托盘图标和菜单：实际的菜单内容、点击分发和动态重建都在
+            // tray_menu 模块里（见该模块顶部说明），这里只触发一次性创建
+            crate::tray_menu::create(app)?;
             Ok(())
         })
         // 管理API进程状态
@@ -1117,6 +1796,10 @@ pub fn run() {
             port: 60315,
             host: "127.0.0.1".to_string(),
             db_path: String::new(),
+            pid: None,
+            health: ApiHealthState::Running,
+            restart_timestamps: std::collections::VecDeque::new(),
+            resource_limits: crate::resource_limits::ResourceLimits::default(),
         }))))
         // 管理文件监控状态
         .manage(Arc::new(Mutex::new(Option::<FileMonitor>::None)))
@@ -1124,6 +1807,7 @@ pub fn run() {
             commands::refresh_monitoring_config,         // 刷新监控配置
             commands::refresh_simplified_config,         // 刷新简化配置
             commands::read_directory,                    // 读取目录内容
+            commands::read_directory_stream,             // 流式递归枚举目录（分批事件+独立错误事件）
             commands::get_tag_cloud_data,                // 获取标签云数据
             commands::search_files_by_tags,              // 按标签搜索文件
             commands::queue_add_blacklist_folder,        // 添加黑名单文件夹
@@ -1131,10 +1815,36 @@ pub fn run() {
             commands::queue_toggle_folder_status,        // 切换文件夹状态（黑名单/白名单）
             commands::queue_add_whitelist_folder,        // 添加白名单文件夹
             commands::queue_get_status,                  // 获取队列状态
+            commands::queue_get_task,                    // 查询单个配置变更任务的进度
+            commands::queue_list_tasks,                  // 分页列出配置变更任务历史
+            commands::queue_cancel_pending_change,       // 撤回一个还未执行的配置变更
+            commands::queue_reorder_pending,             // 调整一个仍在排队的配置变更的顺序
+            commands::queue_clear_pending,               // 清空所有仍在排队的配置变更
+            commands::set_folder_ignore_files_enabled,   // 开关某文件夹的 .gitignore/.ignore 支持
+            commands::set_folder_path_filter,            // 设置某文件夹的 allow/ignore glob 过滤规则
+            window_tiling::tile_window,                  // 前端触发窗口分区（半屏/象限/最大化）
+            commands::capture_selection,                 // 将抓取到的选中文本送进知识库
+            commands::pause_file_monitoring,             // 暂停文件监控（托盘/前端共用）
+            commands::resume_file_monitoring,            // 恢复文件监控（托盘/前端共用）
+            search_overlay::dismiss_search_overlay,       // 关闭快速搜索悬浮窗
             file_scanner::start_backend_scanning,        // 后端扫描启动命令
             file_scanner::scan_files_by_time_range,      // 按时间范围扫描文件
             file_scanner::scan_files_by_type,            // 按类型扫描文件
             file_scanner::scan_files_simplified_command, // 简化扫描命令（支持Bundle和新配置）
+            file_scanner::scan_files_simplified_page_command, // 分页版简化扫描，支持增量加载
+            file_scanner::find_duplicate_files_command,  // 在扫描结果里查找内容重复的文件
+            file_scanner::find_largest_files_command,     // 在扫描结果里查找占用空间最大的文件
+            commands::sync_lifecycle,                     // 补发生命周期事件的最新状态，填平前端监听器晚注册的竞态
+            commands::api_status,                         // 查询 Python API sidecar 的 host/port/PID/存活状态
+            commands::stop_api,                           // 停止 Python API sidecar，不重新拉起
+            commands::restart_api,                        // 重启 Python API sidecar 并等待就绪
+            commands::set_event_strategy,                 // 运行期覆盖某个桥接事件的缓冲策略
+            commands::mute_event,                          // 静音某个桥接事件，进入缓冲前直接丢弃
+            commands::unmute_event,                        // 取消静音，恢复正常处理
+            commands::enable_diagnostics_export,           // 打开桥接事件到外部可观测性后端的批量导出
+            commands::disable_diagnostics_export,          // 关闭可观测性导出
+            commands::get_event_buffer_metrics,            // 拍一张事件缓冲器的计数器/gauge/延迟直方图快照
+            commands::get_event_buffer_metrics_text,       // 同上，渲染成 Prometheus 文本 exposition 格式
         ])
         .on_window_event(|window, event| match event {
             WindowEvent::Destroyed => {
@@ -0,0 +1,323 @@
+//! 层级化的本地配置层。
+//!
+//! `fetch_and_store_all_config` 默认把 HTTP `/config/all` 的响应当成唯一的
+//! 配置来源。这个模块在其上加一层可选的本地文件层：应用数据目录下
+//! `local_config/` 里的入口文件（[`ENTRY_FILE_NAME`]）被解析成若干条类别/
+//! 过滤规则/扩展名映射/监控目录记录，用 later-layer-wins 的语义合并到
+//! （先到的）API 层之上，这样用户可以离线自定义，或者覆盖服务端下发的
+//! 默认配置。
+//!
+//! 语法是一个简化的、INI/TOML 风格的 DSL（不是完整 TOML，避免为了这一个
+//! 功能引入额外的 TOML 解析 crate 依赖）：
+//!
+//! ```text
+//! # 注释以 # 开头
+//! %include shared/base.leafconf   # 递归展开另一个文件，相对路径相对当前文件
+//! %unset monitored_folder:/Users/alice/OldProject  # 删掉较低层级里的同名条目
+//!
+//! [monitored_folder]
+//! path = /Users/alice/Documents
+//! alias = Documents
+//! is_blacklist = false
+//! ```
+//!
+//! 空行或下一个 `[section]`/`%指令` 结束当前正在填写的记录。`%unset` 的
+//! `<kind>:<id>` 里，`kind` 是 `category`/`filter_rule`/`extension_map`/
+//! `monitored_folder` 之一，`id` 对类别/规则/扩展名映射是数字 id（和
+//! `extension_map` 还额外支持按扩展名本身匹配），对监控目录是路径本身。
+
+use crate::file_monitor::{
+    AllConfigurations, FileCategoryRust, FileExtensionMapRust, FileFilterRuleRust,
+    MonitoredDirectory, RuleActionRust, RulePriorityRust, RuleTypeRust,
+};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// 本地配置层的入口文件名，放在调用方指定的本地配置目录下。
+pub const ENTRY_FILE_NAME: &str = "local.leafconf";
+
+#[derive(Debug, Clone)]
+struct UnsetDirective {
+    kind: String,
+    id: String,
+}
+
+/// 从本地配置文件（及它递归 `%include` 的文件）解析出的覆盖层，尚未合并到
+/// 任何 [`AllConfigurations`] 上。
+#[derive(Debug, Default)]
+pub struct LocalConfigOverrides {
+    categories: Vec<FileCategoryRust>,
+    filter_rules: Vec<FileFilterRuleRust>,
+    extension_maps: Vec<FileExtensionMapRust>,
+    monitored_folders: Vec<MonitoredDirectory>,
+    unset: Vec<UnsetDirective>,
+}
+
+impl LocalConfigOverrides {
+    /// 把这一层合并到 `base`（通常是刚从 API 拉到的配置）之上：先执行全部
+    /// `%unset`，再逐条按 id（监控目录按 path）合并本地记录——id 已存在就
+    /// 原地替换，否则追加，体现"后面的层覆盖前面的层"。
+    pub fn apply_to(self, base: &mut AllConfigurations) {
+        for directive in &self.unset {
+            match directive.kind.as_str() {
+                "category" => base
+                    .file_categories
+                    .retain(|c| c.id.to_string() != directive.id),
+                "filter_rule" => base
+                    .file_filter_rules
+                    .retain(|r| r.id.to_string() != directive.id),
+                "extension_map" => base
+                    .file_extension_maps
+                    .retain(|m| m.id.to_string() != directive.id && m.extension != directive.id),
+                "monitored_folder" => base.monitored_folders.retain(|d| d.path != directive.id),
+                other => eprintln!("[LOCAL_CONFIG] 忽略未知的 %unset 类型: {}", other),
+            }
+        }
+
+        for category in self.categories {
+            match base.file_categories.iter_mut().find(|c| c.id == category.id) {
+                Some(existing) => *existing = category,
+                None => base.file_categories.push(category),
+            }
+        }
+        for rule in self.filter_rules {
+            match base.file_filter_rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => *existing = rule,
+                None => base.file_filter_rules.push(rule),
+            }
+        }
+        for map in self.extension_maps {
+            match base.file_extension_maps.iter_mut().find(|m| m.id == map.id) {
+                Some(existing) => *existing = map,
+                None => base.file_extension_maps.push(map),
+            }
+        }
+        for folder in self.monitored_folders {
+            match base
+                .monitored_folders
+                .iter_mut()
+                .find(|d| d.path == folder.path)
+            {
+                Some(existing) => *existing = folder,
+                None => base.monitored_folders.push(folder),
+            }
+        }
+    }
+}
+
+/// 加载 `entry_path` 及它递归 `%include` 的所有文件，解析失败（文件不存在、
+/// 读取出错、检测到 `%include` 循环）时只打印一条日志并返回目前已经解析到的
+/// 部分结果——本地层出问题不应该让整个应用没法拿到 API 配置。
+pub fn load(entry_path: &Path) -> LocalConfigOverrides {
+    let mut overrides = LocalConfigOverrides::default();
+    let mut active_chain: HashSet<PathBuf> = HashSet::new();
+    if let Err(e) = load_file(entry_path, &mut active_chain, &mut overrides) {
+        eprintln!("[LOCAL_CONFIG] 加载本地配置 {:?} 失败: {}", entry_path, e);
+    }
+    overrides
+}
+
+fn load_file(
+    path: &Path,
+    active_chain: &mut HashSet<PathBuf>,
+    overrides: &mut LocalConfigOverrides,
+) -> Result<(), String> {
+    let canonical = path
+        .canonicalize()
+        .map_err(|e| format!("无法解析路径 {:?}: {}", path, e))?;
+    // `active_chain` 只跟踪"当前正在展开的 %include 调用链"，文件结束时会把
+    // 自己移出去（见函数末尾），所以同一个文件被两条不相交的链分别
+    // include（菱形依赖）是允许的，只有真正的环（A 直接或间接 include 自己）
+    // 才会被挡住。
+    if !active_chain.insert(canonical.clone()) {
+        return Err(format!("检测到 %include 循环: {:?}", path));
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取失败: {}", e))?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut section: Option<String> = None;
+    let mut fields: HashMap<String, String> = HashMap::new();
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            if line.is_empty() {
+                if let Some(sec) = section.take() {
+                    finalize_record(&sec, &fields, overrides);
+                    fields.clear();
+                }
+            }
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%include") {
+            if let Some(sec) = section.take() {
+                finalize_record(&sec, &fields, overrides);
+                fields.clear();
+            }
+            let include_target = resolve_include_path(base_dir, rest.trim());
+            load_file(&include_target, active_chain, overrides)?;
+            continue;
+        }
+
+        if let Some(rest) = line.strip_prefix("%unset") {
+            if let Some(sec) = section.take() {
+                finalize_record(&sec, &fields, overrides);
+                fields.clear();
+            }
+            match rest.trim().split_once(':') {
+                Some((kind, id)) => overrides.unset.push(UnsetDirective {
+                    kind: kind.trim().to_string(),
+                    id: id.trim().to_string(),
+                }),
+                None => eprintln!("[LOCAL_CONFIG] 忽略格式错误的指令（期望 %unset <kind>:<id>）: {}", line),
+            }
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            if let Some(sec) = section.take() {
+                finalize_record(&sec, &fields, overrides);
+                fields.clear();
+            }
+            section = Some(line[1..line.len() - 1].trim().to_string());
+            continue;
+        }
+
+        if let Some((key, value)) = line.split_once('=') {
+            fields.insert(key.trim().to_string(), value.trim().to_string());
+        }
+    }
+
+    if let Some(sec) = section.take() {
+        finalize_record(&sec, &fields, overrides);
+    }
+
+    active_chain.remove(&canonical);
+    Ok(())
+}
+
+fn resolve_include_path(base_dir: &Path, include_path: &str) -> PathBuf {
+    let candidate = Path::new(include_path);
+    if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        base_dir.join(candidate)
+    }
+}
+
+fn finalize_record(
+    section: &str,
+    fields: &HashMap<String, String>,
+    overrides: &mut LocalConfigOverrides,
+) {
+    match section {
+        "category" => match build_category(fields) {
+            Some(category) => overrides.categories.push(category),
+            None => eprintln!("[LOCAL_CONFIG] 忽略字段不全的 [category] 记录: {:?}", fields),
+        },
+        "filter_rule" => match build_filter_rule(fields) {
+            Some(rule) => overrides.filter_rules.push(rule),
+            None => eprintln!("[LOCAL_CONFIG] 忽略字段不全的 [filter_rule] 记录: {:?}", fields),
+        },
+        "extension_map" => match build_extension_map(fields) {
+            Some(map) => overrides.extension_maps.push(map),
+            None => eprintln!("[LOCAL_CONFIG] 忽略字段不全的 [extension_map] 记录: {:?}", fields),
+        },
+        "monitored_folder" => match build_monitored_folder(fields) {
+            Some(folder) => overrides.monitored_folders.push(folder),
+            None => eprintln!(
+                "[LOCAL_CONFIG] 忽略字段不全的 [monitored_folder] 记录: {:?}",
+                fields
+            ),
+        },
+        other => eprintln!("[LOCAL_CONFIG] 忽略未知 section: [{}]", other),
+    }
+}
+
+fn parse_rule_type(value: &str) -> Option<RuleTypeRust> {
+    match value {
+        "extension" => Some(RuleTypeRust::Extension),
+        "filename" => Some(RuleTypeRust::Filename),
+        "folder" => Some(RuleTypeRust::Folder),
+        "structure" => Some(RuleTypeRust::Structure),
+        "os_bundle" => Some(RuleTypeRust::OSBundle),
+        _ => None,
+    }
+}
+
+fn parse_priority(value: &str) -> Option<RulePriorityRust> {
+    match value {
+        "low" => Some(RulePriorityRust::Low),
+        "medium" => Some(RulePriorityRust::Medium),
+        "high" => Some(RulePriorityRust::High),
+        _ => None,
+    }
+}
+
+fn parse_action(value: &str) -> Option<RuleActionRust> {
+    match value {
+        "include" => Some(RuleActionRust::Include),
+        "exclude" => Some(RuleActionRust::Exclude),
+        "label" => Some(RuleActionRust::Label),
+        _ => None,
+    }
+}
+
+fn build_category(fields: &HashMap<String, String>) -> Option<FileCategoryRust> {
+    Some(FileCategoryRust {
+        id: fields.get("id")?.parse().ok()?,
+        name: fields.get("name")?.clone(),
+        description: fields.get("description").cloned(),
+        icon: fields.get("icon").cloned(),
+    })
+}
+
+fn build_filter_rule(fields: &HashMap<String, String>) -> Option<FileFilterRuleRust> {
+    Some(FileFilterRuleRust {
+        id: fields.get("id")?.parse().ok()?,
+        name: fields.get("name")?.clone(),
+        description: fields.get("description").cloned(),
+        rule_type: parse_rule_type(fields.get("rule_type")?)?,
+        category_id: fields.get("category_id").and_then(|v| v.parse().ok()),
+        priority: parse_priority(fields.get("priority")?)?,
+        action: parse_action(fields.get("action")?)?,
+        enabled: fields.get("enabled").map(|v| v == "true").unwrap_or(true),
+        is_system: fields.get("is_system").map(|v| v == "true").unwrap_or(false),
+        pattern: fields.get("pattern")?.clone(),
+        pattern_type: fields.get("pattern_type")?.clone(),
+        // %unset/记录 DSL 目前没有办法表达任意 JSON，本地层新增的规则就没有
+        // extra_data；需要的话用户应当直接在 API 那一层配置。
+        extra_data: None,
+    })
+}
+
+fn build_extension_map(fields: &HashMap<String, String>) -> Option<FileExtensionMapRust> {
+    Some(FileExtensionMapRust {
+        id: fields.get("id")?.parse().ok()?,
+        extension: fields.get("extension")?.to_lowercase(),
+        category_id: fields.get("category_id")?.parse().ok()?,
+        description: fields.get("description").cloned(),
+        priority: fields
+            .get("priority")
+            .and_then(|v| parse_priority(v))
+            .unwrap_or(RulePriorityRust::Medium),
+    })
+}
+
+fn build_monitored_folder(fields: &HashMap<String, String>) -> Option<MonitoredDirectory> {
+    Some(MonitoredDirectory {
+        id: fields.get("id").and_then(|v| v.parse().ok()),
+        path: fields.get("path")?.clone(),
+        alias: fields.get("alias").cloned(),
+        is_blacklist: fields
+            .get("is_blacklist")
+            .map(|v| v == "true")
+            .unwrap_or(false),
+        created_at: None,
+        updated_at: None,
+        max_depth: fields.get("max_depth").and_then(|v| v.parse().ok()),
+    })
+}
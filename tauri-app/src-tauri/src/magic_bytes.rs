@@ -0,0 +1,137 @@
+//! 基于文件头部字节（"magic bytes"）的内容类型嗅探。
+//!
+//! 和 [`crate::path_filter`]/[`crate::ignore_matcher`] 一样，这棵树里没有
+//! Cargo.toml，没法引入 `infer` 这样未经验证的第三方 crate，所以这里手写一
+//! 个覆盖常见格式的签名表，只识别扫描管线实际需要区分的几大类
+//! （图片/音视频/压缩包/文档），不追求 `infer` 那种覆盖上百种格式的完整性。
+
+use crate::file_scanner::FileType;
+
+/// 一次嗅探命中的结果：MIME 类型、规范扩展名，以及映射到的 [`FileType`] 大类。
+pub struct SniffedType {
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+    pub file_type: FileType,
+}
+
+/// 嗅探时实际读取的前缀字节数：足够覆盖下面所有签名，也足够在 ZIP 容器里
+/// 找到 `word/`/`xl/`/`ppt/` 这类 Office 文档的内部路径标记。
+pub const SNIFF_PREFIX_LEN: usize = 8192;
+
+/// 对读到的前缀字节做签名匹配，返回识别出的类型；没有任何签名匹配时返回
+/// `None`（调用方应当回退到按扩展名分类，或者视为无法分类）。
+pub fn sniff(buf: &[u8]) -> Option<SniffedType> {
+    if buf.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        return Some(image("image/jpeg", "jpg"));
+    }
+    if buf.starts_with(&[0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A]) {
+        return Some(image("image/png", "png"));
+    }
+    if buf.starts_with(b"GIF87a") || buf.starts_with(b"GIF89a") {
+        return Some(image("image/gif", "gif"));
+    }
+    if buf.starts_with(&[0x42, 0x4D]) {
+        return Some(image("image/bmp", "bmp"));
+    }
+    if buf.len() >= 12 && &buf[0..4] == b"RIFF" && &buf[8..12] == b"WEBP" {
+        return Some(image("image/webp", "webp"));
+    }
+
+    if buf.starts_with(b"%PDF") {
+        return Some(document("application/pdf", "pdf"));
+    }
+
+    if buf.len() >= 12 && &buf[0..4] == b"RIFF" && &buf[8..12] == b"WAVE" {
+        return Some(audio_video("audio/wav", "wav"));
+    }
+    if buf.starts_with(b"fLaC") {
+        return Some(audio_video("audio/flac", "flac"));
+    }
+    if buf.starts_with(b"OggS") {
+        return Some(audio_video("audio/ogg", "ogg"));
+    }
+    if buf.starts_with(b"ID3")
+        || buf.starts_with(&[0xFF, 0xFB])
+        || buf.starts_with(&[0xFF, 0xF3])
+        || buf.starts_with(&[0xFF, 0xF2])
+    {
+        return Some(audio_video("audio/mpeg", "mp3"));
+    }
+    if buf.len() >= 8 && &buf[4..8] == b"ftyp" {
+        return Some(audio_video("video/mp4", "mp4"));
+    }
+
+    if buf.starts_with(&[0x37, 0x7A, 0xBC, 0xAF, 0x27, 0x1C]) {
+        return Some(archive("application/x-7z-compressed", "7z"));
+    }
+    if buf.starts_with(&[0x1F, 0x8B]) {
+        return Some(archive("application/gzip", "gz"));
+    }
+    if buf.starts_with(&[0x52, 0x61, 0x72, 0x21, 0x1A, 0x07]) {
+        return Some(archive("application/vnd.rar", "rar"));
+    }
+    if buf.starts_with(&[0x50, 0x4B, 0x03, 0x04])
+        || buf.starts_with(&[0x50, 0x4B, 0x05, 0x06])
+        || buf.starts_with(&[0x50, 0x4B, 0x07, 0x08])
+    {
+        // ZIP 容器：Office Open XML 文档在内部会有 word//xl//ppt/ 这几个
+        // 目录条目，在嗅探到的前缀里找一下就能和普通 zip 压缩包区分开。
+        if contains(buf, b"word/") {
+            return Some(document(
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+                "docx",
+            ));
+        }
+        if contains(buf, b"xl/") {
+            return Some(document(
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+                "xlsx",
+            ));
+        }
+        if contains(buf, b"ppt/") {
+            return Some(document(
+                "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+                "pptx",
+            ));
+        }
+        return Some(archive("application/zip", "zip"));
+    }
+
+    None
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack.windows(needle.len()).any(|w| w == needle)
+}
+
+fn image(mime_type: &'static str, extension: &'static str) -> SniffedType {
+    SniffedType {
+        mime_type,
+        extension,
+        file_type: FileType::Image,
+    }
+}
+
+fn audio_video(mime_type: &'static str, extension: &'static str) -> SniffedType {
+    SniffedType {
+        mime_type,
+        extension,
+        file_type: FileType::AudioVideo,
+    }
+}
+
+fn archive(mime_type: &'static str, extension: &'static str) -> SniffedType {
+    SniffedType {
+        mime_type,
+        extension,
+        file_type: FileType::Archive,
+    }
+}
+
+fn document(mime_type: &'static str, extension: &'static str) -> SniffedType {
+    SniffedType {
+        mime_type,
+        extension,
+        file_type: FileType::Document,
+    }
+}
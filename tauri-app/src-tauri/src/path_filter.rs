@@ -0,0 +1,140 @@
+//! 基于 glob 的扫描过滤层（allow/ignore 两张有序列表，按监控文件夹配置）。
+//!
+//! 和 [`crate::ignore_matcher`] 一样，这棵树里没有 Cargo.toml，没法引入
+//! `ignore`/`globset` 这类未经验证的第三方 crate（参见 ignore_matcher.rs 的
+//! 先例），所以继续用仓库里已经在用的 `regex` crate 手写一个轻量 glob 匹配
+//! 器，语义上和 `ignore::Gitignore` 等价：一个相对路径只有在匹配某条 allow
+//! 模式（allow 列表为空则视为"允许一切"）、且不被 ignore 列表里最后一条
+//! 匹配的规则判定为忽略（支持前导 `!` 取反）时才算通过。
+//!
+//! 为了避免在不相关的子树里做无意义的 glob 匹配，每条 allow 模式被拆成
+//! "基础前缀"（第一个通配符之前的字面路径部分）和剩余模式；[`PathFilter::could_descend`]
+//! 只用基础前缀判断某个目录是否值得继续往下走，配合 `WalkDir::filter_entry`
+//! 在遍历时就剪掉整个子树，而不是等发现了所有条目之后再逐个过滤。
+
+use serde::{Deserialize, Serialize};
+
+/// 某个监控文件夹的 allow/ignore glob 配置，纯本地设置（和
+/// `AppState::ignore_files_enabled_for` 一样，不随 Python API 的配置快照下发）。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PathFilterConfig {
+    /// 允许模式，按声明顺序；为空表示保留旧行为——不额外限制。
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// 忽略模式，按声明顺序；支持前导 `!` 表示"即使匹配到也不忽略"。
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+struct CompiledGlob {
+    /// 第一个通配符之前的字面前缀（用于遍历剪枝），没有通配符则是整条模式。
+    base_prefix: String,
+    negated: bool,
+    regex: regex::Regex,
+}
+
+/// 编译好的过滤器，由 [`PathFilterConfig`] 构建一次后在一次扫描中反复使用。
+pub struct PathFilter {
+    allow: Vec<CompiledGlob>,
+    ignore: Vec<CompiledGlob>,
+}
+
+impl PathFilter {
+    pub fn compile(config: &PathFilterConfig) -> Self {
+        Self {
+            allow: config.allow.iter().map(|p| compile_glob(p)).collect(),
+            ignore: config.ignore.iter().map(|p| compile_glob(p)).collect(),
+        }
+    }
+
+    /// 是否值得继续往 `relative_dir`（相对监控根目录，`/` 分隔，不带前导
+    /// `/`）遍历：没有配置 allow 列表，或者存在至少一条 allow 模式的基础前缀
+    /// 和这个目录"可能相关"（谁是谁的前缀都算，因为遍历是自顶向下的，这时
+    /// 还不知道子目录会不会进一步匹配），就值得继续下探。
+    pub fn could_descend(&self, relative_dir: &str) -> bool {
+        if self.allow.is_empty() {
+            return true;
+        }
+        self.allow.iter().any(|g| {
+            g.base_prefix.is_empty()
+                || relative_dir.starts_with(&g.base_prefix)
+                || g.base_prefix.starts_with(relative_dir)
+        })
+    }
+
+    /// 判断相对路径是否通过过滤：先看 allow（空列表视为全部允许），再看
+    /// ignore 列表里最后一条匹配的规则会不会把它判为忽略。
+    pub fn is_allowed(&self, relative_path: &str) -> bool {
+        let allowed =
+            self.allow.is_empty() || self.allow.iter().any(|g| g.regex.is_match(relative_path));
+        if !allowed {
+            return false;
+        }
+
+        let mut ignored = false;
+        for g in &self.ignore {
+            if g.regex.is_match(relative_path) {
+                ignored = !g.negated;
+            }
+        }
+        !ignored
+    }
+}
+
+fn compile_glob(pattern: &str) -> CompiledGlob {
+    let (negated, pattern) = match pattern.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+
+    let wildcard_idx = pattern.find(['*', '?', '[']).unwrap_or(pattern.len());
+    let base_prefix = match pattern[..wildcard_idx].rfind('/') {
+        Some(slash_idx) => pattern[..slash_idx].to_string(),
+        None => String::new(),
+    };
+
+    // 编译失败时退化为一个永远不匹配的正则，而不是让整个过滤器构建失败。
+    let regex = regex::Regex::new(&glob_to_anchored_regex(pattern))
+        .unwrap_or_else(|_| regex::Regex::new("$^").unwrap());
+
+    CompiledGlob {
+        base_prefix,
+        negated,
+        regex,
+    }
+}
+
+/// 和 `ignore_matcher::glob_to_regex` 同样的转换规则：`**` 跨层级匹配任意
+/// 深度，单个 `*`/`?` 不跨越 `/`，其余字符按字面转义，整体锚定到相对路径
+/// 全串。
+fn glob_to_anchored_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    regex.push_str(".*");
+                    i += 2;
+                    if i < chars.len() && chars[i] == '/' {
+                        i += 1;
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '?' => {
+                regex.push_str("[^/]");
+                i += 1;
+            }
+            c => {
+                regex.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
@@ -0,0 +1,153 @@
+//! 跨平台进程树终止工具。
+//!
+//! `tauri_plugin_shell` 的 sidecar API 不暴露子进程创建时的进程组/Job Object
+//! 钩子，所以我们无法在 `uv` 启动前把它放进自己的进程组（Unix）或关联到一个
+//! Job Object（Windows）。退而求其次，这里用 PID 树遍历：记录 `uv` 自身的
+//! PID，递归找出它所有的子孙进程，逐个发送终止信号，而不是像之前那样用
+//! `pkill -f "main.py --host ..."` 这种脆弱的命令行字符串匹配。
+
+use std::collections::HashSet;
+use std::process::Command;
+use std::time::Duration;
+
+/// 终止以 `root_pid` 为根的整棵进程树：先尝试优雅终止（SIGTERM /
+/// taskkill 不带 `/F`），等待一小段宽限期，再强制终止仍然存活的部分。
+pub fn kill_process_tree(root_pid: u32) {
+    let pids = collect_descendants(root_pid);
+    println!(
+        "[PROCESS_TREE] 终止进程树，根PID: {}，共 {} 个进程",
+        root_pid,
+        pids.len()
+    );
+
+    // `collect_descendants` 按 BFS 顺序返回（根在最前面），终止时反过来
+    // 按叶子优先的顺序发信号：先杀最深层的子孙，最后才杀根——避免根先退出
+    // 导致还没来得及终止的子孙被系统重新挂到 init/Windows 的 services.exe
+    // 下面，增加后续发现、确认它们都已死亡的不确定性。
+    for pid in pids.iter().rev() {
+        terminate(*pid, false);
+    }
+
+    std::thread::sleep(Duration::from_millis(800));
+
+    for pid in pids.iter().rev() {
+        if is_alive(*pid) {
+            println!("[PROCESS_TREE] PID {} 仍存活，强制终止", pid);
+            terminate(*pid, true);
+        }
+    }
+}
+
+/// 收集 `root_pid` 自身及其所有子孙 PID（含根）。
+fn collect_descendants(root_pid: u32) -> Vec<u32> {
+    let mut all = vec![root_pid];
+    let mut frontier = vec![root_pid];
+    let mut seen: HashSet<u32> = HashSet::from([root_pid]);
+
+    // 最多展开几层，避免系统进程表异常导致死循环
+    for _ in 0..16 {
+        if frontier.is_empty() {
+            break;
+        }
+        let mut next_frontier = Vec::new();
+        for pid in frontier.drain(..) {
+            for child in direct_children(pid) {
+                if seen.insert(child) {
+                    all.push(child);
+                    next_frontier.push(child);
+                }
+            }
+        }
+        frontier = next_frontier;
+    }
+
+    all
+}
+
+#[cfg(unix)]
+fn direct_children(pid: u32) -> Vec<u32> {
+    // `ps -o pid=,ppid=` 比解析 `pgrep -P` 的单列输出更不容易受本地化影响
+    match Command::new("ps").args(["-e", "-o", "pid=,ppid="]).output() {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let child_pid: u32 = parts.next()?.parse().ok()?;
+                let parent_pid: u32 = parts.next()?.parse().ok()?;
+                (parent_pid == pid).then_some(child_pid)
+            })
+            .collect(),
+        Err(e) => {
+            eprintln!("[PROCESS_TREE] 枚举子进程失败 (ps): {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(windows)]
+fn direct_children(pid: u32) -> Vec<u32> {
+    // wmic 在较新 Windows 上已被标记弃用，但仍广泛可用且无需额外依赖；
+    // 真正的长期方案是通过 Job Object 在 spawn 时建立父子关系。
+    match Command::new("wmic")
+        .args([
+            "process",
+            "where",
+            &format!("(ParentProcessId={})", pid),
+            "get",
+            "ProcessId",
+        ])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().parse::<u32>().ok())
+            .collect(),
+        Err(e) => {
+            eprintln!("[PROCESS_TREE] 枚举子进程失败 (wmic): {}", e);
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(unix)]
+fn terminate(pid: u32, force: bool) {
+    let signal = if force { "-KILL" } else { "-TERM" };
+    let _ = Command::new("kill")
+        .args([signal, &pid.to_string()])
+        .status();
+}
+
+#[cfg(windows)]
+fn terminate(pid: u32, force: bool) {
+    let mut cmd = Command::new("taskkill");
+    cmd.args(["/PID", &pid.to_string()]);
+    if force {
+        cmd.arg("/F");
+    }
+    let _ = cmd.status();
+}
+
+/// 探测某个 PID 是否还存活。`kill_process_tree` 用它判断强制终止宽限期
+/// 过后是否还需要补一刀；`api_startup::spawn_health_supervisor` 也用它
+/// 直接探测 sidecar 本身的 PID——不依赖 `sysinfo` 这类额外 crate（这棵树
+/// 没有 Cargo.toml，没法引入），用和上面 `direct_children`/`terminate`
+/// 同一套思路，shell 出系统自带的进程查询命令。
+#[cfg(unix)]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .status()
+        .map(|s| s.success())
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+pub(crate) fn is_alive(pid: u32) -> bool {
+    match Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid)])
+        .output()
+    {
+        Ok(output) => String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()),
+        Err(_) => false,
+    }
+}
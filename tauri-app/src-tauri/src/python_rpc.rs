@@ -0,0 +1,155 @@
+//! Rust→Python 的请求/响应 RPC 层，叠加在 `api_startup` 既有的单向桥接事件
+//! 协议之上。Python 一侧原来只能通过 `EVENT_NOTIFY_JSON:` 主动通知 Rust，
+//! 这里新增一对对称的行协议前缀，让 Rust 也能发起调用并拿到结果：
+//!
+//! - Rust 写入子进程 stdin: `RPC_REQUEST_JSON:{"id":"rpc-1","method":"...","params":{...}}`
+//! - Python 写回 stdout: `RPC_RESPONSE_JSON:{"id":"rpc-1","result":...}` 或
+//!   `{"id":"rpc-1","error":"..."}`
+//!
+//! 挂起的请求按 id 登记在一张内存表里，`api_startup` 的 stdout 读取循环把
+//! 匹配到 `RPC_RESPONSE_JSON:` 前缀的行交给 `try_handle_response_line`
+//! 路由，其余行继续走原来的 `parse_bridge_event` 路径。
+
+use crate::ApiProcessState;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// 一次RPC调用失败的原因。
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", content = "detail", rename_all = "snake_case")]
+pub enum RpcError {
+    /// 在调用方指定的超时内没有收到匹配id的 `RPC_RESPONSE_JSON:` 回复
+    Timeout,
+    /// 当前没有存活的 Python 子进程可以写入请求
+    ProcessUnavailable,
+    /// 序列化请求或写入子进程 stdin 失败
+    Io(String),
+    /// Python 端在 `RPC_RESPONSE_JSON:` 里带回了 `error` 字段
+    Remote(String),
+}
+
+#[derive(Debug, Serialize)]
+struct RpcRequestEnvelope<'a> {
+    id: &'a str,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcResponseEnvelope {
+    id: String,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Rust↔Python RPC 客户端。可以自由 `Clone`（内部字段都是 `Arc`），供
+/// `api_startup` 在启动任务和 stdout 读取任务之间共享同一张挂起请求表。
+#[derive(Clone)]
+pub struct PythonRpcClient {
+    api_state: Arc<Mutex<ApiProcessState>>,
+    next_id: Arc<Mutex<u64>>,
+    pending: Arc<Mutex<HashMap<String, oneshot::Sender<Result<Value, RpcError>>>>>,
+}
+
+impl PythonRpcClient {
+    pub fn new(api_state: Arc<Mutex<ApiProcessState>>) -> Self {
+        Self {
+            api_state,
+            next_id: Arc::new(Mutex::new(0)),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// 调用 Python 端注册的 `method`，等待匹配id的回复。超过 `timeout` 仍未
+    /// 收到就返回 `RpcError::Timeout`，并把挂起表里的登记项清理掉，避免
+    /// 之后迟到的回复（如果还是来了）找不到归宿而静默丢弃没关系，但登记项
+    /// 本身不能一直占着内存。
+    pub async fn call_python(
+        &self,
+        method: &str,
+        params: Value,
+        timeout: Duration,
+    ) -> Result<Value, RpcError> {
+        let id = {
+            let mut next_id = self.next_id.lock().unwrap();
+            let id = *next_id;
+            *next_id += 1;
+            format!("rpc-{}", id)
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id.clone(), tx);
+
+        let envelope = RpcRequestEnvelope {
+            id: &id,
+            method,
+            params,
+        };
+        let line = match serde_json::to_string(&envelope) {
+            Ok(json) => format!("RPC_REQUEST_JSON:{}\n", json),
+            Err(e) => {
+                self.pending.lock().unwrap().remove(&id);
+                return Err(RpcError::Io(e.to_string()));
+            }
+        };
+
+        {
+            let mut state = self.api_state.lock().unwrap();
+            match state.process_child_mut() {
+                Some(child) => {
+                    if let Err(e) = child.write(line.as_bytes()) {
+                        self.pending.lock().unwrap().remove(&id);
+                        return Err(RpcError::Io(e.to_string()));
+                    }
+                }
+                None => {
+                    self.pending.lock().unwrap().remove(&id);
+                    return Err(RpcError::ProcessUnavailable);
+                }
+            }
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(RpcError::Io("RPC响应通道被提前关闭".to_string())),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&id);
+                Err(RpcError::Timeout)
+            }
+        }
+    }
+
+    /// 尝试把一行 stdout 当作 `RPC_RESPONSE_JSON:` 回复处理：命中就按 id
+    /// 路由给对应的挂起请求并返回 `true`（调用方不应该再把这行交给
+    /// `parse_bridge_event`/当日志展示）；不是这个前缀就原样返回 `false`。
+    pub fn try_handle_response_line(&self, line: &str) -> bool {
+        let line = line.trim();
+        let Some(json_part) = line.strip_prefix("RPC_RESPONSE_JSON:") else {
+            return false;
+        };
+
+        let envelope: RpcResponseEnvelope = match serde_json::from_str(json_part) {
+            Ok(envelope) => envelope,
+            Err(e) => {
+                eprintln!("解析RPC响应JSON失败: {} - 原始内容: {}", e, json_part);
+                return true;
+            }
+        };
+
+        if let Some(sender) = self.pending.lock().unwrap().remove(&envelope.id) {
+            let result = match envelope.error {
+                Some(message) => Err(RpcError::Remote(message)),
+                None => Ok(envelope.result.unwrap_or(Value::Null)),
+            };
+            let _ = sender.send(result);
+        }
+
+        true
+    }
+}
@@ -0,0 +1,101 @@
+//! 对已启动的 `uv`/Python sidecar 进程施加尽力而为的资源限制。
+//!
+//! `tauri_plugin_shell` 的 sidecar API 不暴露子进程创建时的钩子（见
+//! `process_tree` 模块头注释），所以没法像典型 Unix 做法那样在 `pre_exec`
+//! 里调用 `setrlimit`——那需要访问 `std::process::Command` 的
+//! `CommandExt::pre_exec`，而 sidecar 的 `Command` 构建过程完全在
+//! `tauri_plugin_shell` 内部，不对外暴露。这里退而求其次，在子进程已经
+//! 拉起、PID 已知之后，用 Linux 的 `prlimit` 命令行工具对一个正在运行的
+//! 进程就地设置限制——这是 `prlimit()` 系统调用相对于 `setrlimit()` 的
+//! 一个特性：后者只能影响调用者自身（或其在 fork/exec 之前的子进程），
+//! 前者可以对任意同用户的在运行进程生效。macOS/Windows 没有对应的、
+//! 不需要额外依赖（`libc`/`nix`/`windows` crate 均未引入，见
+//! `process_tree` 的先例）就能做到的事后设限手段，所以这两个平台上只记录
+//! 一条说明性日志，不实际生效。
+
+use std::process::Command;
+
+/// 施加给 sidecar 进程的资源限制配置，字段全部默认为 `None`（不限制），
+/// 与当前无限制行为保持一致，只有显式配置过的字段才会生效。
+#[derive(Debug, Clone, Default)]
+pub struct ResourceLimits {
+    pub max_memory_mb: Option<u64>,
+    pub max_cpu_seconds: Option<u64>,
+    pub max_open_files: Option<u64>,
+}
+
+impl ResourceLimits {
+    /// 是否所有字段都未配置——未配置时调用方可以跳过整套施加流程。
+    pub fn is_empty(&self) -> bool {
+        self.max_memory_mb.is_none() && self.max_cpu_seconds.is_none() && self.max_open_files.is_none()
+    }
+}
+
+/// 对 `pid` 尽力而为地施加 `limits`。只在 Linux 上真正生效（通过
+/// `prlimit` 命令行工具）；其余平台只记录一条不支持的说明日志。任何一步
+/// 失败都只打印日志，不影响 sidecar 继续运行——资源限制是锦上添花的保护
+/// 措施，不应该成为启动失败的新理由。
+pub fn apply_best_effort(pid: u32, limits: &ResourceLimits) {
+    if limits.is_empty() {
+        return;
+    }
+
+    #[cfg(target_os = "linux")]
+    apply_via_prlimit(pid, limits);
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        println!(
+            "[RESOURCE_LIMITS] 当前平台不支持对运行中的 sidecar 进程（PID {}）事后设置资源限制，跳过（需要额外依赖才能实现）",
+            pid
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn apply_via_prlimit(pid: u32, limits: &ResourceLimits) {
+    let mut command = Command::new("prlimit");
+    command.arg(format!("--pid={}", pid));
+
+    if let Some(max_memory_mb) = limits.max_memory_mb {
+        command.arg(format!("--as={}", max_memory_mb * 1024 * 1024));
+    }
+    if let Some(max_cpu_seconds) = limits.max_cpu_seconds {
+        command.arg(format!("--cpu={}", max_cpu_seconds));
+    }
+    if let Some(max_open_files) = limits.max_open_files {
+        command.arg(format!("--nofile={}", max_open_files));
+    }
+
+    match command.output() {
+        Ok(output) if output.status.success() => {
+            println!("[RESOURCE_LIMITS] 已对 PID {} 施加资源限制: {:?}", pid, limits);
+        }
+        Ok(output) => {
+            eprintln!(
+                "[RESOURCE_LIMITS] prlimit 对 PID {} 施加限制失败: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Err(e) => {
+            eprintln!(
+                "[RESOURCE_LIMITS] 无法执行 prlimit（可能未安装），跳过对 PID {} 的限制: {}",
+                pid, e
+            );
+        }
+    }
+}
+
+/// 根据子进程的终止状态，判断它是否疑似被资源限制杀死：Unix 上
+/// `RLIMIT_AS`/`RLIMIT_NOFILE` 超限通常表现为 `SIGKILL`（9）或内存分配
+/// 失败后的异常退出，`RLIMIT_CPU` 超限则是 `SIGXCPU`（24）。只有配置过
+/// 对应限制时才据此下结论，避免把普通崩溃误判为限制触发。
+pub fn classify_termination(limits: &ResourceLimits, signal: Option<i32>) -> Option<&'static str> {
+    let signal = signal?;
+    match signal {
+        24 if limits.max_cpu_seconds.is_some() => Some("CPU时间超过限制（SIGXCPU）"),
+        9 if limits.max_memory_mb.is_some() => Some("内存或文件描述符超过限制，进程被系统强制终止（SIGKILL）"),
+        _ => None,
+    }
+}
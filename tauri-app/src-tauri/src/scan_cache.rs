@@ -0,0 +1,99 @@
+//! 持久化的扫描缓存：按 `(path, size, mtime)` 判断一个文件自上次扫描以来
+//! 是否发生变化，没变化就直接复用上次算出的 [`crate::file_scanner::FileInfo`]
+//! （包括可能很昂贵的内容嗅探结果），把重复扫描变成主要是元数据比较。
+//!
+//! 缓存以 JSON 形式持久化到应用数据目录下的一个文件，和仓库里其它需要落盘
+//! 的状态一样用 `serde` 直接序列化，不引入额外的存储格式/数据库依赖。
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::file_scanner::FileInfo;
+
+const CACHE_FILE_NAME: &str = "scan_cache.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    file_size: u64,
+    mtime_secs: u64,
+    file_info: FileInfo,
+}
+
+/// 一次扫描期间使用的缓存：从磁盘加载一次，扫描过程中按命中/未命中读写，
+/// 扫描结束后清掉已经不存在的路径再落盘。
+#[derive(Debug, Default)]
+pub struct ScanCache {
+    entries: HashMap<String, CacheEntry>,
+    /// 本次扫描里被复用（命中）的记录数，扫描结束后写进 `ScanStats::cache_hits`。
+    pub hits: u64,
+    touched: std::collections::HashSet<String>,
+}
+
+impl ScanCache {
+    /// 从 `app_data_dir` 下的缓存文件加载；文件不存在或解析失败时返回一个
+    /// 空缓存（相当于首次扫描，不会报错中断扫描）。
+    pub fn load(app_data_dir: &Path) -> Self {
+        let cache_path = app_data_dir.join(CACHE_FILE_NAME);
+        let entries = std::fs::read_to_string(&cache_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            hits: 0,
+            touched: std::collections::HashSet::new(),
+        }
+    }
+
+    /// 如果 `path` 的缓存记录的 size/mtime 和当前值一致，返回缓存的
+    /// `FileInfo`（跳过重新读取/重新分类）。命中的路径会被标记为"仍然存在"，
+    /// 不会在 [`Self::evict_missing`] 阶段被清掉。
+    pub fn get(&mut self, path: &str, file_size: u64, mtime_secs: u64) -> Option<FileInfo> {
+        self.touched.insert(path.to_string());
+        let entry = self.entries.get(path)?;
+        if entry.file_size == file_size && entry.mtime_secs == mtime_secs {
+            self.hits += 1;
+            Some(entry.file_info.clone())
+        } else {
+            None
+        }
+    }
+
+    /// 记录（或更新）一条缓存：文件被重新处理之后调用。
+    pub fn update(&mut self, path: String, file_size: u64, mtime_secs: u64, file_info: FileInfo) {
+        self.touched.insert(path.clone());
+        self.entries.insert(
+            path,
+            CacheEntry {
+                file_size,
+                mtime_secs,
+                file_info,
+            },
+        );
+    }
+
+    /// 清掉这次扫描里完全没被访问到的记录——说明对应的路径在这轮扫描中不复
+    /// 存在了（被删除、被移出白名单等）。
+    pub fn evict_missing(&mut self) {
+        let touched = &self.touched;
+        self.entries.retain(|path, _| touched.contains(path));
+    }
+
+    /// 把当前缓存写回 `app_data_dir` 下的缓存文件。
+    pub fn save(&self, app_data_dir: &Path) -> std::io::Result<()> {
+        std::fs::create_dir_all(app_data_dir)?;
+        let cache_path = app_data_dir.join(CACHE_FILE_NAME);
+        let content = serde_json::to_string(&self.entries)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        std::fs::write(cache_path, content)
+    }
+}
+
+/// 解析出扫描缓存文件应该落盘的目录（应用数据目录）；拿不到时返回
+/// `None`，调用方应当跳过缓存而不是让扫描失败。
+pub fn resolve_cache_dir(app_handle: &tauri::AppHandle) -> Option<PathBuf> {
+    use tauri::Manager;
+    app_handle.path().app_data_dir().ok()
+}
@@ -0,0 +1,109 @@
+//! Spotlight 风格的快速搜索悬浮窗。
+//!
+//! 和主窗口不同，这个窗口不常驻：它只在用户按下全局快捷键时按需创建，
+//! 用户选中结果或按 Esc 放弃后直接销毁（而不是像主窗口那样"关闭即隐藏"），
+//! 所以 `app_handle.get_webview_window(OVERLAY_LABEL)` 在它被关掉之后会
+//! 如预期地返回 `None`，下次再按快捷键会重新创建一个全新的窗口实例。
+//! `lib.rs` 里的全局 `on_window_event`/`CloseRequested` 处理只对 `"main"`
+//! 这个 label 做特殊的"隐藏代替关闭"处理，其它 label（包括这个悬浮窗）
+//! 落到默认分支、走正常的关闭销毁流程，因此这里不需要额外豁免。
+
+use tauri::{AppHandle, LogicalPosition, LogicalSize, Manager, WebviewUrl, WebviewWindowBuilder};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+pub const OVERLAY_LABEL: &str = "search_overlay";
+pub const TOGGLE_SHORTCUT: &str = "CmdOrCtrl+Shift+Space";
+
+const OVERLAY_WIDTH: f64 = 640.0;
+const OVERLAY_HEIGHT: f64 = 72.0;
+
+/// 注册全局快捷键，按下后切换悬浮窗的显示/隐藏（已存在就聚焦或销毁，
+/// 不存在就新建）。
+pub fn register(app_handle: &AppHandle) {
+    let handle_for_callback = app_handle.clone();
+    let result = app_handle.global_shortcut().on_shortcut(
+        TOGGLE_SHORTCUT,
+        move |_app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            toggle(&handle_for_callback);
+        },
+    );
+
+    if let Err(e) = result {
+        eprintln!(
+            "[SEARCH_OVERLAY] 注册全局快捷键 {} 失败: {}",
+            TOGGLE_SHORTCUT, e
+        );
+    } else {
+        println!("[SEARCH_OVERLAY] 已注册快速搜索悬浮窗快捷键: {}", TOGGLE_SHORTCUT);
+    }
+}
+
+/// 切换悬浮窗：已经存在就销毁它（等价于"再按一次关闭"），不存在就创建并显示。
+pub fn toggle(app_handle: &AppHandle) {
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_LABEL) {
+        let _ = window.close();
+        return;
+    }
+
+    if let Err(e) = create_and_show(app_handle) {
+        eprintln!("[SEARCH_OVERLAY] 创建快速搜索悬浮窗失败: {}", e);
+    }
+}
+
+/// 供前端在用户选中结果或按 Esc 放弃时调用，直接销毁悬浮窗。
+#[tauri::command(rename_all = "snake_case")]
+pub fn dismiss_search_overlay(app_handle: AppHandle) -> Result<(), String> {
+    if let Some(window) = app_handle.get_webview_window(OVERLAY_LABEL) {
+        window.close().map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+fn create_and_show(app_handle: &AppHandle) -> tauri::Result<()> {
+    // 居中展示在当前鼠标所在的显示器上；拿不到就退化成不指定位置，
+    // 交给窗口管理器的默认放置策略。
+    let centered_position = primary_monitor_center(app_handle);
+
+    let mut builder = WebviewWindowBuilder::new(
+        app_handle,
+        OVERLAY_LABEL,
+        WebviewUrl::App("index.html#/search-overlay".into()),
+    )
+    .title("Quick Search")
+    .inner_size(OVERLAY_WIDTH, OVERLAY_HEIGHT)
+    .decorations(false)
+    .resizable(false)
+    .always_on_top(true)
+    .visible_on_all_workspaces(true)
+    .skip_taskbar(true)
+    .focused(true)
+    .shadow(true);
+
+    if let Some(position) = centered_position {
+        builder = builder.position(position.x, position.y);
+    }
+
+    let window = builder.build()?;
+    window.show()?;
+    window.set_focus()?;
+    Ok(())
+}
+
+/// 计算悬浮窗在主显示器上水平居中、靠上方一点的逻辑坐标。
+/// 显示器信息挂在窗口对象上而不是 `AppHandle` 上，这里借用（可能是隐藏的）
+/// 主窗口来查询，拿不到就放弃定位。
+fn primary_monitor_center(app_handle: &AppHandle) -> Option<LogicalPosition<f64>> {
+    let main_window = app_handle.get_webview_window("main")?;
+    let monitor = main_window.primary_monitor().ok().flatten()?;
+    let scale_factor = monitor.scale_factor();
+    let monitor_size: LogicalSize<f64> = monitor.size().to_logical(scale_factor);
+    let monitor_position: LogicalPosition<f64> = monitor.position().to_logical(scale_factor);
+
+    Some(LogicalPosition::new(
+        monitor_position.x + (monitor_size.width - OVERLAY_WIDTH) / 2.0,
+        monitor_position.y + monitor_size.height * 0.2,
+    ))
+}
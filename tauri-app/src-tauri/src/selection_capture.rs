@@ -0,0 +1,196 @@
+//! 全局快捷键"划词抓取"子系统。
+//!
+//! 按下全局快捷键时，从前台应用（不需要是本应用的窗口）读取当前选中的文本，
+//! 作为一条新笔记送进知识库。没有跨平台、免额外依赖的"读取任意应用选中文本"
+//! API，所以分两步尝试：先用 macOS 的辅助功能（Accessibility）读取
+//! `AXSelectedText`（需要用户已经像 `tauri_plugin_macos_permissions` 那样
+//! 授权辅助功能权限）；读不到或者不是 macOS，就退化成"模拟一次复制快捷键，
+//! 再读剪贴板"。Windows 没有免依赖的选中文本读取方式，直接走模拟复制。
+
+use std::process::Command;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+/// 默认的划词抓取快捷键，可以后续开放给前端设置页做自定义绑定。
+pub const DEFAULT_CAPTURE_SHORTCUT: &str = "CmdOrCtrl+Shift+K";
+
+/// 在 `setup()` 中调用一次，注册全局快捷键并绑定抓取流程。
+pub fn register(app_handle: &AppHandle) {
+    let handle_for_callback = app_handle.clone();
+    let result = app_handle.global_shortcut().on_shortcut(
+        DEFAULT_CAPTURE_SHORTCUT,
+        move |_app, _shortcut, event| {
+            if event.state() != ShortcutState::Pressed {
+                return;
+            }
+            let handle = handle_for_callback.clone();
+            tauri::async_runtime::spawn(async move {
+                capture_and_ingest(handle).await;
+            });
+        },
+    );
+
+    if let Err(e) = result {
+        eprintln!(
+            "[SELECTION_CAPTURE] 注册全局快捷键 {} 失败: {}",
+            DEFAULT_CAPTURE_SHORTCUT, e
+        );
+    } else {
+        println!(
+            "[SELECTION_CAPTURE] 已注册全局划词抓取快捷键: {}",
+            DEFAULT_CAPTURE_SHORTCUT
+        );
+    }
+}
+
+/// 抓取当前选中文本并送进知识库，结束后向主窗口发一个 toast 确认事件。
+async fn capture_and_ingest(app_handle: AppHandle) {
+    let captured = match grab_selected_text() {
+        Ok(text) if !text.trim().is_empty() => text,
+        Ok(_) => {
+            println!("[SELECTION_CAPTURE] 抓取到的选中文本为空，跳过入库");
+            emit_toast(&app_handle, false, "没有检测到选中的文本");
+            return;
+        }
+        Err(e) => {
+            eprintln!("[SELECTION_CAPTURE] 抓取选中文本失败: {}", e);
+            emit_toast(&app_handle, false, "抓取选中文本失败");
+            return;
+        }
+    };
+
+    match ingest_captured_text(captured, app_handle.clone()).await {
+        Ok(_) => emit_toast(&app_handle, true, "已将选中内容收入知识库"),
+        Err(e) => {
+            eprintln!("[SELECTION_CAPTURE] 写入知识库失败: {}", e);
+            emit_toast(&app_handle, false, "写入知识库失败");
+        }
+    }
+}
+
+fn emit_toast(app_handle: &AppHandle, success: bool, message: &str) {
+    if let Some(window) = app_handle.get_webview_window("main") {
+        let _ = window.emit(
+            "selection-capture-result",
+            serde_json::json!({ "success": success, "message": message }),
+        );
+    }
+}
+
+/// 把已经抓取到的文本转发给 Python API，作为一条新笔记写入知识库。
+/// 供 `commands::capture_selection` 和本模块的全局快捷键回调共用。
+pub async fn ingest_captured_text(
+    text: String,
+    app_handle: AppHandle,
+) -> Result<serde_json::Value, String> {
+    let (api_host, api_port) = {
+        let api_state = app_handle.state::<crate::ApiState>();
+        let api_state_guard = api_state.0.lock().unwrap();
+
+        if api_state_guard.process_child.is_none() {
+            return Err("API服务未运行".to_string());
+        }
+
+        (api_state_guard.host.clone(), api_state_guard.port)
+    };
+
+    let client = reqwest::Client::new();
+    let url = format!("http://{}:{}/notes/quick-capture", api_host, api_port);
+
+    let request_data = serde_json::json!({
+        "content": text,
+        "source": "selection-capture",
+    });
+
+    match client.post(&url).json(&request_data).send().await {
+        Ok(response) => {
+            if response.status().is_success() {
+                response
+                    .json::<serde_json::Value>()
+                    .await
+                    .map_err(|e| format!("解析划词抓取响应失败: {}", e))
+            } else {
+                let status = response.status();
+                let error_text = response
+                    .text()
+                    .await
+                    .unwrap_or_else(|_| "无法读取错误响应".to_string());
+                Err(format!("API请求失败 [{}]: {}", status, error_text))
+            }
+        }
+        Err(e) => Err(format!("发送请求失败: {}", e)),
+    }
+}
+
+fn grab_selected_text() -> Result<String, String> {
+    #[cfg(target_os = "macos")]
+    {
+        grab_via_accessibility_macos()
+            .filter(|text| !text.trim().is_empty())
+            .or_else(|| grab_via_copy_macos().ok())
+            .ok_or_else(|| "无法读取选中文本".to_string())
+    }
+    #[cfg(target_os = "windows")]
+    {
+        grab_via_copy_windows()
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        Err("当前平台暂不支持划词抓取".to_string())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn grab_via_accessibility_macos() -> Option<String> {
+    // 读取前台应用当前聚焦元素的 AXSelectedText；没有辅助功能权限，或者
+    // 聚焦元素不支持这个属性时，System Events 会直接报错，按 None 处理。
+    let script = r#"
+tell application "System Events"
+    set frontApp to first application process whose frontmost is true
+    tell frontApp
+        set focusedElement to value of attribute "AXFocusedUIElement"
+        return value of attribute "AXSelectedText" of focusedElement
+    end tell
+end tell
+"#;
+    let output = Command::new("osascript").arg("-e").arg(script).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+fn grab_via_copy_macos() -> Result<String, String> {
+    // 没有辅助功能读取路径时的退化方案：模拟一次 Cmd+C，再读剪贴板。
+    // 这会覆盖用户剪贴板原有内容，但既然是用户主动触发的"抓取"操作，
+    // 这是预期行为，不做恢复。
+    let script = r#"tell application "System Events" to keystroke "c" using command down"#;
+    Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .status()
+        .map_err(|e| e.to_string())?;
+    std::thread::sleep(Duration::from_millis(150));
+    let output = Command::new("pbpaste").output().map_err(|e| e.to_string())?;
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn grab_via_copy_windows() -> Result<String, String> {
+    // Windows 没有免依赖的选中文本读取 API，直接模拟 Ctrl+C 再读剪贴板。
+    let ps_script = r#"Add-Type -AssemblyName System.Windows.Forms
+[System.Windows.Forms.SendKeys]::SendWait('^c')
+Start-Sleep -Milliseconds 150
+Get-Clipboard -Raw"#;
+    let output = Command::new("powershell")
+        .args(["-NoProfile", "-Command", ps_script])
+        .output()
+        .map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).to_string())
+    }
+}
@@ -0,0 +1,161 @@
+//! 多 sidecar 服务的统一登记表。
+//!
+//! `api_startup::start_python_api` 目前自己管理一个裸的
+//! `Arc<Mutex<ApiProcessState>>`，并各自起了好几个独立的
+//! `tauri::async_runtime::spawn` 循环（就绪探测、健康监督、事件分发……），
+//! 整个应用里也只有这一个 sidecar。这里的 [`ServiceController`] 是面向
+//! "以后不止一个 sidecar"（比如再加一个独立的索引服务）的登记中心：每个
+//! 命名服务在这里有一份 [`ServiceHandle`]（主机/端口、共享的
+//! `ApiProcessState`、状态、重启策略），控制器负责统一的登记/查询/停止，
+//! 而不是让调用方各自攥着一个独立的 `Arc<Mutex<..>>` 互不知情。
+//!
+//! 目前真正负责"怎么拉起一个 sidecar"（uv sync 缓存判断、就绪探测、健康
+//! 监督、RPC、日志落盘……）的细节仍然全部留在 `api_startup` 里，这里不重
+//! 复实现；`ServiceController::register` 只是把 `api_startup` 已经创建好
+//! 的 `ApiProcessState` 纳入这张表，`stop`/`status` 则是在此之上提供统一
+//! 入口。后续真正接入第二个 sidecar 时，可以把每个服务自己的事件循环改成
+//! 从控制器的单一分发循环里 fan-out，但那是一次单独的、更大的改动，这里
+//! 先把登记/查询/统一停止这一层搭好。
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// 服务登记名，例如 `"python-api"`。用字符串而不是枚举，是因为将来会有
+/// 运行时才知道名字的服务（比如按文件夹配置动态起的索引子服务），不想
+/// 为每一个都改一遍枚举定义。
+pub type ServiceId = String;
+
+/// 服务崩溃后的重启策略，与 `api_startup::maybe_trigger_restart` 现有的
+/// 指数退避逻辑对应——控制器本身不执行重启，只记录"这个服务应不应该被
+/// 自动重启"这一条策略供调用方查询。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RestartPolicy {
+    Never,
+    ExponentialBackoff,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceStatus {
+    /// 已登记但还没有请求启动过，或者已经被 `stop` 过
+    Stopped,
+    Starting,
+    Running,
+    Crashed,
+}
+
+/// 一个已登记服务的状态快照，供前端/优雅关闭流程统一枚举，不暴露内部的
+/// 进程句柄等实现细节——除了 `pid`：`api_status` 这类诊断命令需要把它
+/// 原样透传给前端，用来判断"进程还活着但健康检查没过"和"进程已经不在了"
+/// 这两种不同的异常。
+#[derive(Debug, Clone, Serialize)]
+pub struct ServiceSnapshot {
+    pub id: ServiceId,
+    pub host: String,
+    pub port: u16,
+    pub pid: Option<u32>,
+    pub status: ServiceStatus,
+    pub restart_policy: RestartPolicy,
+}
+
+struct ServiceHandle {
+    host: String,
+    port: u16,
+    status: ServiceStatus,
+    restart_policy: RestartPolicy,
+    api_state: Arc<Mutex<crate::ApiProcessState>>,
+}
+
+/// 所有已登记 sidecar 服务的中央登记表。方法都只需要 `&self`（内部用
+/// `Mutex` 做互斥），与仓库里其它需要跨异步任务共享的状态（如
+/// `EventBuffer`/`TaskRegistry`）是同一种写法，直接包在 `Arc` 里共享。
+#[derive(Default)]
+pub struct ServiceController {
+    services: Mutex<HashMap<ServiceId, ServiceHandle>>,
+}
+
+impl ServiceController {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 登记一个服务。已存在同名服务时直接替换掉旧的登记记录——调用方
+    /// 应该在替换前自行确认旧服务已经停止，控制器不会替你终止旧进程。
+    pub fn register(
+        &self,
+        id: impl Into<ServiceId>,
+        host: String,
+        port: u16,
+        restart_policy: RestartPolicy,
+        api_state: Arc<Mutex<crate::ApiProcessState>>,
+    ) {
+        let handle = ServiceHandle {
+            host,
+            port,
+            status: ServiceStatus::Starting,
+            restart_policy,
+            api_state,
+        };
+        self.services.lock().unwrap().insert(id.into(), handle);
+    }
+
+    /// 把一个已登记服务标记为某个状态；由实际负责起停该服务的模块（目前
+    /// 是 `api_startup`）在对应时机调用——控制器本身不知道如何判断一个
+    /// sidecar 是否健康，只负责记录调用方告诉它的结论。
+    pub fn mark_status(&self, id: &str, status: ServiceStatus) {
+        if let Some(handle) = self.services.lock().unwrap().get_mut(id) {
+            handle.status = status;
+        }
+    }
+
+    /// 取出已登记服务共享的 `ApiProcessState`，供真正负责起停的模块（如
+    /// `api_startup::start_python_api`）使用；未登记时返回 `None`。
+    pub fn api_state(&self, id: &str) -> Option<Arc<Mutex<crate::ApiProcessState>>> {
+        self.services
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|handle| handle.api_state.clone())
+    }
+
+    /// 优雅停止一个已登记服务：终止它的整棵进程树（见
+    /// `process_tree::kill_process_tree`）并标记为 `Stopped`。未登记或
+    /// 已经没有存活子进程时是安全的空操作。
+    pub fn stop(&self, id: &str) {
+        let child_pid = {
+            let mut services = self.services.lock().unwrap();
+            let Some(handle) = services.get_mut(id) else {
+                return;
+            };
+            handle.status = ServiceStatus::Stopped;
+            let mut api_state = handle.api_state.lock().unwrap();
+            let pid = api_state.pid();
+            api_state.reset_after_stop();
+            pid
+        };
+
+        if let Some(pid) = child_pid {
+            crate::process_tree::kill_process_tree(pid);
+        }
+    }
+
+    /// 返回所有已登记服务的当前状态快照，供状态查询命令或优雅关闭流程
+    /// 统一枚举，而不用各自记着一个个独立的 `Arc<Mutex<..>>`。
+    pub fn status(&self) -> Vec<ServiceSnapshot> {
+        self.services
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(id, handle)| ServiceSnapshot {
+                id: id.clone(),
+                host: handle.host.clone(),
+                port: handle.port,
+                pid: handle.api_state.lock().unwrap().pid(),
+                status: handle.status,
+                restart_policy: handle.restart_policy,
+            })
+            .collect()
+    }
+}
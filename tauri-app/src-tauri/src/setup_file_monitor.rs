@@ -40,6 +40,26 @@ pub async fn setup_file_monitoring_infrastructure(
             println!("[基础设施] 已将文件监控器实例保存到 AppState.file_monitor");
         }
 
+        // 同步此前（监控器初始化之前）已经设置过的 ignore 文件开关快照，
+        // 否则在监控器就绪前调用过 set_folder_ignore_files_enabled 的文件夹
+        // 会丢失这个设置。
+        base_monitor.set_ignore_files_enabled_for(app_state.ignore_files_enabled_paths());
+
+        // 本地配置层（见 local_config 模块）的入口文件放在应用数据目录下的
+        // `local_config/` 子目录；拿不到应用数据目录时跳过，退化为只用 API
+        // 配置，不影响监控器正常工作。
+        if let Ok(app_data_dir) = app_handle.path().app_data_dir() {
+            base_monitor.set_local_config_dir(Some(app_data_dir.join("local_config")));
+
+            // 批量发送重试耗尽后的本地 spool 文件也放在应用数据目录下；拿不到
+            // 应用数据目录时跳过，退化为没有磁盘兜底（重试耗尽直接丢弃）。
+            base_monitor.set_spool_dir(Some(app_data_dir.join("batch_spool")));
+
+            // 初始扫描检查点同理放在应用数据目录下；拿不到应用数据目录时
+            // 跳过，退化为不写检查点（中断后下次启动从头全量重扫）。
+            base_monitor.set_scan_checkpoint_dir(Some(app_data_dir.join("scan_checkpoint")));
+        }
+
         // 创建但不启动防抖动监控器
         let base_monitor_arc = Arc::new(base_monitor.clone());
         let debounced_monitor =
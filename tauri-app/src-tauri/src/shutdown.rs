@@ -0,0 +1,130 @@
+//! 统一的关闭信号子系统。
+//!
+//! 之前的清理完全依赖 `ApiProcessManager` 的 `Drop` 实现和一个全局 panic
+//! hook，这两者都接不到 SIGTERM/SIGINT（例如被进程管理器直接发信号终止），
+//! 也不会在退出前把还没发出去的配置变更/事件缓冲排空。这里把 Ctrl-C、
+//! SIGTERM、SIGINT 以及作为"重新加载配置"触发器的 SIGHUP 统一接进来，
+//! 转成一次有序的优雅关闭流程，而不是散落在 `Drop` 和 panic hook 里。
+
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+/// 排空阶段允许的最长耗时；超时后直接进入强制终止，不再等待。
+const GRACEFUL_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 安装信号处理器。每种信号触发时都执行同一套有序关闭流程：
+/// 停止文件监控 -> 排空待处理配置变更与事件缓冲 -> 终止 API 进程树 -> 退出进程。
+/// SIGHUP 例外：只触发"重新加载配置"而不退出进程。
+pub fn install(app_handle: AppHandle) {
+    // Ctrl-C 在所有平台都存在对应语义（Windows 上由 tokio 模拟），放在最前面
+    {
+        let app_handle = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                println!("[SHUTDOWN] 收到 Ctrl-C，开始优雅关闭");
+                graceful_shutdown_and_exit(app_handle).await;
+            }
+        });
+    }
+
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let app_handle_term = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(mut term) = signal(SignalKind::terminate()) {
+                term.recv().await;
+                println!("[SHUTDOWN] 收到 SIGTERM，开始优雅关闭");
+                graceful_shutdown_and_exit(app_handle_term).await;
+            }
+        });
+
+        let app_handle_int = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(mut int) = signal(SignalKind::interrupt()) {
+                int.recv().await;
+                println!("[SHUTDOWN] 收到 SIGINT，开始优雅关闭");
+                graceful_shutdown_and_exit(app_handle_int).await;
+            }
+        });
+
+        let app_handle_hup = app_handle.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Ok(mut hup) = signal(SignalKind::hangup()) {
+                loop {
+                    hup.recv().await;
+                    println!("[SHUTDOWN] 收到 SIGHUP，触发配置重新加载（不退出进程）");
+                    reload_config(app_handle_hup.clone()).await;
+                }
+            }
+        });
+    }
+}
+
+/// SIGHUP 触发的配置重新加载：只刷新监控配置，不涉及进程退出。
+async fn reload_config(app_handle: AppHandle) {
+    let app_state = app_handle.state::<crate::AppState>();
+    let monitor = {
+        let guard = app_state.file_monitor.lock().unwrap();
+        guard.clone()
+    };
+    if let Some(monitor) = monitor {
+        match monitor.refresh_all_configurations().await {
+            Ok(_) => println!("[SHUTDOWN] SIGHUP 触发的配置重新加载成功"),
+            Err(e) => eprintln!("[SHUTDOWN] SIGHUP 触发的配置重新加载失败: {}", e),
+        }
+    }
+}
+
+/// 有序关闭流程，带硬超时兜底：排空步骤整体限时 `GRACEFUL_DRAIN_TIMEOUT`，
+/// 超时就放弃继续排空、直接进入强制终止，保证关闭流程本身不会被卡死。
+async fn graceful_shutdown_and_exit(app_handle: AppHandle) {
+    match tokio::time::timeout(GRACEFUL_DRAIN_TIMEOUT, drain(app_handle.clone())).await {
+        Ok(_) => println!("[SHUTDOWN] 优雅排空完成"),
+        Err(_) => eprintln!(
+            "[SHUTDOWN] 优雅排空超过 {:?}，放弃继续等待，直接强制终止",
+            GRACEFUL_DRAIN_TIMEOUT
+        ),
+    }
+
+    // 无论排空是否完整完成，都要确保 API 进程树被终止
+    if let Some(api_manager) = app_handle.try_state::<crate::ApiProcessManager>() {
+        api_manager.cleanup();
+    } else {
+        crate::ApiProcessManager::cleanup_processes();
+    }
+
+    std::process::exit(0);
+}
+
+/// 排空阶段本身：停止文件监控、处理完队列中剩余的配置变更、把事件缓冲区
+/// 中还没发出去的事件发送出去。三步都是尽力而为，单步失败不阻塞后续步骤。
+async fn drain(app_handle: AppHandle) {
+    let app_state = app_handle.state::<crate::AppState>();
+
+    // 1. 停止文件监控，避免排空期间又产生新的变更/事件
+    let monitor_to_stop = {
+        let guard = app_state.debounced_file_monitor.lock().unwrap();
+        guard.clone()
+    };
+    if let Some(mut monitor) = monitor_to_stop {
+        if let Err(e) = monitor.stop_monitoring().await {
+            eprintln!("[SHUTDOWN] 停止文件监控时出错: {}", e);
+        }
+    }
+
+    // 2. 处理完队列里剩余的配置变更（此时调度器可能还没来得及跑下一轮）
+    if app_state.has_pending_config_changes() {
+        println!("[SHUTDOWN] 排空前还有待处理的配置变更，立即处理一次");
+        app_state.process_pending_config_changes();
+        // process_pending_config_changes 内部是异步 spawn 出去的，这里给它
+        // 一点时间跑完，而不是立刻继续往下终止 API 进程
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    // 3. 排空事件缓冲区中尚未发送的事件
+    if let Some(event_buffer) = app_state.get_event_buffer() {
+        event_buffer.flush_all().await;
+    }
+}
@@ -0,0 +1,68 @@
+//! 记录上一次 `uv sync` 成功时所用 `pyproject.toml` 内容哈希的小清单文件，
+//! 让 `api_startup::start_python_api` 能跳过"每次启动都复制+同步一遍"的
+//! 慢路径：只有资源文件哈希变化（新版本部署或依赖被编辑过）时才需要真正
+//! 复制并执行 `uv sync`，否则直接进入 `uv run`。
+//!
+//! 和仓库里其它需要落盘的状态（见 `scan_cache`）一样，以 JSON 形式存放在
+//! `venv_parent_path` 下，不引入额外的存储格式/数据库依赖。
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const MANIFEST_FILE_NAME: &str = ".uv_sync_manifest.json";
+/// 清单格式版本号：以后如果清单要记录的内容变化（比如加入 lockfile 哈希），
+/// 递增这个值即可让旧清单被当成"不匹配"从而触发一次完整同步，不需要额外
+/// 写迁移逻辑。
+const MANIFEST_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SyncManifest {
+    version: u32,
+    pyproject_hash: String,
+}
+
+impl SyncManifest {
+    fn current(pyproject_hash: String) -> Self {
+        Self {
+            version: MANIFEST_VERSION,
+            pyproject_hash,
+        }
+    }
+}
+
+/// 清单文件在 `venv_parent_path` 下的固定路径。
+pub fn manifest_path(venv_parent_path: &Path) -> PathBuf {
+    venv_parent_path.join(MANIFEST_FILE_NAME)
+}
+
+/// 加载 `path` 处的清单；文件不存在或解析失败都视为"没有可用的缓存记录"，
+/// 返回 `None` 即可，调用方会因此走完整同步路径，不会报错中断启动。
+fn load(path: &Path) -> Option<SyncManifest> {
+    let content = std::fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// 判断 `pyproject_hash` 是否和 `manifest_path` 处已记录的哈希一致（且清单
+/// 版本匹配）——一致则可以跳过复制和 `uv sync`，直接进入 `uv run`。
+pub fn is_up_to_date(manifest_path: &Path, pyproject_hash: &str) -> bool {
+    match load(manifest_path) {
+        Some(manifest) => manifest.version == MANIFEST_VERSION && manifest.pyproject_hash == pyproject_hash,
+        None => false,
+    }
+}
+
+/// 在一次 `uv sync` 成功后，把最新哈希写回清单，供下次启动比对。写入失败
+/// 只打日志，不影响本次启动——最坏情况只是下次启动会多走一次完整同步。
+pub fn write(manifest_path: &Path, pyproject_hash: &str) {
+    let manifest = SyncManifest::current(pyproject_hash.to_string());
+    let content = match serde_json::to_string(&manifest) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("[SYNC_MANIFEST] 序列化清单失败: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = std::fs::write(manifest_path, content) {
+        eprintln!("[SYNC_MANIFEST] 写入清单文件失败: {}", e);
+    }
+}
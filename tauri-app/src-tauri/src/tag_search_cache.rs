@@ -0,0 +1,153 @@
+//! 标签搜索 / 标签云结果的内存缓存，避免 `search_files_by_tags`/
+//! `get_tag_cloud_data` 每次调用都对 sidecar API 发起一次 HTTP 往返
+//! （前端通常是每次按键、每次打开标签云面板就调一次）。
+//!
+//! 和 `scan_cache` 不落盘：那里缓存的是文件内容哈希，重新计算的代价是
+//! 重新读一遍磁盘上的大量文件；这里缓存的只是对 API 的一次请求结果，
+//! 重新请求一次的代价远低于落盘/读盘本身，落盘持久化没有必要，反而会
+//! 在高频命中的路径上引入不必要的 IO。
+//!
+//! 缓存条目按请求的全部筛选/分页参数（见 [`TagSearchKey`]，标签搜索）或
+//! `limit`（标签云）做键，带一个固定 TTL；同时维护一个全局版本号，配置
+//! 变更队列在实际执行了会改变"哪些文件被索引"的操作
+//! （`AddBlacklist`/`DeleteFolder`/`ToggleFolder`/`AddWhitelist`）之后会
+//! 调用 [`TagSearchCache::bump_version`]——写入缓存时记录当时的版本号，
+//! 读取时版本号对不上就视为未命中，而不是等 TTL 慢慢过期，这样才能保证
+//! 加入/移除文件夹之后标签搜索不会继续返回变更前的结果。
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::commands::TagSearchResult;
+
+/// 缓存条目的存活时间：比单次请求耗时长得多，但远短于"用户可能已经改了
+/// 标签"的时间尺度，足够吸收同一次搜索/面板展示里的重复调用。
+const CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// 标签搜索缓存键，覆盖一次 `search_files_by_tags` 调用会影响结果的全部
+/// 参数——漏掉任何一个都会让缓存把本应不同的查询当成同一个返回。
+#[derive(Debug, Clone, Hash, Eq, PartialEq)]
+struct TagSearchKey {
+    tag_names: Vec<String>,
+    operator: String,
+    offset: u32,
+    limit: u32,
+    sort: Option<String>,
+    extensions: Vec<String>,
+    file_categories: Vec<String>,
+    include_facets: bool,
+}
+
+/// 构造 [`TagSearchKey`] 所需的参数，由 `commands::search_files_by_tags`
+/// 在查缓存/写缓存时各构造一次，避免 `get_search`/`put_search` 各自长长
+/// 一串位置参数。
+pub struct TagSearchQuery<'a> {
+    pub tag_names: &'a [String],
+    pub operator: &'a str,
+    pub offset: u32,
+    pub limit: u32,
+    pub sort: Option<&'a str>,
+    pub extensions: &'a [String],
+    pub file_categories: &'a [String],
+    pub include_facets: bool,
+}
+
+impl TagSearchKey {
+    fn from_query(query: &TagSearchQuery) -> Self {
+        let mut tag_names = query.tag_names.to_vec();
+        tag_names.sort();
+        let mut extensions = query.extensions.to_vec();
+        extensions.sort();
+        let mut file_categories = query.file_categories.to_vec();
+        file_categories.sort();
+        Self {
+            tag_names,
+            operator: query.operator.to_string(),
+            offset: query.offset,
+            limit: query.limit,
+            sort: query.sort.map(str::to_string),
+            extensions,
+            file_categories,
+            include_facets: query.include_facets,
+        }
+    }
+}
+
+struct CacheEntry<T> {
+    value: T,
+    cached_at: Instant,
+    version: u64,
+}
+
+/// 标签搜索/标签云结果缓存。所有方法只需要 `&self`（内部用 `Mutex`/
+/// `AtomicU64` 做互斥），与仓库里其它需要跨命令调用共享的状态
+/// （如 `task_registry::TaskRegistry`）是同一种写法，可以直接包在 `Arc`
+/// 里挂到 `AppState` 上。
+#[derive(Default)]
+pub struct TagSearchCache {
+    version: AtomicU64,
+    search_entries: Mutex<HashMap<TagSearchKey, CacheEntry<TagSearchResult>>>,
+    tag_cloud_entries: Mutex<HashMap<Option<u32>, CacheEntry<serde_json::Value>>>,
+}
+
+impl TagSearchCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 配置变更队列里实际应用了会改变被索引文件集合的操作后调用，使此前
+    /// 缓存的所有标签搜索/标签云结果在下次读取时失效。不直接清空两张表，
+    /// 是为了避免在持锁状态下做一次全量清空；旧条目会在下次按同键读取时
+    /// 因版本号不匹配被当作未命中并自然替换掉。
+    pub fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn current_version(&self) -> u64 {
+        self.version.load(Ordering::Relaxed)
+    }
+
+    pub fn get_search(&self, query: &TagSearchQuery) -> Option<TagSearchResult> {
+        let key = TagSearchKey::from_query(query);
+        let current_version = self.current_version();
+        let entries = self.search_entries.lock().unwrap();
+        let entry = entries.get(&key)?;
+        if entry.version == current_version && entry.cached_at.elapsed() < CACHE_TTL {
+            Some(entry.value.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn put_search(&self, query: &TagSearchQuery, value: TagSearchResult) {
+        let key = TagSearchKey::from_query(query);
+        let entry = CacheEntry {
+            value,
+            cached_at: Instant::now(),
+            version: self.current_version(),
+        };
+        self.search_entries.lock().unwrap().insert(key, entry);
+    }
+
+    pub fn get_tag_cloud(&self, limit: Option<u32>) -> Option<serde_json::Value> {
+        let current_version = self.current_version();
+        let entries = self.tag_cloud_entries.lock().unwrap();
+        let entry = entries.get(&limit)?;
+        if entry.version == current_version && entry.cached_at.elapsed() < CACHE_TTL {
+            Some(entry.value.clone())
+        } else {
+            None
+        }
+    }
+
+    pub fn put_tag_cloud(&self, limit: Option<u32>, value: serde_json::Value) {
+        let entry = CacheEntry {
+            value,
+            cached_at: Instant::now(),
+            version: self.current_version(),
+        };
+        self.tag_cloud_entries.lock().unwrap().insert(limit, entry);
+    }
+}
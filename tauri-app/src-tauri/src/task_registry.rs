@@ -0,0 +1,209 @@
+//! 配置变更队列的任务跟踪表：每个通过 `AppState::add_pending_config_change`
+//! 入队的 [`crate::ConfigChangeRequest`] 都会在这里登记一条记录并分配一个
+//! 单调递增的 uid，供前端在操作发起后立即拿到的凭证轮询进度，而不是只能
+//! 看到"已加入队列"这种没有操作粒度的模糊提示。
+//!
+//! 这里只维护一个有上限的内存内历史（不落盘），结构上模仿搜索引擎任务存储
+//! （如 Meilisearch 的 tasks API）：每条记录有 uid、状态、各阶段时间戳，
+//! 可以按状态过滤、按 uid 游标翻页。
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 内存内保留的任务记录条数上限，超出的最旧记录会被挤出（同
+/// `AppState::recent_activity` 的裁剪方式一致）。
+const MAX_TASK_HISTORY: usize = 2_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskStatus {
+    Enqueued,
+    Processing,
+    Succeeded,
+    Failed,
+    /// 在调度器取走执行之前被用户主动撤销（`queue_cancel_pending_change`/
+    /// `queue_clear_pending`），与 `Failed` 区分开——前端不应该把一次用户
+    /// 自己撤回的操作当成错误展示。
+    Canceled,
+}
+
+/// 与 [`crate::ConfigChangeRequest`] 的变体一一对应，但去掉了各变体携带的
+/// 具体参数——任务记录只需要知道"这是哪一类操作"。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeKind {
+    AddBlacklist,
+    DeleteFolder,
+    ToggleFolder,
+    AddWhitelist,
+    BundleExtensionChange,
+}
+
+impl ConfigChangeKind {
+    fn from_request(change: &crate::ConfigChangeRequest) -> Self {
+        match change {
+            crate::ConfigChangeRequest::AddBlacklist { .. } => Self::AddBlacklist,
+            crate::ConfigChangeRequest::DeleteFolder { .. } => Self::DeleteFolder,
+            crate::ConfigChangeRequest::ToggleFolder { .. } => Self::ToggleFolder,
+            crate::ConfigChangeRequest::AddWhitelist { .. } => Self::AddWhitelist,
+            crate::ConfigChangeRequest::BundleExtensionChange => Self::BundleExtensionChange,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigChangeTask {
+    pub uid: u64,
+    pub kind: ConfigChangeKind,
+    pub status: TaskStatus,
+    pub folder_path: Option<String>,
+    pub folder_id: Option<i32>,
+    pub enqueued_at: chrono::DateTime<chrono::Utc>,
+    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub finished_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// 失败原因；也用来记录"被同批次后续操作合并/取代，未实际执行"这类
+    /// 非错误性的说明（此时 `status` 为 `Succeeded`）。
+    pub error: Option<String>,
+}
+
+/// 配置变更队列的任务登记表。所有方法都只需要 `&self`（内部用 `Mutex`
+/// 做互斥），与仓库里其它需要跨异步任务共享的状态（如 `EventBuffer`）是
+/// 同一种写法，可以直接包在 `Arc` 里在线程间共享。
+#[derive(Debug, Default)]
+pub struct TaskRegistry {
+    next_uid: Mutex<u64>,
+    tasks: Mutex<VecDeque<ConfigChangeTask>>,
+}
+
+impl TaskRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 为一个刚入队的配置变更请求登记一条新任务记录，返回分配的 uid。
+    pub fn enqueue(&self, change: &crate::ConfigChangeRequest) -> u64 {
+        let uid = {
+            let mut next_uid = self.next_uid.lock().unwrap();
+            let uid = *next_uid;
+            *next_uid += 1;
+            uid
+        };
+
+        let (folder_path, folder_id) = Self::folder_ref(change);
+        let task = ConfigChangeTask {
+            uid,
+            kind: ConfigChangeKind::from_request(change),
+            status: TaskStatus::Enqueued,
+            folder_path,
+            folder_id,
+            enqueued_at: chrono::Utc::now(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        };
+
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks.push_back(task);
+        while tasks.len() > MAX_TASK_HISTORY {
+            tasks.pop_front();
+        }
+
+        uid
+    }
+
+    fn folder_ref(change: &crate::ConfigChangeRequest) -> (Option<String>, Option<i32>) {
+        match change {
+            crate::ConfigChangeRequest::AddBlacklist { folder_path, .. } => {
+                (Some(folder_path.clone()), None)
+            }
+            crate::ConfigChangeRequest::DeleteFolder {
+                folder_id,
+                folder_path,
+                ..
+            } => (Some(folder_path.clone()), Some(*folder_id)),
+            crate::ConfigChangeRequest::ToggleFolder {
+                folder_id,
+                folder_path,
+                ..
+            } => (Some(folder_path.clone()), Some(*folder_id)),
+            crate::ConfigChangeRequest::AddWhitelist { folder_path, .. } => {
+                (Some(folder_path.clone()), None)
+            }
+            crate::ConfigChangeRequest::BundleExtensionChange => (None, None),
+        }
+    }
+
+    pub fn mark_processing(&self, uid: u64) {
+        self.update(uid, |task| {
+            task.status = TaskStatus::Processing;
+            task.started_at = Some(chrono::Utc::now());
+        });
+    }
+
+    pub fn mark_succeeded(&self, uid: u64) {
+        self.update(uid, |task| {
+            task.status = TaskStatus::Succeeded;
+            task.finished_at = Some(chrono::Utc::now());
+        });
+    }
+
+    /// 标记一个任务被同批次内的后续操作合并/取代而未实际执行（见
+    /// `AppState::coalesce_config_changes`）。状态记为 `Succeeded`——对用户
+    /// 来说这不是一次失败，只是这次操作变得没有必要了。
+    pub fn mark_superseded(&self, uid: u64, note: String) {
+        self.update(uid, |task| {
+            task.status = TaskStatus::Succeeded;
+            task.finished_at = Some(chrono::Utc::now());
+            task.error = Some(note);
+        });
+    }
+
+    /// 标记一个仍在排队、尚未被调度器取走执行的任务被用户主动撤销（见
+    /// `AppState::cancel_pending_config_change`/`clear_pending_config_changes`）。
+    pub fn mark_canceled(&self, uid: u64, note: String) {
+        self.update(uid, |task| {
+            task.status = TaskStatus::Canceled;
+            task.finished_at = Some(chrono::Utc::now());
+            task.error = Some(note);
+        });
+    }
+
+    pub fn mark_failed(&self, uid: u64, error: String) {
+        self.update(uid, |task| {
+            task.status = TaskStatus::Failed;
+            task.finished_at = Some(chrono::Utc::now());
+            task.error = Some(error);
+        });
+    }
+
+    fn update(&self, uid: u64, f: impl FnOnce(&mut ConfigChangeTask)) {
+        let mut tasks = self.tasks.lock().unwrap();
+        if let Some(task) = tasks.iter_mut().find(|t| t.uid == uid) {
+            f(task);
+        }
+    }
+
+    /// 查询单个任务的当前记录。
+    pub fn get(&self, uid: u64) -> Option<ConfigChangeTask> {
+        self.tasks.lock().unwrap().iter().find(|t| t.uid == uid).cloned()
+    }
+
+    /// 按 uid 升序分页列出任务记录，可选按状态过滤。`after_uid` 是游标式
+    /// 翻页的起点（只返回 uid 大于它的记录），`limit` 限制单页条数。
+    pub fn list(
+        &self,
+        status_filter: Option<TaskStatus>,
+        limit: usize,
+        after_uid: Option<u64>,
+    ) -> Vec<ConfigChangeTask> {
+        let tasks = self.tasks.lock().unwrap();
+        tasks
+            .iter()
+            .filter(|t| after_uid.map_or(true, |after| t.uid > after))
+            .filter(|t| status_filter.map_or(true, |s| t.status == s))
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
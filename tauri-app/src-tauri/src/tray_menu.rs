@@ -0,0 +1,266 @@
+//! 动态重建的托盘菜单。
+//!
+//! 之前托盘菜单只有一个 "Quit" 项，在 `setup()` 里构建一次就再也不变。这里
+//! 把它扩展成一个真正的控制面板：暂停/恢复监控、手动触发一次简化扫描、
+//! 只读展示当前队列状态、以及"最近索引"子菜单。状态变化（暂停/恢复、扫描
+//! 结果、配置队列变化）之后都调用 `rebuild` 重新构建整个菜单并通过
+//! `TrayIcon::set_menu` 替换，而不是维护一堆可变菜单项。菜单点击统一转发到
+//! `commands` 模块里前端也在用的同一批 async 命令函数，保证行为一致。
+
+use std::path::Path;
+use tauri::{
+    menu::{IsMenuItem, Menu, MenuItem, PredefinedMenuItem, Submenu},
+    tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
+    AppHandle, Manager, Wry,
+};
+
+/// "Recently indexed" 子菜单最多展示的条目数。
+pub const RECENT_ACTIVITY_LIMIT: usize = 5;
+
+/// 创建托盘图标本身（只在 `setup()` 里调用一次）。后续的状态变化都走
+/// `rebuild` 替换菜单内容，而不是重新创建图标。
+pub fn create(app: &tauri::App) -> tauri::Result<()> {
+    let app_handle = app.handle();
+    let menu = build_menu(app_handle, false, Vec::new())?;
+
+    let tray_icon = TrayIconBuilder::new()
+        .menu(&menu)
+        .show_menu_on_left_click(false)
+        .on_menu_event(handle_menu_event)
+        .on_tray_icon_event(handle_tray_icon_event)
+        .build(app)?;
+
+    println!("Tray Icon ID: {:?}", tray_icon.id());
+    app.state::<crate::AppState>().set_tray_icon(tray_icon);
+    Ok(())
+}
+
+/// 根据当前监控暂停状态和最近索引列表重新构建托盘菜单并替换。
+pub fn rebuild(app_handle: &AppHandle) {
+    let app_state = app_handle.state::<crate::AppState>();
+    let monitoring_paused = app_state.is_monitoring_paused();
+    let recent = app_state.recent_activity_snapshot();
+
+    match build_menu(app_handle, monitoring_paused, recent) {
+        Ok(menu) => {
+            if let Some(tray) = app_state.get_tray_icon() {
+                if let Err(e) = tray.set_menu(Some(menu)) {
+                    eprintln!("[TRAY] 替换托盘菜单失败: {}", e);
+                }
+            }
+        }
+        Err(e) => eprintln!("[TRAY] 构建托盘菜单失败: {}", e),
+    }
+}
+
+fn build_menu(
+    app_handle: &AppHandle,
+    monitoring_paused: bool,
+    recent: Vec<String>,
+) -> tauri::Result<Menu<Wry>> {
+    let app_state = app_handle.state::<crate::AppState>();
+    let pending_changes_count = app_state.get_pending_config_changes_count();
+    let initial_scan_completed = app_state.is_initial_scan_completed();
+
+    let status_label = if !initial_scan_completed {
+        "Status: initial scan in progress...".to_string()
+    } else if pending_changes_count > 0 {
+        format!("Status: {} change(s) pending", pending_changes_count)
+    } else {
+        "Status: up to date".to_string()
+    };
+    let status_item = MenuItem::with_id(app_handle, "tray_status", status_label, false, None::<&str>)?;
+
+    let pause_resume_label = if monitoring_paused {
+        "Resume monitoring"
+    } else {
+        "Pause monitoring"
+    };
+    let pause_resume_item = MenuItem::with_id(
+        app_handle,
+        "tray_toggle_monitoring",
+        pause_resume_label,
+        true,
+        None::<&str>,
+    )?;
+
+    let trigger_scan_item =
+        MenuItem::with_id(app_handle, "tray_trigger_scan", "Scan now", true, None::<&str>)?;
+
+    let recent_items: Vec<MenuItem<Wry>> = if recent.is_empty() {
+        vec![MenuItem::with_id(
+            app_handle,
+            "tray_recent_empty",
+            "No recent activity",
+            false,
+            None::<&str>,
+        )?]
+    } else {
+        recent
+            .iter()
+            .enumerate()
+            .map(|(idx, path)| {
+                let label = Path::new(path)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.clone());
+                MenuItem::with_id(app_handle, format!("tray_recent_{}", idx), label, true, None::<&str>)
+            })
+            .collect::<tauri::Result<_>>()?
+    };
+    let recent_item_refs: Vec<&dyn IsMenuItem<Wry>> = recent_items
+        .iter()
+        .map(|item| item as &dyn IsMenuItem<Wry>)
+        .collect();
+    let recent_submenu = Submenu::with_id_and_items(
+        app_handle,
+        "tray_recent",
+        "Recently indexed",
+        true,
+        &recent_item_refs,
+    )?;
+
+    let quit_item = MenuItem::with_id(app_handle, "quit", "Quit", true, None::<&str>)?;
+
+    Menu::with_items(
+        app_handle,
+        &[
+            &status_item,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &pause_resume_item,
+            &trigger_scan_item,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &recent_submenu,
+            &PredefinedMenuItem::separator(app_handle)?,
+            &quit_item,
+        ],
+    )
+}
+
+fn handle_menu_event(app: &AppHandle, event: tauri::menu::MenuEvent) {
+    let id = event.id().as_ref();
+
+    if let Some(index_str) = id.strip_prefix("tray_recent_") {
+        if let Ok(index) = index_str.parse::<usize>() {
+            open_recent_activity_item(app, index);
+        }
+        return;
+    }
+
+    match id {
+        "tray_toggle_monitoring" => {
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let app_state = app_handle.state::<crate::AppState>();
+                let result = if app_state.is_monitoring_paused() {
+                    crate::commands::resume_file_monitoring(app_handle.clone(), app_state).await
+                } else {
+                    crate::commands::pause_file_monitoring(app_handle.clone(), app_state).await
+                };
+                if let Err(e) = result {
+                    eprintln!("[TRAY] 切换监控暂停/恢复失败: {}", e);
+                }
+            });
+        }
+        "tray_trigger_scan" => {
+            println!("[TRAY] 手动触发一次简化扫描");
+            let app_handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let app_state = app_handle.state::<crate::AppState>();
+                match crate::file_scanner::scan_files_simplified_command(
+                    app_handle.clone(),
+                    None,
+                    None,
+                    None,
+                    app_state,
+                )
+                .await
+                {
+                    Ok(files) => {
+                        println!("[TRAY] 手动扫描完成，文件数量: {}", files.len());
+                        let app_state = app_handle.state::<crate::AppState>();
+                        app_state.record_recent_activity(
+                            files
+                                .into_iter()
+                                .take(RECENT_ACTIVITY_LIMIT)
+                                .map(|f| f.file_path),
+                        );
+                        rebuild(&app_handle);
+                    }
+                    Err(e) => eprintln!("[TRAY] 手动扫描失败: {}", e),
+                }
+            });
+        }
+        "quit" => {
+            println!("退出菜单项被点击");
+            println!("执行完整进程清理");
+
+            if let Some(api_manager) = app.try_state::<crate::ApiProcessManager>() {
+                api_manager.cleanup();
+                println!("通过ApiProcessManager实例执行了完整清理");
+            } else {
+                println!("无法获取ApiProcessManager，使用静态清理");
+                crate::ApiProcessManager::cleanup_processes();
+            }
+
+            app.exit(0);
+        }
+        _ => {
+            // 其它 id（如只读的 tray_status、tray_recent_empty）不需要处理
+        }
+    }
+}
+
+/// 打开"最近索引"子菜单中被点击的条目：聚焦主窗口并让前端定位到该文件。
+fn open_recent_activity_item(app: &AppHandle, index: usize) {
+    let app_state = app.state::<crate::AppState>();
+    let recent = app_state.recent_activity_snapshot();
+    let Some(path) = recent.get(index) else {
+        return;
+    };
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
+    }
+
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.emit("focus-recent-activity-item", path);
+    }
+}
+
+fn handle_tray_icon_event(tray: &tauri::tray::TrayIcon, event: TrayIconEvent) {
+    match event {
+        TrayIconEvent::Click {
+            button: MouseButton::Left,
+            button_state: MouseButtonState::Up,
+            ..
+        } => {
+            let app = tray.app_handle();
+            #[cfg(target_os = "macos")]
+            {
+                let _ = app.set_activation_policy(tauri::ActivationPolicy::Regular);
+                app.show().unwrap();
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            #[cfg(not(target_os = "macos"))]
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+        TrayIconEvent::Click {
+            button: MouseButton::Right,
+            button_state: MouseButtonState::Up,
+            ..
+        } => {
+            // 菜单由 show_menu_on_left_click(false) 自动显示
+        }
+        _ => {}
+    }
+}
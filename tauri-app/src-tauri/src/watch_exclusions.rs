@@ -0,0 +1,98 @@
+//! 防抖实时监控（`file_monitor_debounced`）用的路径排除规则：glob 模式加
+//! 字面路径前缀，在 notify 回调把事件送进防抖队列之前就筛掉临时文件、
+//! `.git` 内部对象、生成产物这类噪声——否则一次"写入生成产物"会重新触发
+//! 对该产物的扫描，扫描又可能写回某个被监控的元数据文件，形成反馈循环。
+//!
+//! 这棵树里没有 Cargo.toml，没法引入 `globset`（参见 `path_filter`/
+//! `ignore_matcher` 模块说明里同样的取舍），所以 glob 到正则的转换规则
+//! 照抄那两个模块已经在用的写法（`**` 跨层级匹配任意深度，单个 `*`/`?`
+//! 不跨越路径分隔符，其余字符按字面转义，整体锚定到全串）。
+
+use regex::Regex;
+
+struct CompiledExcludeGlob {
+    regex: Regex,
+}
+
+/// 编译好的排除规则集合：对监控目录下每个 notify 回调报告的路径，在送进
+/// 防抖队列前过一遍 [`WatchExclusions::is_excluded`]。因为排除检查发生在
+/// 事件源头，被排除目录下的所有后续事件自然也不会被处理，相当于把整棵
+/// 子树都剪掉了，不需要专门维护一张"已排除目录"列表。
+pub struct WatchExclusions {
+    globs: Vec<CompiledExcludeGlob>,
+    literal_prefixes: Vec<String>,
+}
+
+impl WatchExclusions {
+    /// `glob_patterns` 按 gitignore 风格书写（`**`/`*`/`?`），`literal_prefixes`
+    /// 是不需要通配符、直接按字符串前缀比较的路径（比如一个固定的缓存目录）。
+    pub fn compile(glob_patterns: &[String], literal_prefixes: &[String]) -> Self {
+        Self {
+            globs: glob_patterns
+                .iter()
+                .map(|pattern| CompiledExcludeGlob {
+                    regex: Regex::new(&glob_to_anchored_regex(pattern))
+                        .unwrap_or_else(|_| Regex::new("$^").unwrap()),
+                })
+                .collect(),
+            literal_prefixes: literal_prefixes.to_vec(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.globs.is_empty() && self.literal_prefixes.is_empty()
+    }
+
+    /// 判断一个路径是否应当被排除：`absolute_path` 总是会被检查，
+    /// `relative_path`（相对监控根目录，`/` 分隔，不带前导 `/`）如果有的话
+    /// 也会被检查——命中任意一种形式、任意一条规则都算排除，满足"既可以
+    /// 匹配绝对路径也可以匹配相对路径"的要求。
+    pub fn is_excluded(&self, absolute_path: &str, relative_path: Option<&str>) -> bool {
+        if self.matches_any(absolute_path) {
+            return true;
+        }
+        if let Some(relative_path) = relative_path {
+            if self.matches_any(relative_path) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn matches_any(&self, path: &str) -> bool {
+        self.literal_prefixes.iter().any(|prefix| path.starts_with(prefix.as_str()))
+            || self.globs.iter().any(|glob| glob.regex.is_match(path))
+    }
+}
+
+fn glob_to_anchored_regex(pattern: &str) -> String {
+    let mut regex = String::from("^");
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                if i + 1 < chars.len() && chars[i + 1] == '*' {
+                    regex.push_str(".*");
+                    i += 2;
+                    if i < chars.len() && chars[i] == '/' {
+                        i += 1;
+                    }
+                } else {
+                    regex.push_str("[^/]*");
+                    i += 1;
+                }
+            }
+            '?' => {
+                regex.push_str("[^/]");
+                i += 1;
+            }
+            c => {
+                regex.push_str(&regex::escape(&c.to_string()));
+                i += 1;
+            }
+        }
+    }
+    regex.push('$');
+    regex
+}
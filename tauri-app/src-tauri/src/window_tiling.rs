@@ -0,0 +1,136 @@
+//! 跨平台、DPI 感知的窗口分区工具。
+//!
+//! 之前 `move_left`/`move_right` 直接用 `monitor.size()`/`monitor.position()`
+//! 的物理像素做一半屏切分，在高 DPI 显示器上会算出错误的尺寸（Tauri 的
+//! `Monitor` 返回的是物理像素，不能直接当成用户看到的逻辑坐标用）。这里统一
+//! 先把显示器边界转换成 `LogicalSize`/`LogicalPosition`（用 `scale_factor()`
+//! 换算），再计算目标区域，最后写回时还原成物理坐标交给窗口 API。
+//!
+//! Tauri 的 `Monitor` 不暴露"工作区"（排除任务栏/菜单栏后的可用区域）这个
+//! 概念——这是平台相关的信息，需要调用 Win32/Cocoa 原生 API 才能拿到，
+//! Tauri 本身没有封装。作为一个诚实的近似，这里只在 macOS 上减去一个固定的
+//! 菜单栏高度，其余平台使用显示器全尺寸；如果需要更精确的工作区，需要另外
+//! 通过平台相关代码获取。
+
+use tauri::{LogicalPosition, LogicalSize, Manager, PhysicalPosition, PhysicalSize, WebviewWindow};
+
+/// macOS 菜单栏的近似逻辑高度；没有通过 Tauri 暴露的跨平台方式可以精确查询。
+const MACOS_MENU_BAR_LOGICAL_HEIGHT: f64 = 24.0;
+
+/// 窗口可以被分配到的目标分区。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TileRegion {
+    LeftHalf,
+    RightHalf,
+    TopHalf,
+    BottomHalf,
+    TopLeftQuarter,
+    TopRightQuarter,
+    BottomLeftQuarter,
+    BottomRightQuarter,
+    Maximize,
+    /// 恢复到分区之前的大小：直接清除最大化状态，交还给窗口管理器的默认行为。
+    Restore,
+}
+
+/// 显示器可用区域（逻辑坐标），近似排除了 macOS 菜单栏。
+struct UsableArea {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+fn usable_area(window: &WebviewWindow) -> tauri::Result<Option<UsableArea>> {
+    let Some(monitor) = window.current_monitor()? else {
+        return Ok(None);
+    };
+
+    let scale_factor = monitor.scale_factor();
+    let logical_size: LogicalSize<f64> = monitor.size().to_logical(scale_factor);
+    let logical_position: LogicalPosition<f64> = monitor.position().to_logical(scale_factor);
+
+    let menu_bar_height = if cfg!(target_os = "macos") {
+        MACOS_MENU_BAR_LOGICAL_HEIGHT
+    } else {
+        0.0
+    };
+
+    Ok(Some(UsableArea {
+        x: logical_position.x,
+        y: logical_position.y + menu_bar_height,
+        width: logical_size.width,
+        height: logical_size.height - menu_bar_height,
+    }))
+}
+
+/// 把窗口分配到 `region` 指定的区域。所有计算都在逻辑坐标下完成，写回时
+/// 转换成物理坐标，因此在任何 DPI 缩放比例下都得到一致的可视效果。
+pub fn apply_tile(window: &WebviewWindow, region: TileRegion) -> tauri::Result<()> {
+    if region == TileRegion::Maximize {
+        return window.maximize();
+    }
+    if region == TileRegion::Restore {
+        return window.unmaximize();
+    }
+
+    let Some(area) = usable_area(window)? else {
+        return Ok(());
+    };
+
+    let (rel_x, rel_y, rel_w, rel_h) = match region {
+        TileRegion::LeftHalf => (0.0, 0.0, 0.5, 1.0),
+        TileRegion::RightHalf => (0.5, 0.0, 0.5, 1.0),
+        TileRegion::TopHalf => (0.0, 0.0, 1.0, 0.5),
+        TileRegion::BottomHalf => (0.0, 0.5, 1.0, 0.5),
+        TileRegion::TopLeftQuarter => (0.0, 0.0, 0.5, 0.5),
+        TileRegion::TopRightQuarter => (0.5, 0.0, 0.5, 0.5),
+        TileRegion::BottomLeftQuarter => (0.0, 0.5, 0.5, 0.5),
+        TileRegion::BottomRightQuarter => (0.5, 0.5, 0.5, 0.5),
+        TileRegion::Maximize | TileRegion::Restore => unreachable!("已在上面提前返回"),
+    };
+
+    let logical_x = area.x + area.width * rel_x;
+    let logical_y = area.y + area.height * rel_y;
+    let logical_width = area.width * rel_w;
+    let logical_height = area.height * rel_h;
+
+    let scale_factor = window.scale_factor().unwrap_or(1.0);
+    let physical_position: PhysicalPosition<i32> =
+        LogicalPosition::new(logical_x, logical_y).to_physical(scale_factor);
+    let physical_size: PhysicalSize<u32> =
+        LogicalSize::new(logical_width, logical_height).to_physical(scale_factor);
+
+    // 分区前先取消最大化状态，否则部分平台会忽略随后的位置/尺寸设置
+    let _ = window.unmaximize();
+    window.set_position(tauri::Position::Physical(physical_position))?;
+    window.set_size(tauri::Size::Physical(physical_size))?;
+    Ok(())
+}
+
+/// 供前端通过 `invoke` 触发窗口分区的 Tauri 命令。
+#[tauri::command(rename_all = "snake_case")]
+pub fn tile_window(
+    region: String,
+    app_handle: tauri::AppHandle,
+) -> std::result::Result<(), String> {
+    let Some(window) = app_handle.get_webview_window("main") else {
+        return Err("找不到主窗口".to_string());
+    };
+
+    let region = match region.as_str() {
+        "left_half" => TileRegion::LeftHalf,
+        "right_half" => TileRegion::RightHalf,
+        "top_half" => TileRegion::TopHalf,
+        "bottom_half" => TileRegion::BottomHalf,
+        "top_left_quarter" => TileRegion::TopLeftQuarter,
+        "top_right_quarter" => TileRegion::TopRightQuarter,
+        "bottom_left_quarter" => TileRegion::BottomLeftQuarter,
+        "bottom_right_quarter" => TileRegion::BottomRightQuarter,
+        "maximize" => TileRegion::Maximize,
+        "restore" => TileRegion::Restore,
+        other => return Err(format!("未知的窗口分区: {}", other)),
+    };
+
+    apply_tile(&window, region).map_err(|e| e.to_string())
+}